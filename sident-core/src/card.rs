@@ -1,9 +1,18 @@
-use crate::{codec::SICodec, errors::DeserializeCardPersonalDataError, extract_fixed};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    charset,
+    errors::{DeserializeCardPersonalDataError, SerializeCardPersonalDataError},
+    extract_fixed,
+};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 #[cfg_attr(feature = "ts-rs", ts(export))]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 /// Personal data of the runner
 ///
 /// Source: SPORTident.CardPersonalData
@@ -31,7 +40,7 @@ impl CardPersonalData {
     ///
     /// Source: SPORTident.Communication.Communication._cardParseCard6PersonalData
     pub fn deserialize_card_6(data: &[u8; 204]) -> Result<Self, DeserializeCardPersonalDataError> {
-        let data = SICodec::replace_printer_charset_bytes(data);
+        let data = charset::replace_printer_charset_bytes(data);
 
         let surname_bytes = &extract_fixed!(&data, 0x00..0x13);
         let first_name_bytes = &extract_fixed!(&data, 0x14..0x27);
@@ -46,8 +55,8 @@ impl CardPersonalData {
         let gender_bytes = &extract_fixed!(&data, 0xC0..0xC3);
         let birthdate_bytes = &extract_fixed!(&data, 0xC4..0xCB);
 
-        fn d(buf: &[u8]) -> Result<String, std::string::FromUtf8Error> {
-            SICodec::decode_iso_8859_1(buf)
+        fn d(buf: &[u8]) -> Result<String, alloc::string::FromUtf8Error> {
+            charset::decode_iso_8859_1(buf)
         }
 
         let surname = d(surname_bytes)?;
@@ -63,10 +72,12 @@ impl CardPersonalData {
         let birthdate = d(birthdate_bytes)?;
 
         fn t(str: String) -> Option<String> {
+            // fields are padded with spaces, never written bytes are 0xEE (î)
+            let str = str.trim_end_matches(['\0', '\u{EE}']).trim();
             if str.is_empty() {
                 return None;
             }
-            return Some(str);
+            return Some(str.to_string());
         }
 
         return Ok(Self {
@@ -99,14 +110,15 @@ impl CardPersonalData {
             }
         }
 
-        if data.len() > 104 {
+        // pCard has the longest personal data (144 bytes)
+        if data.len() > 144 {
             return Err(DeserializeCardPersonalDataError::DataTooLong);
         }
 
         // replace the printer charset bytes
-        let data = SICodec::replace_printer_charset_bytes(&data);
+        let data = charset::replace_printer_charset_bytes(&data);
         // decode bytes to string via iso8859-1
-        let mut decoded = SICodec::decode_iso_8859_1(&data)?;
+        let mut decoded = charset::decode_iso_8859_1(&data)?;
 
         // null terminator support
         if let Some(pos) = decoded.find('\0') {
@@ -129,8 +141,8 @@ impl CardPersonalData {
 
         let mut fields: Vec<&str> = decoded.split(';').collect();
 
-        let garbage = SICodec::decode_iso_8859_1(&[0xEE, 0xEE])?;
-        let garbage_single = SICodec::decode_iso_8859_1(&[0xEE])?;
+        let garbage = charset::decode_iso_8859_1(&[0xEE, 0xEE])?;
+        let garbage_single = charset::decode_iso_8859_1(&[0xEE])?;
 
         if let Some(f0) = fields.get_mut(0) {
             if f0.contains(&garbage) {
@@ -169,13 +181,140 @@ impl CardPersonalData {
             country: nonempty_or_none(fields[10]),
         })
     }
+
+    /// Encodes one text field to the card bytes (ISO-8859-1 + printer charset)
+    fn encode_field(field: &Option<String>) -> Result<Vec<u8>, SerializeCardPersonalDataError> {
+        let field = field.as_deref().unwrap_or("").trim();
+        if field.contains(';') {
+            return Err(SerializeCardPersonalDataError::ContainsSeparator);
+        }
+        let encoded = charset::encode_iso_8859_1(field)
+            .ok_or(SerializeCardPersonalDataError::InvalidCharacter)?;
+        return Ok(charset::encode_printer_charset_bytes(&encoded));
+    }
+
+    /// Serializes `CardPersonalData` to SI8+ format. Reverse of `deserialize`.
+    ///
+    /// The fields are separated by a semicolon. Trailing empty fields are left out.
+    /// If the data are shorter than `len`, they are terminated with 0x00 and the rest is filled with 0xEE.
+    ///
+    /// * `len` - Size of the personal data area of the card
+    ///
+    /// # Example
+    /// ```
+    /// use sident_core::card::CardPersonalData;
+    ///
+    /// let data = CardPersonalData {
+    ///     first_name: Some("Jan".into()),
+    ///     last_name: Some("Novák".into()),
+    ///     phone: None,
+    ///     city: None,
+    ///     club: Some("OK Praha".into()),
+    ///     country: None,
+    ///     birthdate: None,
+    ///     email: None,
+    ///     gender: None,
+    ///     street: None,
+    ///     zipcode: None,
+    /// };
+    /// let bytes = data.serialize(24).unwrap();
+    /// assert_eq!(&bytes[..21], b"Jan;Nov\xA0k;;;OK Praha;");
+    /// assert_eq!(&bytes[21..], &[0x00, 0xEE, 0xEE]);
+    ///
+    /// let back = CardPersonalData::deserialize(&bytes).unwrap();
+    /// assert_eq!(back.last_name.as_deref(), Some("Novák"));
+    /// assert_eq!(back.club.as_deref(), Some("OK Praha"));
+    /// ```
+    pub fn serialize(&self, len: usize) -> Result<Vec<u8>, SerializeCardPersonalDataError> {
+        if self.first_name.as_deref().unwrap_or("").trim().is_empty()
+            || self.last_name.as_deref().unwrap_or("").trim().is_empty()
+        {
+            return Err(SerializeCardPersonalDataError::RequiredFieldsAreEmpty);
+        }
+
+        // same order as in `deserialize`
+        let fields = [
+            &self.first_name,
+            &self.last_name,
+            &self.gender,
+            &self.birthdate,
+            &self.club,
+            &self.email,
+            &self.phone,
+            &self.city,
+            &self.street,
+            &self.zipcode,
+            &self.country,
+        ];
+
+        let mut encoded_fields = fields
+            .iter()
+            .map(|field| Self::encode_field(field))
+            .collect::<Result<Vec<Vec<u8>>, _>>()?;
+
+        while encoded_fields.last().is_some_and(|field| field.is_empty()) {
+            encoded_fields.pop();
+        }
+
+        let mut data: Vec<u8> = Vec::new();
+        for field in encoded_fields {
+            data.extend_from_slice(&field);
+            data.push(b';');
+        }
+
+        if data.len() > len {
+            return Err(SerializeCardPersonalDataError::DataTooLong);
+        }
+
+        if data.len() < len {
+            data.push(0x00);
+        }
+        data.resize(len, 0xEE);
+
+        return Ok(data);
+    }
+
+    /// Serializes `CardPersonalData` to SI6 format. Reverse of `deserialize_card_6`.
+    ///
+    /// Every field has a fixed size and is padded with spaces.
+    /// The user ID (0x50..0x5F) is not a part of `CardPersonalData`, so it is filled with 0xEE.
+    pub fn serialize_card_6(&self) -> Result<[u8; 204], SerializeCardPersonalDataError> {
+        let mut data = [0xEE; 204];
+
+        // (field, start, end) - same layout as in `deserialize_card_6`
+        let fields = [
+            (&self.last_name, 0x00, 0x13),
+            (&self.first_name, 0x14, 0x27),
+            (&self.country, 0x28, 0x2B),
+            (&self.club, 0x2C, 0x4F),
+            (&self.phone, 0x60, 0x6F),
+            (&self.email, 0x70, 0x93),
+            (&self.street, 0x94, 0xA7),
+            (&self.city, 0xA8, 0xB7),
+            (&self.zipcode, 0xB8, 0xBF),
+            (&self.gender, 0xC0, 0xC3),
+            (&self.birthdate, 0xC4, 0xCB),
+        ];
+
+        for (field, start, end) in fields {
+            let encoded = Self::encode_field(field)?;
+            let slot = &mut data[start..=end];
+            if encoded.len() > slot.len() {
+                return Err(SerializeCardPersonalDataError::DataTooLong);
+            }
+            slot.fill(b' ');
+            slot[..encoded.len()].copy_from_slice(&encoded);
+        }
+
+        return Ok(data);
+    }
 }
 
 /// Source: SPORTident.CardType
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 #[cfg_attr(feature = "ts-rs", ts(export))]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CardType {
     Card5,
     Card6,
@@ -223,8 +362,8 @@ impl CardType {
     }
 }
 
-impl std::fmt::Display for CardType {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for CardType {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             CardType::Card5 => write!(f, "Card 5"),
             CardType::Card6 => write!(f, "Card 6"),
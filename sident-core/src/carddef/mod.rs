@@ -0,0 +1,147 @@
+use crate::{
+    card::CardPersonalData,
+    errors::{DeserializeCardPersonalDataError, FeedBlockError, SerializeCardPersonalDataError},
+    punch::Punch,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt::Debug;
+
+/// EXPERIMENTAL! pCard has not been tested yet.
+pub mod pcard;
+pub mod si10;
+/// EXPERIMENTAL! SI5 has not been tested yet.
+pub mod si5;
+/// EXPERIMENTAL! SI6 has not been tested yet.
+pub mod si6;
+pub mod si8;
+/// EXPERIMENTAL! SI9 has not been tested yet.
+pub mod si9;
+pub mod siac;
+/// EXPERIMENTAL! tCard has not been tested yet.
+pub mod tcard;
+
+/// EXPERIMENTAL! SI11 has not been tested yet.
+pub mod si11 {
+    /// Card11 is a passive card with active feedback. Thanks to the feedback Card11 has battery and shares the structure with SIAC
+    pub type Card11Def = super::siac::ActiveCardDef;
+}
+
+/// EXPERIMENTAL! ComCardUp has not been tested yet.
+pub mod comcardup {
+    pub type ComCardUpDef = super::si8::Card8Def;
+}
+
+/// EXPERIMENTAL! fCard has not been tested yet.
+pub mod fcard {
+    /// fCard shares the structure (including the training records) with tCard
+    pub type FCardDef = super::tcard::TCardDef;
+}
+
+/// EXPERIMENTAL! ComCardPro has not been tested yet.
+pub mod comcardpro {
+    pub type ComCardProDef = super::si10::Card10Def;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockNeededIntention {
+    CardPersonalData,
+    Punches,
+    CardExclusives,
+}
+
+/// Command used for reading the blocks of the card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadoutCommand {
+    /// `GetSICard5` (0xB1) - the whole card is one block
+    GetSICard5,
+    /// `GetSICard6` (0xE1)
+    GetSICard6,
+    /// `GetSICardNewer` (0xEF) - SI8 and newer
+    GetSICardNewer,
+}
+
+/// Continuous part of a card block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub block: u8,
+    /// Offset in the block
+    pub offset: u8,
+    pub len: u8,
+}
+
+impl MemoryRegion {
+    pub const fn new(block: u8, offset: u8, len: u8) -> Self {
+        return Self { block, offset, len };
+    }
+}
+
+/// Fills the sub second of the start/finish punch from its reserve record (Card 10/11, SIAC).
+/// The reserve records are in block 3, which is needed only for `BlockNeededIntention::CardExclusives`,
+/// so without the exclusives the start and finish have no sub second.
+///
+/// * `punch` - Start/finish punch
+/// * `reserve` - Raw reserve record, `None` if the block with the reserve was not read
+pub(crate) fn with_reserve_subsecond(
+    punch: Option<Punch>,
+    reserve: Option<&[u8; 4]>,
+) -> Option<Punch> {
+    let (mut punch, reserve) = match (punch, reserve) {
+        (Some(punch), Some(reserve)) => (punch, reserve),
+        (punch, _) => return punch,
+    };
+    punch.punch_time.subsecond = punch.subsecond_from_reserve(reserve);
+    return Some(punch);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockNeededResult {
+    Need(u8),
+    NoNeed,
+}
+
+pub trait CardDefinition: Debug {
+    const HAS_CARD_EXCLUSIVES: bool = false;
+    /// Command used for reading the blocks of the card
+    const READOUT_COMMAND: ReadoutCommand = ReadoutCommand::GetSICardNewer;
+    /// Regions of the card holding the personal data (in order).
+    /// Empty if writing the personal data is not supported.
+    const PERSONAL_DATA_REGIONS: &'static [MemoryRegion] = &[];
+    type CardExclusivesType;
+    fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
+        return None;
+    }
+    fn has_exclusives(&self) -> bool {
+        return Self::HAS_CARD_EXCLUSIVES;
+    }
+
+    fn new_empty() -> Self;
+    fn get_siid(&self) -> Option<u32>;
+    fn get_punch_count(&self) -> Option<u8>;
+    /// Note that SI5 does not support CardPersonalData
+    fn get_personal_data(
+        &self,
+    ) -> Option<Result<CardPersonalData, DeserializeCardPersonalDataError>>;
+    /// Check punch (or clear punch, if there is no check). `Some(None)` if the card has neither.
+    fn get_clear_check(&self) -> Option<Option<Punch>>;
+    fn get_start(&self) -> Option<Option<Punch>>;
+    fn get_finish(&self) -> Option<Option<Punch>>;
+    fn feed_block(&mut self, block_id: u8, block_buffer: &[u8; 128]) -> Result<(), FeedBlockError>;
+    /// Raw blocks fed to the definition (block number -> data).
+    /// Every fed block is kept, also the ones which could not be parsed.
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]>;
+    /// Why read every block when you can ask the CardDef what block is needed?
+    fn block_needed(&self, intention: &BlockNeededIntention) -> BlockNeededResult;
+    fn has_block(&self, block_id: u8) -> bool;
+    fn get_punches(&self) -> Option<Vec<Punch>>;
+    /// Serializes the personal data, so they can be written to `PERSONAL_DATA_REGIONS`
+    fn serialize_personal_data(
+        data: &CardPersonalData,
+    ) -> Result<Vec<u8>, SerializeCardPersonalDataError> {
+        let len = Self::PERSONAL_DATA_REGIONS
+            .iter()
+            .map(|region| region.len as usize)
+            .sum();
+        return data.serialize(len);
+    }
+    // TODO: more methods
+}
@@ -0,0 +1,386 @@
+/*
+    SPORTident pCard Memory Structure
+
+    Every integer is big endian encoded unless said differently in the desc.
+
+    ---- BLOCK 0x00 ----
+    0x00..0x03  Unique device ID
+    0x04..0x07  Start of record - 0xEA 0xEA 0xEA 0xEA
+    0x08..0x0B  Clear/Check (punch)
+    0x0C..0x0F  Start (punch)
+    0x10..0x13  Finish (punch)
+    0x14..0x15  Last visited control
+    0x16        Punch pointer - punch count
+    0x18..0x1B  SIID - SI3, SI2, SI1, SI0 - SI2..SI0 makes up the SIID (card number)
+    0x1C        Prod date MONTH
+    0x1D        Prod date YEAR (2000+x)
+    0x20..0x7F  Card personal data - PART1
+    ----            ----
+    ---- BLOCK 0x01 ----
+    0x00..0x2F  Card personal data - PART2
+    0x30..0x7F  Punches (20 punches)
+    ----            ----
+
+    The header is the same as the Card 8 header, but the personal data are longer (144 bytes)
+    and there is space only for 20 punches.
+*/
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    card::CardPersonalData,
+    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition},
+    errors::{DeserializeBlockError, DeserializeCardPersonalDataError},
+    extract_fixed,
+    punch::Punch,
+};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "PCardBlock0.ts"))]
+#[derive(Debug, Clone)]
+struct Block0 {
+    uid: u32,
+    clear_check: Punch,
+    start: Option<Punch>,
+    finish: Option<Punch>,
+    last_visited: u16,
+    punch_count: u8,
+    siid: u32,
+    prod_month: u8,
+    prod_year: u8,
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
+    #[cfg_attr(feature = "ts-rs", ts(type = "[number; 96]"))]
+    card_personal_data1: [u8; 96],
+    personal_data_finished: bool,
+}
+
+impl Block0 {
+    pub fn deserialize(data: [u8; 128]) -> Result<Self, DeserializeBlockError> {
+        let uid = u32::from_be_bytes(extract_fixed!(&data, 0x00..0x03));
+        let clear_check = Punch::deserialize(&extract_fixed!(&data, 0x08..0x0B))?;
+        let start = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x0C..0x0F))?;
+        let finish = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x10..0x13))?;
+        let last_visited = u16::from_be_bytes(extract_fixed!(&data, 0x14..0x15));
+        let punch_count = data[0x16];
+        let siid = u32::from_be_bytes([0, data[0x19], data[0x1A], data[0x1B]]);
+        let prod_month = data[0x1C];
+        let prod_year = data[0x1D];
+        let card_personal_data1 = extract_fixed!(&data, 0x20..0x7F);
+        let personal_data_finished =
+            [card_personal_data1[94], card_personal_data1[95]] == [0x00, 0x00];
+
+        return Ok(Self {
+            uid,
+            clear_check,
+            start,
+            finish,
+            last_visited,
+            punch_count,
+            siid,
+            prod_month,
+            prod_year,
+            card_personal_data1,
+            personal_data_finished,
+        });
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "PCardBlock1.ts"))]
+#[derive(Debug, Clone)]
+struct Block1 {
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
+    #[cfg_attr(feature = "ts-rs", ts(type = "[number; 48]"))]
+    card_personal_data2: [u8; 48],
+    punches: Vec<Punch>,
+}
+
+impl Block1 {
+    pub fn deserialize(data: [u8; 128]) -> Result<Self, DeserializeBlockError> {
+        let card_personal_data2 = extract_fixed!(&data, 0x00..0x2F);
+        let punches_bytes = extract_fixed!(&data, 0x30..0x7F);
+
+        let mut punches: Vec<Punch> = Vec::new();
+        for punch_chunk in punches_bytes.chunks(4) {
+            let punch_chunk: &[u8; 4] = punch_chunk.try_into().unwrap();
+            if *punch_chunk == [0xEE, 0xEE, 0xEE, 0xEE] {
+                break;
+            }
+            punches.push(Punch::deserialize(punch_chunk)?);
+        }
+
+        return Ok(Self {
+            card_personal_data2,
+            punches,
+        });
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug)]
+pub struct PCardDef {
+    block0: Option<Block0>,
+    block1: Option<Block1>,
+    /// Every block fed to the definition, also the ones which could not be parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    raw_blocks: BTreeMap<u8, [u8; 128]>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug)]
+pub struct PCardExclusives {
+    pub uid: u32,
+    pub last_visited_station_code: u16,
+    pub prod_date_month: u8,
+    pub prod_date_year: u32,
+}
+
+impl CardDefinition for PCardDef {
+    const HAS_CARD_EXCLUSIVES: bool = true;
+    type CardExclusivesType = PCardExclusives;
+    fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
+        let block0 = self.block0.as_ref()?;
+
+        return Some(Self::CardExclusivesType {
+            uid: block0.uid,
+            last_visited_station_code: block0.last_visited,
+            prod_date_month: block0.prod_month,
+            prod_date_year: 2000 + block0.prod_year as u32,
+        });
+    }
+
+    fn new_empty() -> Self {
+        Self {
+            block0: None,
+            block1: None,
+            raw_blocks: BTreeMap::new(),
+        }
+    }
+
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]> {
+        &self.raw_blocks
+    }
+
+    fn has_block(&self, block_id: u8) -> bool {
+        match block_id {
+            0 => self.block0.is_some(),
+            1 => self.block1.is_some(),
+            _ => false,
+        }
+    }
+
+    fn get_personal_data(
+        &self,
+    ) -> Option<Result<CardPersonalData, DeserializeCardPersonalDataError>> {
+        let block0 = &self.block0.as_ref()?;
+        let mut personal_data_buffer: Vec<u8> = Vec::new();
+        personal_data_buffer.extend_from_slice(&block0.card_personal_data1);
+
+        if !block0.personal_data_finished {
+            let block1 = &self.block1.as_ref()?;
+            personal_data_buffer.extend_from_slice(&block1.card_personal_data2);
+        }
+
+        return Some(CardPersonalData::deserialize(&personal_data_buffer));
+    }
+
+    fn get_punches(&self) -> Option<Vec<Punch>> {
+        let block1 = &self.block1.as_ref()?;
+
+        Some(block1.punches.clone())
+    }
+
+    fn get_punch_count(&self) -> Option<u8> {
+        Some(self.block0.as_ref()?.punch_count)
+    }
+
+    fn get_start(&self) -> Option<Option<Punch>> {
+        Some(self.block0.as_ref()?.start)
+    }
+
+    fn get_siid(&self) -> Option<u32> {
+        Some(self.block0.as_ref()?.siid)
+    }
+
+    fn get_clear_check(&self) -> Option<Option<Punch>> {
+        Some(Some(self.block0.as_ref()?.clear_check))
+    }
+
+    fn get_finish(&self) -> Option<Option<Punch>> {
+        Some(self.block0.as_ref()?.finish)
+    }
+
+    fn feed_block(
+        &mut self,
+        block_id: u8,
+        block_buffer: &[u8; 128],
+    ) -> Result<(), crate::errors::FeedBlockError> {
+        self.raw_blocks.insert(block_id, *block_buffer);
+
+        match block_id {
+            0 => {
+                self.block0 = Some(Block0::deserialize(*block_buffer)?);
+            }
+            1 => {
+                self.block1 = Some(Block1::deserialize(*block_buffer)?);
+            }
+            _ => return Err(crate::errors::FeedBlockError::BlockDoesNotExist),
+        }
+        return Ok(());
+    }
+
+    fn block_needed(&self, intention: &BlockNeededIntention) -> BlockNeededResult {
+        match intention {
+            BlockNeededIntention::CardExclusives => match &self.block0 {
+                Some(_) => BlockNeededResult::NoNeed,
+                None => BlockNeededResult::Need(0),
+            },
+            BlockNeededIntention::CardPersonalData => {
+                if let Some(block0) = &self.block0 {
+                    if !block0.personal_data_finished && self.block1.is_none() {
+                        return BlockNeededResult::Need(1);
+                    } else {
+                        return BlockNeededResult::NoNeed;
+                    }
+                } else {
+                    return BlockNeededResult::Need(0);
+                }
+            }
+            BlockNeededIntention::Punches => match &self.block1 {
+                Some(_) => BlockNeededResult::NoNeed,
+                None => BlockNeededResult::Need(1),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{punch, time};
+    use chrono::NaiveTime;
+
+    /// Blocks 0 and 1 of the card 4000123 with the personal data `personal_data` and `punch_count` punches
+    fn blocks(personal_data: &[u8], punch_count: usize) -> ([u8; 128], [u8; 128]) {
+        let mut block0 = [0xEE; 128];
+        block0[0x00..0x04].copy_from_slice(&0x1234_5678u32.to_be_bytes());
+        block0[0x04..0x08].fill(0xEA);
+        block0[0x08..0x0C].copy_from_slice(&punch(0, 3000));
+        block0[0x0C..0x10].copy_from_slice(&punch(0, 3600));
+        block0[0x10..0x14].copy_from_slice(&punch(0, 7200));
+        block0[0x14..0x16].copy_from_slice(&45u16.to_be_bytes());
+        block0[0x16] = punch_count as u8;
+        block0[0x18..0x1C].copy_from_slice(&4_000_123u32.to_be_bytes());
+        block0[0x1C] = 6;
+        block0[0x1D] = 21;
+
+        // personal data are in block 0 0x20..0x7F and block 1 0x00..0x2F
+        let mut block1 = [0xEE; 128];
+        let (part1, part2) = personal_data.split_at(personal_data.len().min(96));
+        block0[0x20..0x20 + part1.len()].copy_from_slice(part1);
+        block1[..part2.len()].copy_from_slice(part2);
+
+        for i in 0..punch_count {
+            let offset = 0x30 + i * 4;
+            block1[offset..offset + 4].copy_from_slice(&punch(31 + i as u8, 4000 + i as u16));
+        }
+
+        return (block0, block1);
+    }
+
+    fn feed(block0: &[u8; 128], block1: &[u8; 128]) -> PCardDef {
+        let mut carddef = PCardDef::new_empty();
+        assert_eq!(
+            carddef.block_needed(&BlockNeededIntention::Punches),
+            BlockNeededResult::Need(1)
+        );
+        carddef.feed_block(0, block0).unwrap();
+        carddef.feed_block(1, block1).unwrap();
+        assert!(carddef.has_block(0) && carddef.has_block(1));
+        return carddef;
+    }
+
+    #[test]
+    fn header_and_punches() {
+        let (block0, block1) = blocks(b"Jan;Novak;\0\0", 3);
+        let carddef = feed(&block0, &block1);
+
+        assert_eq!(carddef.get_siid(), Some(4_000_123));
+        assert_eq!(carddef.get_punch_count(), Some(3));
+        assert_eq!(
+            carddef.get_clear_check().unwrap().unwrap().punch_time.time,
+            time(3000)
+        );
+        assert_eq!(
+            carddef.get_start().unwrap().unwrap().punch_time.time,
+            time(3600)
+        );
+        assert_eq!(
+            carddef.get_finish().unwrap().unwrap().punch_time.time,
+            time(7200)
+        );
+
+        let punches: Vec<(u16, NaiveTime)> = carddef
+            .get_punches()
+            .unwrap()
+            .iter()
+            .map(|punch| (punch.station_code, punch.punch_time.time))
+            .collect();
+        assert_eq!(
+            punches,
+            [(31, time(4000)), (32, time(4001)), (33, time(4002))]
+        );
+
+        let exclusives = carddef.get_exclusives().unwrap();
+        assert_eq!(exclusives.uid, 0x1234_5678);
+        assert_eq!(exclusives.last_visited_station_code, 45);
+        assert_eq!(exclusives.prod_date_month, 6);
+        assert_eq!(exclusives.prod_date_year, 2021);
+    }
+
+    #[test]
+    fn twenty_punches() {
+        let (block0, block1) = blocks(b"Jan;Novak;\0\0", 20);
+        let carddef = feed(&block0, &block1);
+
+        let punches = carddef.get_punches().unwrap();
+        assert_eq!(punches.len(), 20);
+        assert_eq!(punches[19].station_code, 50);
+    }
+
+    #[test]
+    fn personal_data_in_block_1() {
+        // 96 bytes do not fit block 0, the rest is in block 1
+        let mut data = alloc::vec![b'x'; 100];
+        data[..10].copy_from_slice(b"Jan;Novak;");
+        data.extend_from_slice(b";Brno\0\0");
+        let (block0, block1) = blocks(&data, 0);
+
+        let mut carddef = PCardDef::new_empty();
+        carddef.feed_block(0, &block0).unwrap();
+        assert_eq!(
+            carddef.block_needed(&BlockNeededIntention::CardPersonalData),
+            BlockNeededResult::Need(1)
+        );
+        assert!(carddef.get_personal_data().is_none());
+
+        carddef.feed_block(1, &block1).unwrap();
+        let personal_data = carddef.get_personal_data().unwrap().unwrap();
+        assert_eq!(personal_data.first_name.as_deref(), Some("Jan"));
+        assert_eq!(personal_data.last_name.as_deref(), Some("Novak"));
+        assert_eq!(carddef.get_punches(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn only_blocks_0_and_1_exist() {
+        let mut carddef = PCardDef::new_empty();
+        assert!(carddef.feed_block(4, &[0xEE; 128]).is_err());
+    }
+}
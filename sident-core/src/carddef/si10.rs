@@ -35,8 +35,8 @@
     0x40..0x41  Hardware version
     0x42..0x43  Software version
     0x48..0x49  Usage/Clear count
-    0x58..0x5B  Start reserve (punch)
-    0x5C..0x5F  Finish reserve (punch)
+    0x58..0x5B  Start reserve (punch) - TSS, CN, TH, TL if the station records sub seconds
+    0x5C..0x5F  Finish reserve (punch) - TSS, CN, TH, TL if the station records sub seconds
     0x70..0x73  SISYS?? idk but its always 0x73,0x69,0x61,0x63 and in HxD it says "siac"
     0x74..0x7B  Trim values?? what is this
     0x7C..0x7F  Device configuration - idk what each byte means tho
@@ -45,11 +45,16 @@
     Every 4 bytes is a punch - if all 4 bytes are 0xEE then you can stop parsing because there will be no more punches - but you have the punch count to be safe.
 */
 
+use alloc::{collections::BTreeMap, vec::Vec};
+
 use chrono::NaiveDate;
 
 use crate::{
     card::CardPersonalData,
-    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition},
+    carddef::{
+        BlockNeededIntention, BlockNeededResult, CardDefinition, MemoryRegion,
+        with_reserve_subsecond,
+    },
     errors::{DeserializeBlockError, DeserializeCardPersonalDataError},
     extract_fixed,
     punch::Punch,
@@ -139,6 +144,8 @@ struct Block3 {
     clear_count: u16,
     start_reserve: Option<Punch>,
     finish_reserve: Option<Punch>,
+    start_reserve_bytes: [u8; 4],
+    finish_reserve_bytes: [u8; 4],
 }
 
 impl Block3 {
@@ -164,10 +171,15 @@ impl Block3 {
         let clear_count = u16::from_be_bytes(clear_count_bytes);
 
         let start_reserve_bytes = extract_fixed!(&data, 0x58..0x5B);
-        let start_reserve = Punch::deserialize_control_punch(&start_reserve_bytes)?;
+        // with the sub seconds the reserve is not a valid punch
+        let start_reserve = Punch::deserialize_control_punch(&start_reserve_bytes)
+            .ok()
+            .flatten();
 
         let finish_reserve_bytes = extract_fixed!(&data, 0x5C..0x5F);
-        let finish_reserve = Punch::deserialize_control_punch(&finish_reserve_bytes)?;
+        let finish_reserve = Punch::deserialize_control_punch(&finish_reserve_bytes)
+            .ok()
+            .flatten();
 
         return Ok(Self {
             clear_check_reserve,
@@ -177,6 +189,8 @@ impl Block3 {
             clear_count,
             start_reserve,
             finish_reserve,
+            start_reserve_bytes,
+            finish_reserve_bytes,
         });
     }
 }
@@ -230,6 +244,10 @@ pub struct Card10Def {
     block5: Option<Block5>,
     block6: Option<Block6>,
     block7: Option<Block7>,
+    /// Every block fed to the definition, also the ones which could not be parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    raw_blocks: BTreeMap<u8, [u8; 128]>,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -249,6 +267,10 @@ pub struct Card10Exclusives {
 
 impl CardDefinition for Card10Def {
     const HAS_CARD_EXCLUSIVES: bool = true;
+    const PERSONAL_DATA_REGIONS: &'static [MemoryRegion] = &[
+        MemoryRegion::new(0, 0x20, 96),
+        MemoryRegion::new(1, 0x00, 32),
+    ];
     type CardExclusivesType = Card10Exclusives;
     fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
         let block0 = self.block0.as_ref()?;
@@ -275,9 +297,14 @@ impl CardDefinition for Card10Def {
             block5: None,
             block6: None,
             block7: None,
+            raw_blocks: BTreeMap::new(),
         }
     }
 
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]> {
+        &self.raw_blocks
+    }
+
     fn get_punches(&self) -> Option<Vec<Punch>> {
         if self.block_needed(&BlockNeededIntention::Punches) != BlockNeededResult::NoNeed {
             return None;
@@ -310,12 +337,17 @@ impl CardDefinition for Card10Def {
         Some(self.block0.as_ref()?.siid)
     }
 
-    fn get_clear_check(&self) -> Option<Punch> {
-        Some(self.block0.as_ref()?.clear_check)
+    fn get_clear_check(&self) -> Option<Option<Punch>> {
+        Some(Some(self.block0.as_ref()?.clear_check))
     }
 
     fn get_finish(&self) -> Option<Option<Punch>> {
-        Some(self.block0.as_ref()?.finish)
+        let finish = self.block0.as_ref()?.finish;
+        let reserve = self
+            .block3
+            .as_ref()
+            .map(|block3| &block3.finish_reserve_bytes);
+        Some(with_reserve_subsecond(finish, reserve))
     }
 
     fn get_personal_data(
@@ -338,7 +370,12 @@ impl CardDefinition for Card10Def {
     }
 
     fn get_start(&self) -> Option<Option<Punch>> {
-        Some(self.block0.as_ref()?.start)
+        let start = self.block0.as_ref()?.start;
+        let reserve = self
+            .block3
+            .as_ref()
+            .map(|block3| &block3.start_reserve_bytes);
+        Some(with_reserve_subsecond(start, reserve))
     }
 
     fn block_needed(&self, intention: &super::BlockNeededIntention) -> super::BlockNeededResult {
@@ -408,6 +445,8 @@ impl CardDefinition for Card10Def {
         block_id: u8,
         block_buffer: &[u8; 128],
     ) -> Result<(), crate::errors::FeedBlockError> {
+        self.raw_blocks.insert(block_id, *block_buffer);
+
         match block_id {
             0 => {
                 self.block0 = Some(Block0::deserialize(*block_buffer)?);
@@ -436,3 +475,44 @@ impl CardDefinition for Card10Def {
         return Ok(());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{newer_card_block0, punch, read_punches, si10_block3};
+
+    /// Blocks of the card 7000123 - start and finish with the sub seconds in their reserve records
+    fn blocks() -> BTreeMap<u8, [u8; 128]> {
+        let mut block3 = si10_block3();
+        block3[0x58..0x5C].copy_from_slice(&punch(0, 3600));
+        block3[0x58] = 0x80;
+        block3[0x5C..0x60].copy_from_slice(&punch(0, 7200));
+        block3[0x5C] = 0x40;
+        return BTreeMap::from([
+            (0, newer_card_block0(7_000_123, 3600, 7200, 0)),
+            (3, block3),
+            (4, [0xEE; 128]),
+        ]);
+    }
+
+    #[test]
+    fn subseconds_need_the_exclusives() {
+        let blocks = blocks();
+        let mut carddef = Card10Def::new_empty();
+        carddef.feed_block(0, &blocks[&0]).unwrap();
+        assert_eq!(read_punches(&mut carddef, &blocks), [4]);
+        let start = carddef.get_start().unwrap().unwrap();
+        assert_eq!(start.punch_time.subsecond, None);
+
+        // block 3 with the reserve records is read only for the exclusives
+        assert_eq!(
+            carddef.block_needed(&BlockNeededIntention::CardExclusives),
+            BlockNeededResult::Need(3)
+        );
+        carddef.feed_block(3, &blocks[&3]).unwrap();
+        let start = carddef.get_start().unwrap().unwrap();
+        assert_eq!(start.punch_time.subsecond, Some(0x80));
+        let finish = carddef.get_finish().unwrap().unwrap();
+        assert_eq!(finish.punch_time.subsecond, Some(0x40));
+    }
+}
@@ -0,0 +1,415 @@
+/*
+    SPORTident Card-5 Memory Structure
+
+    Card 5 has only one block with 128 bytes, which is read at once with the 0xB1 command.
+    Every integer is big endian encoded unless said differently in the desc.
+
+    ---- BLOCK 0x00 ----
+    0x04..0x05  SIID - SI1, SI0 (number in the card series)
+    0x06        SIID - SI2 (card series)
+    0x11..0x12  Start number
+    0x13..0x14  Start (12h time)
+    0x15..0x16  Finish (12h time)
+    0x17        Punch pointer - punch count + 1
+    0x19..0x1A  Check (12h time)
+    0x20..0x7F  Punches - 6 rows, 16 bytes each
+    ----            ----
+
+    || Punch row (16 bytes):
+    0x00        Control code of punch 31-36 (these punches have no time)
+    0x01..0x0F  5 punches - CN, TH, TL (3 bytes each)
+    ||
+
+    --------------------
+    Times are 12h binary (seconds). There is no AM/PM and no day of week. 0xEEEE means no time.
+    Control codes are only 1 byte. Start, finish and check have no control code, so it is 0.
+*/
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use chrono::NaiveTime;
+
+use crate::{
+    card::CardPersonalData,
+    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition, ReadoutCommand},
+    errors::{DeserializeBlockError, DeserializeCardPersonalDataError, DeserializePunchError},
+    extract_fixed,
+    punch::{Punch, PunchTime},
+};
+
+/// Number of punches with time
+const TIMED_PUNCHES: usize = 30;
+/// Number of punches without time (31-36)
+const UNTIMED_PUNCHES: usize = 6;
+
+/// Deserializes the Card 5 12h time. Returns `None` if there is no time (0xEEEE).
+fn deserialize_time(data: &[u8; 2]) -> Result<Option<PunchTime>, DeserializePunchError> {
+    if *data == [0xEE, 0xEE] {
+        return Ok(None);
+    }
+
+    let seconds = u16::from_be_bytes(*data);
+    let time = NaiveTime::from_num_seconds_from_midnight_opt(seconds as u32, 0)
+        .ok_or(DeserializePunchError::InvalidTime)?;
+
+    return Ok(Some(PunchTime {
+        time,
+        twelve_hour_only: true,
+        ..Default::default()
+    }));
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "SI5Block0.ts"))]
+#[derive(Debug, Clone)]
+struct Block0 {
+    siid: u32,
+    start_number: u16,
+    start: Option<PunchTime>,
+    finish: Option<PunchTime>,
+    check: Option<PunchTime>,
+    punch_count: u8,
+    punches: Vec<Punch>,
+    punches_without_time: Vec<u16>,
+}
+
+impl Block0 {
+    pub fn deserialize(data: [u8; 128]) -> Result<Self, DeserializeBlockError> {
+        let siid = Card5Def::decode_siid(
+            data[0x06],
+            u16::from_be_bytes(extract_fixed!(&data, 0x04..0x05)),
+        );
+        let start_number = u16::from_be_bytes(extract_fixed!(&data, 0x11..0x12));
+        let start = deserialize_time(&extract_fixed!(&data, 0x13..0x14))?;
+        let finish = deserialize_time(&extract_fixed!(&data, 0x15..0x16))?;
+        let check = deserialize_time(&extract_fixed!(&data, 0x19..0x1A))?;
+        // the pointer points to the next free punch
+        let punch_count = data[0x17].saturating_sub(1);
+
+        let punch_rows = extract_fixed!(&data, 0x20..0x7F);
+        let mut punches: Vec<Punch> = Vec::new();
+        let mut punches_without_time: Vec<u16> = Vec::new();
+
+        let timed_count = (punch_count as usize).min(TIMED_PUNCHES);
+        let timed_chunks = punch_rows
+            .chunks(16)
+            .flat_map(|row| row[1..].chunks(3))
+            .take(timed_count);
+        for punch_chunk in timed_chunks {
+            let station_code = punch_chunk[0] as u16;
+            match deserialize_time(&[punch_chunk[1], punch_chunk[2]])? {
+                Some(punch_time) => punches.push(Punch {
+                    station_code,
+                    punch_time,
+                }),
+                None => punches_without_time.push(station_code),
+            }
+        }
+
+        // punches 31-36 are in the first byte of the rows
+        let untimed_count = (punch_count as usize).saturating_sub(TIMED_PUNCHES);
+        for row in punch_rows
+            .chunks(16)
+            .take(untimed_count.min(UNTIMED_PUNCHES))
+        {
+            punches_without_time.push(row[0] as u16);
+        }
+
+        return Ok(Self {
+            siid,
+            start_number,
+            start,
+            finish,
+            check,
+            punch_count,
+            punches,
+            punches_without_time,
+        });
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone)]
+pub struct Card5Def {
+    block0: Option<Block0>,
+    /// Every block fed to the definition, also the ones which could not be parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    raw_blocks: BTreeMap<u8, [u8; 128]>,
+}
+
+impl Card5Def {
+    /// Decodes the card number from the card series and the number in the series.
+    ///
+    /// Series 0 and 1 are not printed on the card, other series are added as hundred thousands.
+    ///
+    /// * `series` - Card series (SI2)
+    /// * `number` - Number in the series (SI1, SI0)
+    pub fn decode_siid(series: u8, number: u16) -> u32 {
+        if series < 2 {
+            return number as u32;
+        }
+        return series as u32 * 100_000 + number as u32;
+    }
+
+    /// Encodes the card number back to the card series and the number in the series.
+    ///
+    /// * `siid` - SIID (card number)
+    pub fn encode_siid(siid: u32) -> (u8, u16) {
+        if siid < 100_000 {
+            return (1, siid as u16);
+        }
+        return ((siid / 100_000) as u8, (siid % 100_000) as u16);
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone)]
+pub struct Card5Exclusives {
+    pub start_number: u16,
+    /// Control codes of the punches without time, in the order on the card: punches 1-30 without the time
+    /// recorded (0xEEEE) and punches 31-36, which never have a time. These are not in `get_punches`.
+    pub punches_without_time: Vec<u16>,
+}
+
+impl CardDefinition for Card5Def {
+    const HAS_CARD_EXCLUSIVES: bool = true;
+    const READOUT_COMMAND: ReadoutCommand = ReadoutCommand::GetSICard5;
+    type CardExclusivesType = Card5Exclusives;
+    fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
+        let block0 = self.block0.as_ref()?;
+
+        return Some(Self::CardExclusivesType {
+            start_number: block0.start_number,
+            punches_without_time: block0.punches_without_time.clone(),
+        });
+    }
+
+    fn new_empty() -> Self {
+        Self {
+            block0: None,
+            raw_blocks: BTreeMap::new(),
+        }
+    }
+
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]> {
+        &self.raw_blocks
+    }
+
+    fn get_siid(&self) -> Option<u32> {
+        Some(self.block0.as_ref()?.siid)
+    }
+
+    fn get_punch_count(&self) -> Option<u8> {
+        Some(self.block0.as_ref()?.punch_count)
+    }
+
+    fn get_personal_data(
+        &self,
+    ) -> Option<Result<CardPersonalData, DeserializeCardPersonalDataError>> {
+        // Card 5 does not support personal data
+        return None;
+    }
+
+    fn get_clear_check(&self) -> Option<Option<Punch>> {
+        let block0 = self.block0.as_ref()?;
+        return Some(block0.check.map(|punch_time| Punch {
+            station_code: 0,
+            punch_time,
+        }));
+    }
+
+    fn get_start(&self) -> Option<Option<Punch>> {
+        let block0 = self.block0.as_ref()?;
+        return Some(block0.start.map(|punch_time| Punch {
+            station_code: 0,
+            punch_time,
+        }));
+    }
+
+    fn get_finish(&self) -> Option<Option<Punch>> {
+        let block0 = self.block0.as_ref()?;
+        return Some(block0.finish.map(|punch_time| Punch {
+            station_code: 0,
+            punch_time,
+        }));
+    }
+
+    fn get_punches(&self) -> Option<Vec<Punch>> {
+        // punches without time are only in `Card5Exclusives::punches_without_time`
+        return Some(self.block0.as_ref()?.punches.clone());
+    }
+
+    fn feed_block(
+        &mut self,
+        block_id: u8,
+        block_buffer: &[u8; 128],
+    ) -> Result<(), crate::errors::FeedBlockError> {
+        self.raw_blocks.insert(block_id, *block_buffer);
+
+        match block_id {
+            0 => {
+                self.block0 = Some(Block0::deserialize(*block_buffer)?);
+            }
+            _ => return Err(crate::errors::FeedBlockError::BlockDoesNotExist),
+        }
+        return Ok(());
+    }
+
+    fn block_needed(&self, _intention: &BlockNeededIntention) -> BlockNeededResult {
+        match self.block0 {
+            Some(_) => BlockNeededResult::NoNeed,
+            None => BlockNeededResult::Need(0),
+        }
+    }
+
+    fn has_block(&self, block_id: u8) -> bool {
+        match block_id {
+            0 => self.block0.is_some(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{card5_block, time};
+
+    /// Block of the card 3-12345 with the start, finish, check and `punches` (control code, 12h seconds)
+    fn block(punches: &[(u8, u16)]) -> [u8; 128] {
+        return card5_block(312_345, Some(3600), Some(7200), Some(3000), punches);
+    }
+
+    fn feed(block: &[u8; 128]) -> Card5Def {
+        let mut carddef = Card5Def::new_empty();
+        assert_eq!(
+            carddef.block_needed(&BlockNeededIntention::Punches),
+            BlockNeededResult::Need(0)
+        );
+        carddef.feed_block(0, block).unwrap();
+        assert_eq!(
+            carddef.block_needed(&BlockNeededIntention::Punches),
+            BlockNeededResult::NoNeed
+        );
+        return carddef;
+    }
+
+    #[test]
+    fn siid_series() {
+        assert_eq!(Card5Def::decode_siid(0, 12345), 12345);
+        assert_eq!(Card5Def::decode_siid(1, 12345), 12345);
+        assert_eq!(Card5Def::decode_siid(4, 65000), 465000);
+        assert_eq!(Card5Def::encode_siid(12345), (1, 12345));
+        assert_eq!(Card5Def::encode_siid(465000), (4, 65000));
+    }
+
+    #[test]
+    fn block_with_punches() {
+        let carddef = feed(&block(&[(31, 4000), (32, 5000), (200, 6000)]));
+
+        assert_eq!(carddef.get_siid(), Some(312345));
+        assert_eq!(carddef.get_punch_count(), Some(3));
+        assert_eq!(
+            carddef.get_start().unwrap().unwrap().punch_time.time,
+            time(3600)
+        );
+        assert_eq!(
+            carddef.get_finish().unwrap().unwrap().punch_time.time,
+            time(7200)
+        );
+        assert_eq!(
+            carddef.get_clear_check().unwrap().unwrap().punch_time.time,
+            time(3000)
+        );
+        assert!(
+            carddef
+                .get_start()
+                .unwrap()
+                .unwrap()
+                .punch_time
+                .twelve_hour_only
+        );
+
+        let punches: Vec<(u16, NaiveTime)> = carddef
+            .get_punches()
+            .unwrap()
+            .iter()
+            .map(|punch| (punch.station_code, punch.punch_time.time))
+            .collect();
+        assert_eq!(
+            punches,
+            [(31, time(4000)), (32, time(5000)), (200, time(6000))]
+        );
+
+        let exclusives = carddef.get_exclusives().unwrap();
+        assert_eq!(exclusives.start_number, 42);
+        assert!(exclusives.punches_without_time.is_empty());
+        assert!(carddef.get_personal_data().is_none());
+    }
+
+    #[test]
+    fn punches_without_time() {
+        let punches: Vec<(u8, u16)> = (0..36).map(|i| (31 + i as u8, 100 * i)).collect();
+        let carddef = feed(&block(&punches));
+
+        assert_eq!(carddef.get_punch_count(), Some(36));
+        let read = carddef.get_punches().unwrap();
+        assert_eq!(read.len(), 30);
+        assert_eq!(read[29].station_code, 60);
+        assert_eq!(read[29].punch_time.time, time(2900));
+        assert!(read.iter().all(|punch| punch.punch_time.twelve_hour_only));
+        // punches 31-36 are in the first byte of the rows and have no time, so they are not punches
+        assert_eq!(
+            carddef.get_exclusives().unwrap().punches_without_time,
+            [61, 62, 63, 64, 65, 66]
+        );
+    }
+
+    #[test]
+    fn punch_without_recorded_time() {
+        let carddef = feed(&block(&[(31, 4000), (32, 0xEEEE), (33, 6000)]));
+
+        let codes: Vec<u16> = carddef
+            .get_punches()
+            .unwrap()
+            .iter()
+            .map(|punch| punch.station_code)
+            .collect();
+        assert_eq!(codes, [31, 33]);
+        assert_eq!(carddef.get_exclusives().unwrap().punches_without_time, [32]);
+    }
+
+    #[test]
+    fn missing_start_and_finish() {
+        let mut block = block(&[]);
+        block[0x13..0x17].fill(0xEE);
+        let carddef = feed(&block);
+
+        assert_eq!(carddef.get_start(), Some(None));
+        assert_eq!(carddef.get_finish(), Some(None));
+        assert_eq!(carddef.get_punches(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn missing_check() {
+        let mut block = block(&[(31, 4000)]);
+        block[0x19..0x1B].fill(0xEE);
+        let carddef = feed(&block);
+
+        assert_eq!(carddef.get_clear_check(), Some(None));
+        assert_eq!(carddef.get_punches().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn only_block_0_exists() {
+        let mut carddef = Card5Def::new_empty();
+        assert!(carddef.feed_block(1, &[0; 128]).is_err());
+        assert!(carddef.get_siid().is_none());
+    }
+}
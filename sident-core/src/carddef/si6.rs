@@ -37,11 +37,15 @@
     0x00..0x7F  Punches
     ----            ----
 
+    Every punch block has 32 punches. SI6 has 64 punches in blocks 6 and 7.
+    SI6* (192 punches) has punches 1-64 in blocks 6 and 7 and punches 65-192 in blocks 2 to 5,
+    so it can be read out as a regular SI6 if it has less than 65 punches.
+
 
     --------------------
     SPORTident Card-6 Punch Format
     Used in: SI6, SI8, SI9, SI10, SI11, SIAC, pCard
-    
+
     Structure: TD, CN, TH, TL (4 bytes)
 
     CN - Station code number LOWER
@@ -66,7 +70,17 @@
     ||
 */
 
-use crate::{card::CardPersonalData, carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition}, errors::DeserializeBlockError, extract_fixed, punch::Punch};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use crate::{
+    card::CardPersonalData,
+    carddef::{
+        BlockNeededIntention, BlockNeededResult, CardDefinition, MemoryRegion, ReadoutCommand,
+    },
+    errors::{DeserializeBlockError, SerializeCardPersonalDataError},
+    extract_fixed,
+    punch::Punch,
+};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
@@ -78,13 +92,13 @@ struct Block0 {
     finish: Option<Punch>,
     start: Option<Punch>,
     check: Option<Punch>,
-    clear: Punch,
+    clear: Option<Punch>,
     start_number: u32,
     // CARD PERSONAL DATA START
     #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
     #[cfg_attr(feature = "ts-rs", ts(type = "[number; 80]"))]
     card_personal_data1: [u8; 80],
-    ctype_star: bool
+    ctype_star: bool,
 }
 
 impl Block0 {
@@ -94,32 +108,26 @@ impl Block0 {
         let finish = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x14..0x17))?;
         let start = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x18..0x1B))?;
         let check = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x1C..0x1F))?;
-        let clear = Punch::deserialize(&extract_fixed!(&data, 0x20..0x23))?;
+        let clear = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x20..0x23))?;
         let start_number = u32::from_le_bytes(extract_fixed!(&data, 0x28..0x2B));
         let cpd1 = extract_fixed!(&data, 0x30..0x7F);
-        let mut ctype_star = false;
-
-        if punch_count > 64 {
-            ctype_star = true;
-        }
-
-        return Ok(
-            Self {
-                siid,
-                punch_count,
-                finish,
-                start,
-                check,
-                clear,
-                start_number,
-                card_personal_data1: cpd1,
-                ctype_star
-            }
-        );
+        // only SI6* can hold more than 64 punches
+        let ctype_star = punch_count > 64;
+
+        return Ok(Self {
+            siid,
+            punch_count,
+            finish,
+            start,
+            check,
+            clear,
+            start_number,
+            card_personal_data1: cpd1,
+            ctype_star,
+        });
     }
 }
 
-
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 #[cfg_attr(feature = "ts-rs", ts(export, export_to = "SI6Block1.ts"))]
@@ -127,22 +135,19 @@ impl Block0 {
 struct Block1 {
     #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
     #[cfg_attr(feature = "ts-rs", ts(type = "[number; 124]"))]
-    card_personal_data2: [u8; 124]
+    card_personal_data2: [u8; 124],
 }
 
 impl Block1 {
     pub fn deserialize(data: [u8; 128]) -> Result<Self, DeserializeBlockError> {
         let cpd2 = extract_fixed!(&data, 0x00..0x7B);
 
-        return Ok(
-            Self {
-                card_personal_data2: cpd2
-            }
-        )
+        return Ok(Self {
+            card_personal_data2: cpd2,
+        });
     }
 }
 
-
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 #[cfg_attr(feature = "ts-rs", ts(export, export_to = "SI6Block2.ts"))]
@@ -150,7 +155,7 @@ impl Block1 {
 /// SI6* - Block2 and higher, SI6 - Block6 and higher
 struct Block2 {
     punches: Vec<Punch>,
-    punches_finished: bool
+    punches_finished: bool,
 }
 
 impl Block2 {
@@ -169,7 +174,10 @@ impl Block2 {
             punches.push(Punch::deserialize(punch_chunk)?);
         }
 
-        return Ok(Self { punches, punches_finished });
+        return Ok(Self {
+            punches,
+            punches_finished,
+        });
     }
 }
 
@@ -183,9 +191,12 @@ type Block7 = Block2;
 #[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
 #[cfg_attr(feature = "ts-rs", ts(export))]
 #[derive(Debug, Clone, PartialEq, Eq)]
+/// Card 6 layout. SI6* is recognized by the punch count, so SI6* with 64 or less punches is reported as `Regular`.
 pub enum Card6Type {
+    /// SI6 - 64 punches
     Regular,
-    Star
+    /// SI6* - 192 punches
+    Star,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -206,8 +217,10 @@ pub struct Card6Def {
     block6: Option<Block6>,
     block7: Option<Block7>,
     ctype: Card6Type,
-    /// Are we sure about the ctype?
-    ctype_ensured: bool
+    /// Every block fed to the definition, also the ones which could not be parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    raw_blocks: BTreeMap<u8, [u8; 128]>,
 }
 
 impl Card6Def {
@@ -221,19 +234,16 @@ impl Card6Def {
     fn _get_blocks_and_ids(&self) -> Vec<(u8, Option<&Block2>)> {
         match self.ctype {
             Card6Type::Regular => {
+                return vec![(6, self.block6.as_ref()), (7, self.block7.as_ref())];
+            }
+            Card6Type::Star => {
                 return vec![
                     (6, self.block6.as_ref()),
                     (7, self.block7.as_ref()),
-                ];
-            },
-            Card6Type::Star => {
-                return vec![
                     (2, self.block2.as_ref()),
                     (3, self.block3.as_ref()),
                     (4, self.block4.as_ref()),
                     (5, self.block5.as_ref()),
-                    (6, self.block6.as_ref()),
-                    (7, self.block7.as_ref()),
                 ];
             }
         }
@@ -250,21 +260,25 @@ impl Card6Def {
 #[derive(Debug, Clone)]
 pub struct Card6Exclusives {
     pub start_number: u32,
-    pub card_type: Card6Type
+    pub card_type: Card6Type,
 }
 
 impl CardDefinition for Card6Def {
     const HAS_CARD_EXCLUSIVES: bool = true;
+    // the user ID (block 1 0x00..0x0F) is skipped
+    const PERSONAL_DATA_REGIONS: &'static [MemoryRegion] = &[
+        MemoryRegion::new(0, 0x30, 80),
+        MemoryRegion::new(1, 0x10, 108),
+    ];
+    const READOUT_COMMAND: ReadoutCommand = ReadoutCommand::GetSICard6;
     type CardExclusivesType = Card6Exclusives;
     fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
         let block0 = self.block0.as_ref()?;
 
-        return Some(
-            Self::CardExclusivesType {
-                start_number: block0.start_number,
-                card_type: self.ctype.clone()
-            }
-        );
+        return Some(Self::CardExclusivesType {
+            start_number: block0.start_number,
+            card_type: self.ctype.clone(),
+        });
     }
 
     fn new_empty() -> Self {
@@ -278,10 +292,14 @@ impl CardDefinition for Card6Def {
             block6: None,
             block7: None,
             ctype: Card6Type::Regular,
-            ctype_ensured: false,
+            raw_blocks: BTreeMap::new(),
         }
     }
 
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]> {
+        &self.raw_blocks
+    }
+
     fn get_punches(&self) -> Option<Vec<Punch>> {
         let mut final_punches: Vec<Punch> = Vec::new();
         let blocks = self._get_blocks();
@@ -301,10 +319,10 @@ impl CardDefinition for Card6Def {
         Some(self.block0.as_ref()?.siid)
     }
 
-    fn get_clear_check(&self) -> Option<Punch> {
+    fn get_clear_check(&self) -> Option<Option<Punch>> {
         let block0 = self.block0.as_ref()?;
         if block0.check.is_some() {
-            return block0.check;
+            return Some(block0.check);
         }
         return Some(block0.clear);
     }
@@ -314,15 +332,26 @@ impl CardDefinition for Card6Def {
     }
 
     fn get_personal_data(
-            &self,
-        ) -> Option<Result<crate::card::CardPersonalData, crate::errors::DeserializeCardPersonalDataError>> {
+        &self,
+    ) -> Option<
+        Result<crate::card::CardPersonalData, crate::errors::DeserializeCardPersonalDataError>,
+    > {
         let block0 = self.block0.as_ref()?;
         let block1 = self.block1.as_ref()?;
         let mut buff = Vec::new();
         buff.extend_from_slice(&block0.card_personal_data1);
         buff.extend_from_slice(&block1.card_personal_data2);
         let buff: [u8; 204] = buff.try_into().unwrap();
-        return Some(CardPersonalData::deserialize_card_6(buff));
+        return Some(CardPersonalData::deserialize_card_6(&buff));
+    }
+
+    fn serialize_personal_data(
+        data: &CardPersonalData,
+    ) -> Result<Vec<u8>, SerializeCardPersonalDataError> {
+        let data = data.serialize_card_6()?;
+        let mut serialized = data[..0x50].to_vec();
+        serialized.extend_from_slice(&data[0x60..]);
+        return Ok(serialized);
     }
 
     fn get_punch_count(&self) -> Option<u8> {
@@ -337,15 +366,12 @@ impl CardDefinition for Card6Def {
         match intention {
             BlockNeededIntention::CardExclusives => {
                 match self.block0 {
-                    Some(_) => {},
+                    Some(_) => {}
                     None => return BlockNeededResult::Need(0),
                 }
 
-                if !self.ctype_ensured {
-                    todo!()
-                }
                 return BlockNeededResult::NoNeed;
-            },
+            }
             BlockNeededIntention::CardPersonalData => {
                 match self.block0 {
                     Some(_) => {}
@@ -358,12 +384,18 @@ impl CardDefinition for Card6Def {
                 }
 
                 return BlockNeededResult::NoNeed;
-            },
+            }
             BlockNeededIntention::Punches => {
+                // block 0 decides the card 6 type (and therefore the punch blocks)
+                match self.block0 {
+                    Some(_) => {}
+                    None => return BlockNeededResult::Need(0),
+                }
+
                 for (block_id, block) in self._get_blocks_and_ids() {
                     let block = match block {
                         Some(ok) => ok,
-                        None => return BlockNeededResult::Need(block_id)
+                        None => return BlockNeededResult::Need(block_id),
                     };
                     if block.punches_finished {
                         break;
@@ -388,7 +420,12 @@ impl CardDefinition for Card6Def {
         }
     }
 
-    fn feed_block(&mut self, block_id: u8, block_buffer: &[u8; 128]) -> Result<(), crate::errors::FeedBlockError> {
+    fn feed_block(
+        &mut self,
+        block_id: u8,
+        block_buffer: &[u8; 128],
+    ) -> Result<(), crate::errors::FeedBlockError> {
+        self.raw_blocks.insert(block_id, *block_buffer);
 
         if self.ctype == Card6Type::Regular && (block_id < 6 && block_id > 1) {
             return Err(crate::errors::FeedBlockError::BlockDoesNotExist);
@@ -399,7 +436,6 @@ impl CardDefinition for Card6Def {
                 let b0 = Block0::deserialize(*block_buffer)?;
                 if b0.ctype_star {
                     self.ctype = Card6Type::Star;
-                    self.ctype_ensured = true;
                 }
                 self.block0 = Some(b0);
             }
@@ -408,7 +444,7 @@ impl CardDefinition for Card6Def {
             }
             2 => {
                 self.block2 = Some(Block2::deserialize(*block_buffer)?);
-            },
+            }
             3 => {
                 self.block3 = Some(Block2::deserialize(*block_buffer)?);
             }
@@ -428,4 +464,145 @@ impl CardDefinition for Card6Def {
         }
         return Ok(());
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{card6_blocks, read_punches, time, with_personal_data};
+    use alloc::string::ToString;
+
+    fn personal_data() -> CardPersonalData {
+        return CardPersonalData {
+            first_name: Some("Jan".to_string()),
+            last_name: Some("Novak".to_string()),
+            club: Some("OK Praha".to_string()),
+            city: Some("Brno".to_string()),
+            ..Default::default()
+        };
+    }
+
+    /// Blocks of the card 600123 with the start, finish and `count` punches (codes 31, 32, ..., 230, 31, ...)
+    fn blocks(count: usize) -> BTreeMap<u8, [u8; 128]> {
+        let punches: Vec<(u8, u16)> = (0..count)
+            .map(|n| (31 + (n % 200) as u8, n as u16))
+            .collect();
+        return card6_blocks(600_123, 3600, 7200, &punches);
+    }
+
+    fn codes(carddef: &Card6Def) -> Vec<u16> {
+        return carddef
+            .get_punches()
+            .unwrap()
+            .iter()
+            .map(|punch| punch.station_code)
+            .collect();
+    }
+
+    #[test]
+    fn regular_card() {
+        let blocks = blocks(40);
+        let mut carddef = Card6Def::new_empty();
+
+        assert_eq!(read_punches(&mut carddef, &blocks), [0, 6, 7]);
+        assert_eq!(carddef.get_card6_type(), Card6Type::Regular);
+        assert_eq!(carddef.get_siid(), Some(600_123));
+        assert_eq!(carddef.get_punch_count(), Some(40));
+        assert_eq!(
+            carddef.get_start().unwrap().unwrap().punch_time.time,
+            time(3600)
+        );
+        assert_eq!(
+            carddef.get_finish().unwrap().unwrap().punch_time.time,
+            time(7200)
+        );
+        assert_eq!(carddef.get_exclusives().unwrap().start_number, 42);
+
+        let punches = carddef.get_punches().unwrap();
+        assert_eq!(punches.len(), 40);
+        assert_eq!(punches[39].station_code, 70);
+        assert_eq!(punches[39].punch_time.time, time(39));
+    }
+
+    #[test]
+    fn regular_card_has_no_blocks_2_to_5() {
+        let blocks = blocks(10);
+        let mut carddef = Card6Def::new_empty();
+
+        // block 6 is not full, so block 7 is not needed
+        assert_eq!(read_punches(&mut carddef, &blocks), [0, 6]);
+        assert!(carddef.feed_block(2, &[0xEE; 128]).is_err());
+        assert_eq!(codes(&carddef).len(), 10);
+    }
+
+    #[test]
+    fn star_card_block_order() {
+        let blocks = blocks(150);
+        let mut carddef = Card6Def::new_empty();
+
+        // 150 punches fill the blocks 6, 7, 2 and 3 and a part of block 4
+        assert_eq!(read_punches(&mut carddef, &blocks), [0, 6, 7, 2, 3, 4]);
+        assert_eq!(carddef.get_card6_type(), Card6Type::Star);
+        assert_eq!(carddef.get_exclusives().unwrap().card_type, Card6Type::Star);
+
+        let expected: Vec<u16> = (0..150).map(|n| 31 + (n % 200) as u16).collect();
+        assert_eq!(codes(&carddef), expected);
+        let punches = carddef.get_punches().unwrap();
+        assert_eq!(punches[64].punch_time.time, time(64));
+        assert_eq!(punches[149].punch_time.time, time(149));
+    }
+
+    #[test]
+    fn full_star_card() {
+        let blocks = blocks(192);
+        let mut carddef = Card6Def::new_empty();
+
+        assert_eq!(read_punches(&mut carddef, &blocks), [0, 6, 7, 2, 3, 4, 5]);
+        assert_eq!(codes(&carddef).len(), 192);
+    }
+
+    #[test]
+    fn clear_is_used_without_check() {
+        let mut blocks = blocks(0);
+        let mut carddef = Card6Def::new_empty();
+        carddef.feed_block(0, &blocks[&0]).unwrap();
+        assert_eq!(
+            carddef.get_clear_check().unwrap().unwrap().punch_time.time,
+            time(3540)
+        );
+
+        let block0 = blocks.get_mut(&0).unwrap();
+        block0[0x1C..0x20].fill(0xEE);
+        carddef.feed_block(0, &blocks[&0]).unwrap();
+        assert_eq!(
+            carddef.get_clear_check().unwrap().unwrap().punch_time.time,
+            time(3000)
+        );
+
+        let block0 = blocks.get_mut(&0).unwrap();
+        block0[0x14..0x24].fill(0xEE);
+        carddef.feed_block(0, &blocks[&0]).unwrap();
+        assert_eq!(carddef.get_clear_check(), Some(None));
+        assert_eq!(carddef.get_start(), Some(None));
+        assert_eq!(carddef.get_finish(), Some(None));
+    }
+
+    #[test]
+    fn personal_data_in_blocks_0_and_1() {
+        let blocks = with_personal_data::<Card6Def>(&blocks(0), &personal_data());
+        let mut carddef = Card6Def::new_empty();
+        assert_eq!(
+            carddef.block_needed(&BlockNeededIntention::CardPersonalData),
+            BlockNeededResult::Need(0)
+        );
+        carddef.feed_block(0, &blocks[&0]).unwrap();
+        assert_eq!(
+            carddef.block_needed(&BlockNeededIntention::CardPersonalData),
+            BlockNeededResult::Need(1)
+        );
+        carddef.feed_block(1, &blocks[&1]).unwrap();
+
+        let read = carddef.get_personal_data().unwrap().unwrap();
+        assert_eq!(read, personal_data());
+    }
+}
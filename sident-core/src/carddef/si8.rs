@@ -22,9 +22,11 @@
     ----            ----
 */
 
+use alloc::{collections::BTreeMap, vec::Vec};
+
 use crate::{
     card::CardPersonalData,
-    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition},
+    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition, MemoryRegion},
     errors::{DeserializeBlockError, DeserializeCardPersonalDataError},
     extract_fixed,
     punch::Punch,
@@ -118,6 +120,10 @@ impl Block1 {
 pub struct Card8Def {
     block0: Option<Block0>,
     block1: Option<Block1>,
+    /// Every block fed to the definition, also the ones which could not be parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    raw_blocks: BTreeMap<u8, [u8; 128]>,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -133,6 +139,10 @@ pub struct Card8Exclusives {
 
 impl CardDefinition for Card8Def {
     const HAS_CARD_EXCLUSIVES: bool = true;
+    const PERSONAL_DATA_REGIONS: &'static [MemoryRegion] = &[
+        MemoryRegion::new(0, 0x20, 96),
+        MemoryRegion::new(1, 0x00, 8),
+    ];
     type CardExclusivesType = Card8Exclusives;
     fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
         let block0 = self.block0.as_ref()?;
@@ -149,9 +159,14 @@ impl CardDefinition for Card8Def {
         Self {
             block0: None,
             block1: None,
+            raw_blocks: BTreeMap::new(),
         }
     }
 
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]> {
+        &self.raw_blocks
+    }
+
     fn has_block(&self, block_id: u8) -> bool {
         match block_id {
             0 => self.block0.is_some(),
@@ -193,8 +208,8 @@ impl CardDefinition for Card8Def {
         Some(self.block0.as_ref()?.siid)
     }
 
-    fn get_clear_check(&self) -> Option<Punch> {
-        Some(self.block0.as_ref()?.clear_check)
+    fn get_clear_check(&self) -> Option<Option<Punch>> {
+        Some(Some(self.block0.as_ref()?.clear_check))
     }
 
     fn get_finish(&self) -> Option<Option<Punch>> {
@@ -206,6 +221,8 @@ impl CardDefinition for Card8Def {
         block_id: u8,
         block_buffer: &[u8; 128],
     ) -> Result<(), crate::errors::FeedBlockError> {
+        self.raw_blocks.insert(block_id, *block_buffer);
+
         match block_id {
             0 => {
                 self.block0 = Some(Block0::deserialize(*block_buffer)?);
@@ -40,8 +40,8 @@
     0x4C..0x4F  SEL_FEEDBACK - See SiacFeedback enum
     0x50..0x53  BRD_FEEDBACK - idk what this does
     0x54..0x57  System values - RBAT LBAT PROT CRC8
-    0x58..0x5B  Start reserve (punch)
-    0x5C..0x5F  Finish reserve (punch)
+    0x58..0x5B  Start reserve (punch) - TSS, CN, TH, TL if the station records sub seconds
+    0x5C..0x5F  Finish reserve (punch) - TSS, CN, TH, TL if the station records sub seconds
     0x60..0x63  SIID0_AC
     0x64..0x67  SIID1_AC
     0x68..0x6B  SIID2_AC
@@ -54,11 +54,16 @@
     Every 4 bytes is a punch - if all 4 bytes are 0xEE then you can stop parsing because there will be no more punches - but you have the punch count to be safe.
 */
 
+use alloc::{collections::BTreeMap, vec::Vec};
+
 use chrono::NaiveDate;
 
 use crate::{
     card::CardPersonalData,
-    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition},
+    carddef::{
+        BlockNeededIntention, BlockNeededResult, CardDefinition, MemoryRegion,
+        with_reserve_subsecond,
+    },
     errors::{DeserializeBlockError, DeserializeCardPersonalDataError},
     extract_fixed,
     punch::Punch,
@@ -151,6 +156,8 @@ struct Block3 {
     rbat: u8,
     start_reserve: Option<Punch>,
     finish_reserve: Option<Punch>,
+    start_reserve_bytes: [u8; 4],
+    finish_reserve_bytes: [u8; 4],
 }
 
 impl Block3 {
@@ -182,10 +189,15 @@ impl Block3 {
         let clear_count = u16::from_be_bytes(clear_count_bytes);
 
         let start_reserve_bytes = extract_fixed!(&data, 0x58..0x5B);
-        let start_reserve = Punch::deserialize_control_punch(&start_reserve_bytes)?;
+        // with the sub seconds the reserve is not a valid punch
+        let start_reserve = Punch::deserialize_control_punch(&start_reserve_bytes)
+            .ok()
+            .flatten();
 
         let finish_reserve_bytes = extract_fixed!(&data, 0x5C..0x5F);
-        let finish_reserve = Punch::deserialize_control_punch(&finish_reserve_bytes)?;
+        let finish_reserve = Punch::deserialize_control_punch(&finish_reserve_bytes)
+            .ok()
+            .flatten();
 
         return Ok(Self {
             clear_check_reserve,
@@ -198,6 +210,8 @@ impl Block3 {
             rbat,
             start_reserve,
             finish_reserve,
+            start_reserve_bytes,
+            finish_reserve_bytes,
         });
     }
 }
@@ -251,6 +265,10 @@ pub struct ActiveCardDef {
     block5: Option<Block5>,
     block6: Option<Block6>,
     block7: Option<Block7>,
+    /// Every block fed to the definition, also the ones which could not be parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    raw_blocks: BTreeMap<u8, [u8; 128]>,
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -307,11 +325,22 @@ pub struct ActiveCardExclusives {
 
 impl CardDefinition for ActiveCardDef {
     const HAS_CARD_EXCLUSIVES: bool = true;
+    const PERSONAL_DATA_REGIONS: &'static [MemoryRegion] = &[
+        MemoryRegion::new(0, 0x20, 96),
+        MemoryRegion::new(1, 0x00, 32),
+    ];
     type CardExclusivesType = ActiveCardExclusives;
     fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
         fn round_to(val: f64, decs: u32) -> f64 {
-            let factor = 10f64.powi(decs as i32);
-            return (val * factor).round() / factor;
+            // f64::round is not available in no_std
+            let factor = 10u32.pow(decs) as f64;
+            let scaled = val * factor;
+            let rounded = if scaled < 0.0 {
+                scaled - 0.5
+            } else {
+                scaled + 0.5
+            };
+            return (rounded as i64) as f64 / factor;
         }
 
         fn calc_voltage(b: u8) -> f64 {
@@ -352,9 +381,14 @@ impl CardDefinition for ActiveCardDef {
             block5: None,
             block6: None,
             block7: None,
+            raw_blocks: BTreeMap::new(),
         }
     }
 
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]> {
+        &self.raw_blocks
+    }
+
     fn get_punches(&self) -> Option<Vec<Punch>> {
         if self.block_needed(&BlockNeededIntention::Punches) != BlockNeededResult::NoNeed {
             return None;
@@ -387,12 +421,17 @@ impl CardDefinition for ActiveCardDef {
         Some(self.block0.as_ref()?.siid)
     }
 
-    fn get_clear_check(&self) -> Option<Punch> {
-        Some(self.block0.as_ref()?.clear_check)
+    fn get_clear_check(&self) -> Option<Option<Punch>> {
+        Some(Some(self.block0.as_ref()?.clear_check))
     }
 
     fn get_finish(&self) -> Option<Option<Punch>> {
-        Some(self.block0.as_ref()?.finish)
+        let finish = self.block0.as_ref()?.finish;
+        let reserve = self
+            .block3
+            .as_ref()
+            .map(|block3| &block3.finish_reserve_bytes);
+        Some(with_reserve_subsecond(finish, reserve))
     }
 
     fn get_personal_data(
@@ -415,7 +454,12 @@ impl CardDefinition for ActiveCardDef {
     }
 
     fn get_start(&self) -> Option<Option<Punch>> {
-        Some(self.block0.as_ref()?.start)
+        let start = self.block0.as_ref()?.start;
+        let reserve = self
+            .block3
+            .as_ref()
+            .map(|block3| &block3.start_reserve_bytes);
+        Some(with_reserve_subsecond(start, reserve))
     }
 
     fn block_needed(&self, intention: &super::BlockNeededIntention) -> super::BlockNeededResult {
@@ -485,6 +529,8 @@ impl CardDefinition for ActiveCardDef {
         block_id: u8,
         block_buffer: &[u8; 128],
     ) -> Result<(), crate::errors::FeedBlockError> {
+        self.raw_blocks.insert(block_id, *block_buffer);
+
         match block_id {
             0 => {
                 self.block0 = Some(Block0::deserialize(*block_buffer)?);
@@ -0,0 +1,475 @@
+/*
+    SPORTident tCard Memory Structure - also used by fCard
+
+    Every integer is big endian encoded unless said differently in the desc.
+
+    ---- BLOCK 0x00 ----
+    0x00..0x03  Unique device ID
+    0x04..0x07  Start of record - 0xEA 0xEA 0xEA 0xEA
+    0x08..0x0B  Clear/Check (punch)
+    0x0C..0x0F  Start (punch)
+    0x10..0x13  Finish (punch)
+    0x14..0x15  Last visited control
+    0x16        Punch pointer - punch count
+    0x18..0x1B  SIID - SI3, SI2, SI1, SI0 - SI2..SI0 makes up the SIID (card number)
+    0x1C        Prod date MONTH
+    0x1D        Prod date YEAR (2000+x)
+    0x20..0x7F  Card personal data
+    ----            ----
+    ---- BLOCK 0x04 ----
+    0x00..0x7F  Punch records (16 records)
+    ----            ----
+    ---- BLOCK 0x05 ----
+    0x00..0x7F  Punch records (16 records)
+    ----            ----
+    ---- BLOCK 0x06 ----
+    0x00..0x7F  Punch records (16 records)
+    ----            ----
+    ---- BLOCK 0x07 ----
+    0x00..0x7F  Punch records (16 records)
+    ----            ----
+
+
+    --------------------
+    SPORTident tCard Punch Record Format (training record)
+
+    Structure: TD, CN, TH, TL, TSS, reserved (8 bytes)
+
+    TD, CN, TH, TL - same as the Card-6 punch format
+    TSS - sub second (1/256 s)
+    Reserved bytes are 0xEE
+*/
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{
+    card::CardPersonalData,
+    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition},
+    errors::{DeserializeBlockError, DeserializeCardPersonalDataError},
+    extract_fixed,
+    punch::Punch,
+};
+
+/// Punch record of the tCard/fCard with the sub second part of the time
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy)]
+pub struct TCardRecord {
+    /// Punch with the sub second
+    pub punch: Punch,
+}
+
+impl TCardRecord {
+    /// Deserializes the punch record
+    ///
+    /// * `data` - Record data (8 bytes)
+    pub fn deserialize(data: &[u8; 8]) -> Result<Self, DeserializeBlockError> {
+        let mut punch = Punch::deserialize(&extract_fixed!(data, 0x00..0x03))?;
+        punch.punch_time.subsecond = Some(data[4]);
+
+        return Ok(Self { punch });
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "TCardBlock0.ts"))]
+#[derive(Debug, Clone)]
+struct Block0 {
+    uid: u32,
+    clear_check: Punch,
+    start: Option<Punch>,
+    finish: Option<Punch>,
+    last_visited: u16,
+    punch_count: u8,
+    siid: u32,
+    prod_month: u8,
+    prod_year: u8,
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
+    #[cfg_attr(feature = "ts-rs", ts(type = "[number; 96]"))]
+    card_personal_data: [u8; 96],
+}
+
+impl Block0 {
+    pub fn deserialize(data: [u8; 128]) -> Result<Self, DeserializeBlockError> {
+        let uid = u32::from_be_bytes(extract_fixed!(&data, 0x00..0x03));
+        let clear_check = Punch::deserialize(&extract_fixed!(&data, 0x08..0x0B))?;
+        let start = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x0C..0x0F))?;
+        let finish = Punch::deserialize_control_punch(&extract_fixed!(&data, 0x10..0x13))?;
+        let last_visited = u16::from_be_bytes(extract_fixed!(&data, 0x14..0x15));
+        let punch_count = data[0x16];
+        let siid = u32::from_be_bytes([0, data[0x19], data[0x1A], data[0x1B]]);
+        let prod_month = data[0x1C];
+        let prod_year = data[0x1D];
+        let card_personal_data = extract_fixed!(&data, 0x20..0x7F);
+
+        return Ok(Self {
+            uid,
+            clear_check,
+            start,
+            finish,
+            last_visited,
+            punch_count,
+            siid,
+            prod_month,
+            prod_year,
+            card_personal_data,
+        });
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export, export_to = "TCardBlock4.ts"))]
+#[derive(Debug, Clone)]
+struct Block4 {
+    records: Vec<TCardRecord>,
+    records_finished: bool,
+}
+
+impl Block4 {
+    pub fn deserialize(data: [u8; 128]) -> Result<Self, DeserializeBlockError> {
+        let mut records: Vec<TCardRecord> = Vec::new();
+        let mut records_finished = false;
+
+        for record_chunk in data.chunks(8) {
+            let record_chunk: &[u8; 8] = record_chunk.try_into().unwrap(); // cant fail
+            // if the punch part is 0xEE 0xEE 0xEE 0xEE then there should be no more data left
+            if record_chunk[..4].iter().all(|&b| b == 0xEE) {
+                records_finished = true;
+                break;
+            }
+
+            records.push(TCardRecord::deserialize(record_chunk)?);
+        }
+
+        return Ok(Self {
+            records,
+            records_finished,
+        });
+    }
+}
+
+type Block5 = Block4;
+type Block6 = Block4;
+type Block7 = Block4;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug)]
+pub struct TCardDef {
+    block0: Option<Block0>,
+    block4: Option<Block4>,
+    block5: Option<Block5>,
+    block6: Option<Block6>,
+    block7: Option<Block7>,
+    /// Every block fed to the definition, also the ones which could not be parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "ts-rs", ts(skip))]
+    raw_blocks: BTreeMap<u8, [u8; 128]>,
+}
+
+impl TCardDef {
+    /// Returns the punch records (with sub seconds), if all of the record blocks were read
+    pub fn get_records(&self) -> Option<Vec<TCardRecord>> {
+        if self.block_needed(&BlockNeededIntention::Punches) != BlockNeededResult::NoNeed {
+            return None;
+        }
+
+        let mut final_records = Vec::new();
+
+        let blocks: [Option<&Block4>; 4] = [
+            self.block4.as_ref(),
+            self.block5.as_ref(),
+            self.block6.as_ref(),
+            self.block7.as_ref(),
+        ];
+
+        for block in blocks.iter().flatten() {
+            final_records.extend_from_slice(&block.records);
+            if block.records_finished {
+                break;
+            }
+        }
+
+        return Some(final_records);
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug)]
+pub struct TCardExclusives {
+    pub uid: u32,
+    pub last_visited_station_code: u16,
+    pub prod_date_month: u8,
+    pub prod_date_year: u32,
+    /// Punch records including the sub seconds. Empty if the punches were not read.
+    pub records: Vec<TCardRecord>,
+}
+
+impl CardDefinition for TCardDef {
+    const HAS_CARD_EXCLUSIVES: bool = true;
+    type CardExclusivesType = TCardExclusives;
+    fn get_exclusives(&self) -> Option<Self::CardExclusivesType> {
+        let block0 = self.block0.as_ref()?;
+
+        return Some(Self::CardExclusivesType {
+            uid: block0.uid,
+            last_visited_station_code: block0.last_visited,
+            prod_date_month: block0.prod_month,
+            prod_date_year: 2000 + block0.prod_year as u32,
+            records: self.get_records().unwrap_or_default(),
+        });
+    }
+
+    fn new_empty() -> Self {
+        Self {
+            block0: None,
+            block4: None,
+            block5: None,
+            block6: None,
+            block7: None,
+            raw_blocks: BTreeMap::new(),
+        }
+    }
+
+    fn raw_blocks(&self) -> &BTreeMap<u8, [u8; 128]> {
+        &self.raw_blocks
+    }
+
+    fn get_punches(&self) -> Option<Vec<Punch>> {
+        let records = self.get_records()?;
+
+        return Some(records.iter().map(|record| record.punch).collect());
+    }
+
+    fn get_siid(&self) -> Option<u32> {
+        Some(self.block0.as_ref()?.siid)
+    }
+
+    fn get_clear_check(&self) -> Option<Option<Punch>> {
+        Some(Some(self.block0.as_ref()?.clear_check))
+    }
+
+    fn get_finish(&self) -> Option<Option<Punch>> {
+        Some(self.block0.as_ref()?.finish)
+    }
+
+    fn get_personal_data(
+        &self,
+    ) -> Option<Result<CardPersonalData, DeserializeCardPersonalDataError>> {
+        let block0 = &self.block0.as_ref()?;
+
+        return Some(CardPersonalData::deserialize(&block0.card_personal_data));
+    }
+
+    fn get_punch_count(&self) -> Option<u8> {
+        Some(self.block0.as_ref()?.punch_count)
+    }
+
+    fn get_start(&self) -> Option<Option<Punch>> {
+        Some(self.block0.as_ref()?.start)
+    }
+
+    fn block_needed(&self, intention: &BlockNeededIntention) -> BlockNeededResult {
+        match intention {
+            BlockNeededIntention::CardExclusives | BlockNeededIntention::CardPersonalData => {
+                match self.block0 {
+                    Some(_) => BlockNeededResult::NoNeed,
+                    None => BlockNeededResult::Need(0),
+                }
+            }
+            BlockNeededIntention::Punches => {
+                for (block_id, block) in [
+                    (4, &self.block4),
+                    (5, &self.block5),
+                    (6, &self.block6),
+                    (7, &self.block7),
+                ] {
+                    match block {
+                        None => return BlockNeededResult::Need(block_id),
+                        Some(block) => {
+                            if block.records_finished {
+                                return BlockNeededResult::NoNeed;
+                            }
+                        }
+                    }
+                }
+
+                return BlockNeededResult::NoNeed;
+            }
+        }
+    }
+
+    fn has_block(&self, block_id: u8) -> bool {
+        match block_id {
+            0 => self.block0.is_some(),
+            4 => self.block4.is_some(),
+            5 => self.block5.is_some(),
+            6 => self.block6.is_some(),
+            7 => self.block7.is_some(),
+            _ => false,
+        }
+    }
+
+    fn feed_block(
+        &mut self,
+        block_id: u8,
+        block_buffer: &[u8; 128],
+    ) -> Result<(), crate::errors::FeedBlockError> {
+        self.raw_blocks.insert(block_id, *block_buffer);
+
+        match block_id {
+            0 => {
+                self.block0 = Some(Block0::deserialize(*block_buffer)?);
+            }
+            4 => {
+                self.block4 = Some(Block4::deserialize(*block_buffer)?);
+            }
+            5 => {
+                self.block5 = Some(Block5::deserialize(*block_buffer)?);
+            }
+            6 => {
+                self.block6 = Some(Block6::deserialize(*block_buffer)?);
+            }
+            7 => {
+                self.block7 = Some(Block7::deserialize(*block_buffer)?);
+            }
+            _ => return Err(crate::errors::FeedBlockError::BlockDoesNotExist),
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        carddef::fcard::FCardDef,
+        fixtures::{newer_card_block0, read_punches, time},
+    };
+    use chrono::NaiveTime;
+
+    /// Block 0 of the card 6000123 with the check, start and finish
+    fn block0(punch_count: u8) -> [u8; 128] {
+        return newer_card_block0(6_000_123, 3600, 7200, punch_count);
+    }
+
+    /// Record blocks 4..7 with `count` records (TD, CN, TH, TL, TSS, 3 reserved bytes)
+    fn record_blocks(count: usize) -> BTreeMap<u8, [u8; 128]> {
+        let mut blocks = BTreeMap::new();
+        for block_id in 4u8..=7 {
+            let mut block = [0xEE; 128];
+            for i in 0..16 {
+                let n = (block_id as usize - 4) * 16 + i;
+                if n >= count {
+                    break;
+                }
+                let [th, tl] = (4000 + n as u16).to_be_bytes();
+                block[i * 8..i * 8 + 5].copy_from_slice(&[
+                    0b0000_0010,
+                    31 + n as u8,
+                    th,
+                    tl,
+                    n as u8,
+                ]);
+            }
+            blocks.insert(block_id, block);
+        }
+        return blocks;
+    }
+
+    #[test]
+    fn header_and_records() {
+        let mut carddef = TCardDef::new_empty();
+        carddef.feed_block(0, &block0(3)).unwrap();
+        assert_eq!(read_punches(&mut carddef, &record_blocks(3)), [4]);
+
+        assert_eq!(carddef.get_siid(), Some(6_000_123));
+        assert_eq!(carddef.get_punch_count(), Some(3));
+        assert_eq!(
+            carddef.get_clear_check().unwrap().unwrap().punch_time.time,
+            time(3540)
+        );
+        assert_eq!(
+            carddef.get_start().unwrap().unwrap().punch_time.time,
+            time(3600)
+        );
+        assert_eq!(
+            carddef.get_finish().unwrap().unwrap().punch_time.time,
+            time(7200)
+        );
+        let personal_data = carddef.get_personal_data().unwrap().unwrap();
+        assert_eq!(personal_data.last_name.as_deref(), Some("Novak"));
+
+        let records = carddef.get_records().unwrap();
+        let read: Vec<(u16, NaiveTime, Option<u8>)> = records
+            .iter()
+            .map(|record| {
+                let punch_time = record.punch.punch_time;
+                (
+                    record.punch.station_code,
+                    punch_time.time,
+                    punch_time.subsecond,
+                )
+            })
+            .collect();
+        assert_eq!(
+            read,
+            [
+                (31, time(4000), Some(0)),
+                (32, time(4001), Some(1)),
+                (33, time(4002), Some(2)),
+            ]
+        );
+        assert_eq!(carddef.get_punches().unwrap().len(), 3);
+        assert_eq!(carddef.get_exclusives().unwrap().records.len(), 3);
+    }
+
+    #[test]
+    fn records_over_more_blocks() {
+        let mut carddef = TCardDef::new_empty();
+        carddef.feed_block(0, &block0(40)).unwrap();
+        // block 4 and 5 are full
+        assert_eq!(read_punches(&mut carddef, &record_blocks(40)), [4, 5, 6]);
+
+        let punches = carddef.get_punches().unwrap();
+        assert_eq!(punches.len(), 40);
+        assert_eq!(punches[16].station_code, 47);
+        assert_eq!(punches[39].punch_time.subsecond, Some(39));
+    }
+
+    #[test]
+    fn full_card() {
+        let mut carddef = TCardDef::new_empty();
+        assert_eq!(read_punches(&mut carddef, &record_blocks(64)), [4, 5, 6, 7]);
+        assert_eq!(carddef.get_punches().unwrap().len(), 64);
+    }
+
+    #[test]
+    fn punches_are_not_complete_without_every_block() {
+        let blocks = record_blocks(20);
+        let mut carddef = TCardDef::new_empty();
+        carddef.feed_block(4, &blocks[&4]).unwrap();
+        assert!(carddef.get_punches().is_none());
+        carddef.feed_block(5, &blocks[&5]).unwrap();
+        assert_eq!(carddef.get_punches().unwrap().len(), 20);
+    }
+
+    #[test]
+    fn fcard_uses_the_tcard_layout() {
+        let mut carddef = FCardDef::new_empty();
+        carddef.feed_block(0, &block0(5)).unwrap();
+        assert_eq!(read_punches(&mut carddef, &record_blocks(5)), [4]);
+        assert_eq!(carddef.get_siid(), Some(6_000_123));
+        assert_eq!(
+            carddef.get_records().unwrap()[4].punch.punch_time.subsecond,
+            Some(4)
+        );
+        assert!(carddef.feed_block(1, &[0xEE; 128]).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use alloc::{string::String, vec::Vec};
+
+/// Printer charset used by the cards (card byte, ISO-8859-1 byte)
+const PRINTER_CHARSET: [(u8, u8); 34] = [
+    (0x80, 0xC7),
+    (0x81, 0xFC),
+    (0x82, 0xE9),
+    (0x83, 0xE2),
+    (0x84, 0xE4),
+    (0x85, 0xE0),
+    (0x86, 0xE5),
+    (0x87, 0xE7),
+    (0x88, 0xEA),
+    (0x89, 0xEB),
+    (0x8A, 0xE8),
+    (0x8B, 0xEF),
+    (0x8C, 0xEE),
+    (0x8D, 0xEC),
+    (0x8E, 0xC4),
+    (0x8F, 0xC5),
+    (0x90, 0xC9),
+    (0x91, 0xE6),
+    (0x92, 0xC6),
+    (0x93, 0xF4),
+    (0x94, 0xF6),
+    (0x95, 0xF2),
+    (0x96, 0xFB),
+    (0x97, 0xF9),
+    (0x98, 0xFF),
+    (0x99, 0xD6),
+    (0x9A, 0xDC),
+    (0xA0, 0xE1),
+    (0xA1, 0xED),
+    (0xA2, 0xF3),
+    (0xA3, 0xFA),
+    (0xA4, 0xF1),
+    (0xA5, 0xD1),
+    (0xE1, 0xDF),
+];
+
+/// Replaces the printer charset bytes (read from the card) with ISO-8859-1 bytes
+pub fn replace_printer_charset_bytes(data: &[u8]) -> Vec<u8> {
+    return data
+        .iter()
+        .map(|b| {
+            PRINTER_CHARSET
+                .iter()
+                .find(|(card, _)| card == b)
+                .map_or(*b, |(_, iso)| *iso)
+        })
+        .collect();
+}
+
+/// Replaces the ISO-8859-1 bytes with the printer charset bytes (written to the card).
+/// Reverse of `replace_printer_charset_bytes`.
+pub fn encode_printer_charset_bytes(data: &[u8]) -> Vec<u8> {
+    return data
+        .iter()
+        .map(|b| {
+            PRINTER_CHARSET
+                .iter()
+                .find(|(_, iso)| iso == b)
+                .map_or(*b, |(card, _)| *card)
+        })
+        .collect();
+}
+
+/// Encodes the string to ISO-8859-1. `None` if it contains a character outside of ISO-8859-1.
+pub fn encode_iso_8859_1(s: &str) -> Option<Vec<u8>> {
+    return s.chars().map(|c| u8::try_from(c as u32).ok()).collect();
+}
+
+/// Decodes ISO-8859-1 bytes (every byte is the code point of its character)
+pub fn decode_iso_8859_1(data: &[u8]) -> Result<String, alloc::string::FromUtf8Error> {
+    return Ok(data.iter().map(|b| *b as char).collect());
+}
@@ -0,0 +1,79 @@
+use alloc::string::{FromUtf8Error, String};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FrameError {
+    #[error("parse error")]
+    ParseError,
+    #[error("crc is invalid")]
+    CrcError,
+}
+
+#[derive(Debug, Error)]
+pub enum DeserializePacketError {
+    #[error("frame error {0}")]
+    FrameError(#[from] FrameError),
+    #[error("response is NAK")]
+    ResponseIsNak,
+    #[error("data len is wrong")]
+    WrongDataLen,
+    #[error("deserialize punch error: {0}")]
+    DeserializePunchError(#[from] DeserializePunchError),
+    #[error("{0}")]
+    Other(String),
+}
+
+#[derive(Debug, Error)]
+pub enum DeserializePunchError {
+    #[error("Invalid punch time")]
+    InvalidTime,
+    #[error("Invalid day")]
+    InvalidDay,
+    #[error("Invalid week")]
+    InvalidWeek,
+    #[error("The input is cleared (0xFF)")]
+    DataCleared,
+    #[error("Invalid data")]
+    InvalidData,
+}
+
+#[derive(Debug, Error)]
+pub enum DeserializeCardPersonalDataError {
+    #[error("Required fields (first and last name) are empty")]
+    RequiredFieldsAreEmpty,
+    #[error("From UTF8 error: {0}")]
+    FromUtf8Error(#[from] FromUtf8Error),
+    #[error("Data is too long")]
+    DataTooLong,
+}
+
+#[derive(Debug, Error)]
+pub enum SerializeCardPersonalDataError {
+    #[error("Required fields (first and last name) are empty")]
+    RequiredFieldsAreEmpty,
+    #[error("A field contains a character which can not be encoded (ISO-8859-1 only)")]
+    InvalidCharacter,
+    #[error("A field contains the separator (;)")]
+    ContainsSeparator,
+    #[error("Data is too long")]
+    DataTooLong,
+}
+
+#[derive(Debug, Error)]
+pub enum FeedBlockError {
+    #[error("The provided block buffer does not match with the block id")]
+    BlockIdAndBufferNotMatching,
+    #[error("This card definition does no have that block")]
+    BlockDoesNotExist,
+    #[error("Deserialize block error: {0}")]
+    DeserializeBlockError(#[from] DeserializeBlockError),
+}
+
+#[derive(Debug, Error)]
+pub enum DeserializeBlockError {
+    #[error("Deserialize punch error {0}")]
+    DeserializePunchError(#[from] DeserializePunchError),
+    #[error("Could not deserialize a date")]
+    DateError,
+}
@@ -0,0 +1,165 @@
+/*
+    Card fixtures for the tests
+
+    Raw blocks of the cards, as the station sends them, shared by the card definition tests of this crate
+    and the integration tests of sident (through the `fixtures` feature). Not a part of the API.
+
+    Punches are (control code, 12h seconds) pairs. Every full punch (TD, CN, TH, TL) is in the morning of Monday, week 1.
+*/
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use chrono::NaiveTime;
+
+use crate::{
+    card::CardPersonalData,
+    carddef::{BlockNeededIntention, BlockNeededResult, CardDefinition, si5::Card5Def},
+};
+
+/// Punch (TD, CN, TH, TL) in the morning of Monday
+pub fn punch(code: u8, seconds: u16) -> [u8; 4] {
+    let [th, tl] = seconds.to_be_bytes();
+    return [0b0000_0010, code, th, tl];
+}
+
+/// Time `seconds` after midnight
+pub fn time(seconds: u16) -> NaiveTime {
+    return NaiveTime::from_num_seconds_from_midnight_opt(seconds as u32, 0).unwrap();
+}
+
+/// Feeds the blocks the definition asks for, returns the order of the fed blocks
+pub fn read_punches<T: CardDefinition>(
+    carddef: &mut T,
+    blocks: &BTreeMap<u8, [u8; 128]>,
+) -> Vec<u8> {
+    let mut fed = Vec::new();
+    while let BlockNeededResult::Need(block_id) =
+        carddef.block_needed(&BlockNeededIntention::Punches)
+    {
+        carddef.feed_block(block_id, &blocks[&block_id]).unwrap();
+        fed.push(block_id);
+    }
+    return fed;
+}
+
+/// Blocks with the personal data of `T` written over the personal data regions
+pub fn with_personal_data<T: CardDefinition>(
+    blocks: &BTreeMap<u8, [u8; 128]>,
+    data: &CardPersonalData,
+) -> BTreeMap<u8, [u8; 128]> {
+    let serialized = T::serialize_personal_data(data).unwrap();
+    let mut blocks = blocks.clone();
+    let mut position = 0;
+    for region in T::PERSONAL_DATA_REGIONS {
+        let block = blocks.get_mut(&region.block).unwrap();
+        let offset = region.offset as usize;
+        let len = region.len as usize;
+        block[offset..offset + len].copy_from_slice(&serialized[position..position + len]);
+        position += len;
+    }
+    return blocks;
+}
+
+/// Card 5 block - SIID, start number 42, start, finish, check and punches.
+/// Punches 31-36 have no time, only the code in the first byte of the row.
+pub fn card5_block(
+    siid: u32,
+    start: Option<u16>,
+    finish: Option<u16>,
+    check: Option<u16>,
+    punches: &[(u8, u16)],
+) -> [u8; 128] {
+    let (series, number) = Card5Def::encode_siid(siid);
+    let mut block = [0u8; 128];
+    block[0x04..0x06].copy_from_slice(&number.to_be_bytes());
+    block[0x06] = series;
+    block[0x11..0x13].copy_from_slice(&42u16.to_be_bytes());
+    block[0x13..0x15].copy_from_slice(&start.unwrap_or(0xEEEE).to_be_bytes());
+    block[0x15..0x17].copy_from_slice(&finish.unwrap_or(0xEEEE).to_be_bytes());
+    block[0x17] = punches.len() as u8 + 1;
+    block[0x19..0x1B].copy_from_slice(&check.unwrap_or(0xEEEE).to_be_bytes());
+
+    for (i, (code, seconds)) in punches.iter().enumerate() {
+        if i >= 30 {
+            block[0x20 + (i - 30) * 16] = *code;
+            continue;
+        }
+        let offset = 0x20 + i / 5 * 16 + 1 + i % 5 * 3;
+        block[offset] = *code;
+        block[offset + 1..offset + 3].copy_from_slice(&seconds.to_be_bytes());
+    }
+
+    return block;
+}
+
+/// Card 6 blocks - block 0 (check a minute and clear ten minutes before the start, start number 42), block 1
+/// and the punch blocks in the order 6, 7, 2, 3, 4, 5. Blocks 2..5 are only there for more than 64 punches (Card 6*).
+pub fn card6_blocks(
+    siid: u32,
+    start: u16,
+    finish: u16,
+    punches: &[(u8, u16)],
+) -> BTreeMap<u8, [u8; 128]> {
+    let mut block0 = [0xEEu8; 128];
+    block0[0x0B..0x0E].copy_from_slice(&siid.to_be_bytes()[1..]);
+    block0[0x12] = punches.len() as u8;
+    block0[0x14..0x18].copy_from_slice(&punch(0, finish));
+    block0[0x18..0x1C].copy_from_slice(&punch(0, start));
+    block0[0x1C..0x20].copy_from_slice(&punch(0, start.saturating_sub(60)));
+    block0[0x20..0x24].copy_from_slice(&punch(0, start.saturating_sub(600)));
+    block0[0x28..0x2C].copy_from_slice(&42u32.to_le_bytes());
+
+    let order = [6u8, 7, 2, 3, 4, 5];
+    let block_count = if punches.len() > 64 { 6 } else { 2 };
+    let mut blocks = BTreeMap::from([(0, block0), (1, [0xEE; 128])]);
+    for block_number in &order[..block_count] {
+        blocks.insert(*block_number, [0xEE; 128]);
+    }
+
+    for (i, (code, seconds)) in punches.iter().enumerate() {
+        let block = blocks.get_mut(&order[i / 32]).unwrap();
+        let offset = (i % 32) * 4;
+        block[offset..offset + 4].copy_from_slice(&punch(*code, *seconds));
+    }
+
+    return blocks;
+}
+
+/// Block 0 of Card 8 and newer cards - check a minute before the start, start, finish, punch count, SIID
+/// and the personal data "Jan;Novak;". The rest (the punches of Card 9) is 0xEE.
+pub fn newer_card_block0(siid: u32, start: u16, finish: u16, punch_count: u8) -> [u8; 128] {
+    let mut block0 = [0xEEu8; 128];
+    block0[0x00..0x04].copy_from_slice(&0x1234_5678u32.to_be_bytes());
+    block0[0x04..0x08].copy_from_slice(&[0xEA; 4]);
+    block0[0x08..0x0C].copy_from_slice(&punch(0, start.saturating_sub(60)));
+    block0[0x0C..0x10].copy_from_slice(&punch(0, start));
+    block0[0x10..0x14].copy_from_slice(&punch(0, finish));
+    block0[0x16] = punch_count;
+    block0[0x18..0x1C].copy_from_slice(&siid.to_be_bytes());
+    block0[0x1C] = 1;
+    block0[0x1D] = 20;
+    block0[0x20..0x2B].copy_from_slice(b"Jan;Novak;\0");
+    return block0;
+}
+
+/// Writes the punches (TD, CN, TH, TL) as records of `record_len` bytes from `offset`.
+/// Bytes of the record after the punch are 0xEE.
+pub fn write_punches(
+    block: &mut [u8; 128],
+    offset: usize,
+    record_len: usize,
+    punches: &[(u8, u16)],
+) {
+    for (i, (code, seconds)) in punches.iter().enumerate() {
+        let start = offset + i * record_len;
+        block[start..start + 4].copy_from_slice(&punch(*code, *seconds));
+        block[start + 4..start + record_len].fill(0xEE);
+    }
+}
+
+/// Block 3 of Card 10, Card 11 and SIAC - check reserve and the production date, no start/finish reserve
+pub fn si10_block3() -> [u8; 128] {
+    let mut block3 = [0xEEu8; 128];
+    block3[0x38..0x3C].copy_from_slice(&punch(0, 0));
+    block3[0x3C..0x3F].copy_from_slice(&[20, 1, 1]);
+    return block3;
+}
@@ -0,0 +1,391 @@
+/*
+    Framing of the SPORTident protocol
+
+    ---- EXTENDED PROTOCOL ----
+    STX  ID  LEN  DATA (LEN bytes)  CRC (2 bytes, big endian)  ETX
+    CRC is computed from ID, LEN and DATA
+    ----            ----
+
+    ---- BASE PROTOCOL ----
+    STX  ID  DATA  ETX
+    Data bytes 0x00-0x1F are prefixed with DLE
+    ----            ----
+
+    A single NAK byte (outside of a frame) is a negative response.
+*/
+
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use crate::{
+    crc::crc16,
+    errors::FrameError,
+    is_extended_packet_id,
+    packet::{RawPacket, RawPacketBody},
+};
+
+pub mod consts {
+    /// Start of text - first byte transmitted
+    pub const STX: u8 = 0x02;
+    /// End of text - last byte transmitted
+    pub const ETX: u8 = 0x03;
+    /// Positive handshake result
+    pub const ACK: u8 = 0x06;
+    /// Negative handshake result
+    pub const NAK: u8 = 0x15;
+    /// Delimiter inserted before data characters 00-1F (base protocol)
+    pub const DLE: u8 = 0x10;
+}
+
+use consts::{DLE, ETX, NAK, STX};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseState {
+    WaitingForStart,
+    ReadingHeader,
+    ReadingData,
+    ReadingCrc,
+    WaitingForEtx,
+}
+
+/// Longest data of a base protocol frame (after removing `DLE`).
+/// The base frames have no length byte, so a frame with a lost `ETX` is dropped after this many bytes.
+pub const BASE_MAX_DATA_LEN: usize = 255;
+
+/// Sans-IO decoder of the frames. It is fed the received bytes and returns the decoded raw packets,
+/// timing and IO are up to the caller.
+///
+/// Frames can be split across several `push` calls. Bytes before `STX` and repeated `STX` are skipped.
+/// If the `ETX` of the frame is missing, bytes were lost and the decoder resyncs on the next `STX`
+/// inside of the broken frame, if there is one. A complete frame with an invalid CRC is returned as `CrcError`.
+///
+/// # Example
+/// ```
+/// use sident_core::{
+///     errors::FrameError,
+///     framing::{FrameDecoder, consts::NAK},
+///     packet::{RawPacket, RawPacketBody},
+/// };
+///
+/// let frame = RawPacketBody { id: 0xF0, data: vec![0x00, 0x01, 0x4D] }.serialize();
+///
+/// let mut decoder = FrameDecoder::new();
+/// // garbage, then the first half of the frame
+/// let mut data = vec![0xFF];
+/// data.extend_from_slice(&frame[..5]);
+/// assert_eq!(decoder.push(&data).count(), 0);
+/// assert!(!decoder.is_idle());
+///
+/// // rest of the frame, NAK and a frame with a broken CRC
+/// let mut data = frame[5..].to_vec();
+/// data.push(NAK);
+/// let mut broken = frame.clone();
+/// broken[6] ^= 0xFF;
+/// data.extend_from_slice(&broken);
+///
+/// let mut packets = decoder.push(&data);
+/// let Some(Ok(RawPacket::Body(body))) = packets.next() else {
+///     panic!("frame not decoded");
+/// };
+/// assert_eq!(body.id, 0xF0);
+/// assert_eq!(body.data, vec![0x00, 0x01, 0x4D]);
+/// assert!(matches!(packets.next(), Some(Ok(RawPacket::Nak))));
+/// assert!(matches!(packets.next(), Some(Err(FrameError::CrcError))));
+/// assert!(packets.next().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameDecoder {
+    /// Bytes pushed, but not parsed yet
+    input: VecDeque<u8>,
+    /// Bytes of the current frame (from `STX`)
+    frame: Vec<u8>,
+    state: ParseState,
+    body: RawPacketBody,
+    data_len: usize,
+    /// base protocol - the previous byte was DLE, so this one is data
+    dle_pending: bool,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        return Self {
+            input: VecDeque::new(),
+            frame: Vec::new(),
+            state: ParseState::WaitingForStart,
+            body: RawPacketBody {
+                id: 0,
+                data: vec![],
+            },
+            data_len: 0,
+            dle_pending: false,
+        };
+    }
+
+    /// Adds the received bytes to the decoder and returns an iterator over the packets completed by them.
+    ///
+    /// Bytes are decoded lazily while iterating. Packets which were not taken from the iterator
+    /// are returned by the next `push` (or `next_frame`).
+    ///
+    /// * `data` - Received bytes
+    pub fn push(&mut self, data: &[u8]) -> Frames<'_> {
+        self.input.extend(data);
+        return Frames { decoder: self };
+    }
+
+    /// Decodes the pushed bytes until a frame is complete.
+    ///
+    /// Returns `None` if more bytes are needed.
+    pub fn next_frame(&mut self) -> Option<Result<RawPacket, FrameError>> {
+        while let Some(byte) = self.input.pop_front() {
+            if let Some(result) = self.feed(byte) {
+                return Some(result);
+            }
+        }
+
+        return None;
+    }
+
+    /// Returns true if the decoder is not in the middle of a frame (waiting for `STX`).
+    /// Useful for choosing the timeout of the next byte.
+    pub fn is_idle(&self) -> bool {
+        return self.state == ParseState::WaitingForStart;
+    }
+
+    /// Drops the partial frame and all of the bytes which were not parsed yet (e.g. after a timeout)
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.start_over();
+    }
+
+    /// Drops the partial frame
+    fn start_over(&mut self) {
+        self.frame.clear();
+        self.state = ParseState::WaitingForStart;
+        self.body = RawPacketBody {
+            id: 0,
+            data: vec![],
+        };
+        self.data_len = 0;
+        self.dle_pending = false;
+    }
+
+    fn feed(&mut self, byte: u8) -> Option<Result<RawPacket, FrameError>> {
+        if self.state != ParseState::WaitingForStart {
+            self.frame.push(byte);
+        }
+
+        match self.state {
+            ParseState::WaitingForStart => {
+                if byte == STX {
+                    self.frame.push(byte);
+                    self.state = ParseState::ReadingHeader;
+                } else if byte == NAK {
+                    return Some(Ok(RawPacket::Nak));
+                }
+            }
+            ParseState::ReadingHeader => {
+                if self.frame.len() == 2 && byte == STX {
+                    // STX can be sent multiple times (wakeup)
+                    self.frame.pop();
+                } else if self.frame.len() == 2 {
+                    self.body.id = byte;
+                    if !is_extended_packet_id(byte) {
+                        self.state = ParseState::WaitingForEtx;
+                    }
+                } else {
+                    self.data_len = byte as usize;
+                    self.state = if self.data_len == 0 {
+                        // no data (e.g. GetSICard5), next byte is already the CRC
+                        ParseState::ReadingCrc
+                    } else {
+                        ParseState::ReadingData
+                    };
+                }
+            }
+            ParseState::ReadingData => {
+                self.body.data.push(byte);
+                if self.body.data.len() == self.data_len {
+                    self.state = ParseState::ReadingCrc;
+                }
+            }
+            ParseState::ReadingCrc => {
+                if self.frame.len() == 3 + self.data_len + 2 {
+                    self.state = ParseState::WaitingForEtx;
+                }
+            }
+            ParseState::WaitingForEtx => {
+                if !is_extended_packet_id(self.body.id) {
+                    // BASE
+                    if self.dle_pending {
+                        self.dle_pending = false;
+                        self.body.data.push(byte);
+                    } else if byte == DLE {
+                        self.dle_pending = true;
+                    } else if byte == ETX {
+                        return Some(Ok(self.finish_frame()));
+                    } else {
+                        self.body.data.push(byte);
+                    }
+
+                    if self.body.data.len() > BASE_MAX_DATA_LEN {
+                        return self.resync(FrameError::ParseError);
+                    }
+                    return None;
+                }
+
+                // EXTENDED
+                if byte != ETX {
+                    return self.resync(FrameError::ParseError);
+                }
+
+                let crc_start = 3 + self.data_len;
+                let crc = u16::from_be_bytes([self.frame[crc_start], self.frame[crc_start + 1]]);
+                if crc16(&self.frame[1..crc_start]) != crc {
+                    // the frame is complete, so the STX was real and there is nothing to resync on
+                    log::error!("broken frame: {}", FrameError::CrcError);
+                    self.start_over();
+                    return Some(Err(FrameError::CrcError));
+                }
+                return Some(Ok(self.finish_frame()));
+            }
+        }
+
+        return None;
+    }
+
+    fn finish_frame(&mut self) -> RawPacket {
+        let body = core::mem::replace(
+            &mut self.body,
+            RawPacketBody {
+                id: 0,
+                data: vec![],
+            },
+        );
+        self.start_over();
+        return RawPacket::Body(body);
+    }
+
+    /// `ETX` is missing, so bytes were lost and the `STX` of the broken frame may not be the real frame start.
+    /// Scans to the next `STX` after the false one and parses again from there.
+    fn resync(&mut self, error: FrameError) -> Option<Result<RawPacket, FrameError>> {
+        let Some(next_stx) = self.frame[1..].iter().position(|b| *b == STX) else {
+            log::error!("broken frame: {}", error);
+            self.start_over();
+            return Some(Err(error));
+        };
+
+        log::warn!("broken frame ({}), resyncing on the next STX", error);
+        let rest: Vec<u8> = self.frame.drain(1 + next_stx..).collect();
+        for byte in rest.into_iter().rev() {
+            self.input.push_front(byte);
+        }
+        self.start_over();
+        return None;
+    }
+}
+
+/// Iterator over the packets decoded from the pushed bytes, see `FrameDecoder::push`
+#[derive(Debug)]
+pub struct Frames<'a> {
+    decoder: &'a mut FrameDecoder,
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Result<RawPacket, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.decoder.next_frame();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extended_frame(id: u8, data: &[u8]) -> Vec<u8> {
+        return RawPacketBody {
+            id,
+            data: data.to_vec(),
+        }
+        .serialize();
+    }
+
+    #[test]
+    fn crc_error_with_stx_in_data_is_not_resynced() {
+        let mut frame = extended_frame(
+            0xEF,
+            &[0x00, 0x0A, 0x00, 0x02, 0x31, 0x44, 0x55, 0x03, 0x77, 0x88],
+        );
+        let crc_index = frame.len() - 3;
+        frame[crc_index] ^= 0xFF;
+
+        let mut decoder = FrameDecoder::new();
+        let packets: Vec<_> = decoder.push(&frame).collect();
+        assert_eq!(packets.len(), 1);
+        assert!(matches!(packets[0], Err(FrameError::CrcError)));
+        assert!(decoder.is_idle());
+
+        let next = extended_frame(0xF0, &[0x00, 0x01, 0x4D]);
+        let Some(Ok(RawPacket::Body(body))) = decoder.push(&next).next() else {
+            panic!("frame after the CRC error not decoded");
+        };
+        assert_eq!(body.id, 0xF0);
+    }
+
+    #[test]
+    fn repeated_stx_is_skipped() {
+        let mut data = vec![0xFF, STX];
+        data.extend_from_slice(&extended_frame(0xF0, &[0x4D]));
+
+        let mut decoder = FrameDecoder::new();
+        let Some(Ok(RawPacket::Body(body))) = decoder.push(&data).next() else {
+            panic!("frame after wakeup not decoded");
+        };
+        assert_eq!(body.id, 0xF0);
+        assert_eq!(body.data, vec![0x4D]);
+    }
+
+    #[test]
+    fn missing_etx_resyncs_on_next_stx() {
+        let mut broken = extended_frame(0xEF, &[0x00, 0x0A, 0x01]);
+        broken.pop();
+        let good = extended_frame(0xF0, &[0x00, 0x01, 0x4D]);
+
+        let mut data = broken;
+        data.extend_from_slice(&good);
+
+        let mut decoder = FrameDecoder::new();
+        let packets: Vec<_> = decoder.push(&data).collect();
+        assert_eq!(packets.len(), 1);
+        let Ok(RawPacket::Body(body)) = &packets[0] else {
+            panic!("frame after the missing ETX not decoded");
+        };
+        assert_eq!(body.id, 0xF0);
+    }
+
+    #[test]
+    fn base_frame_without_etx_is_capped() {
+        let mut decoder = FrameDecoder::new();
+        let mut data = vec![STX, 0x70];
+        data.extend(core::iter::repeat_n(0x41, BASE_MAX_DATA_LEN + 1));
+        let packets: Vec<_> = decoder.push(&data).collect();
+        assert_eq!(packets.len(), 1);
+        assert!(matches!(packets[0], Err(FrameError::ParseError)));
+        assert!(decoder.is_idle());
+
+        let good = RawPacketBody {
+            id: 0x70,
+            data: vec![0x03, 0x4D],
+        }
+        .serialize();
+        let Some(Ok(RawPacket::Body(body))) = decoder.push(&good).next() else {
+            panic!("base frame after the capped one not decoded");
+        };
+        assert_eq!(body.data, vec![0x03, 0x4D]);
+    }
+}
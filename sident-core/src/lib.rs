@@ -0,0 +1,37 @@
+//! Protocol core of sident - framing, CRC, punches and card block decoders.
+//!
+//! The crate is `no_std` (with `alloc`) unless the `std` feature is enabled, so the same code can decode SPORTident frames on a microcontroller.
+//! Everything related to IO lives in the `sident` crate.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod card;
+pub mod carddef;
+pub mod charset;
+pub mod crc;
+pub mod errors;
+#[cfg(any(test, feature = "fixtures"))]
+#[doc(hidden)]
+pub mod fixtures;
+pub mod framing;
+pub mod macros;
+pub mod packet;
+pub mod punch;
+pub mod time;
+
+/// Returns if the input packet ID is a packet in extended protocol
+///
+/// # Example
+/// ```
+/// use sident_core::is_extended_packet_id;
+///
+/// assert!(is_extended_packet_id(0xFE));
+/// ```
+pub fn is_extended_packet_id(id: u8) -> bool {
+    if id < 0x80 || id == 0xC4 {
+        return false;
+    }
+    true
+}
@@ -0,0 +1,17 @@
+#[macro_export]
+macro_rules! check_vec_len {
+    ($vec:expr, $expected_len:expr, $err_variant:expr) => {
+        if $vec.len() != $expected_len as usize {
+            return Err($err_variant);
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! extract_fixed {
+    ($data:expr, $range:expr) => {{
+        const LEN: usize = $range.end - $range.start + 1;
+        let slice = $data.get($range.start..=$range.end).unwrap();
+        <[u8; LEN]>::try_from(slice).unwrap()
+    }};
+}
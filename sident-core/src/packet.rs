@@ -1,10 +1,11 @@
-use std::fmt::Debug;
+use alloc::vec::Vec;
+use core::fmt::Debug;
 
 use crate::{
     check_vec_len,
-    codec::consts::{ETX, STX},
     crc::crc16,
     errors::DeserializePacketError,
+    framing::consts::{DLE, ETX, STX},
     is_extended_packet_id,
 };
 
@@ -22,6 +23,46 @@ pub struct RawPacketBody {
     pub data: Vec<u8>,
 }
 
+impl RawPacketBody {
+    /// Serializes the packet body to **ready-to-send format** (framing, length and CRC included)
+    ///
+    /// Base protocol packets have no length and CRC, but data bytes 0x00-0x1F are prefixed with `DLE`.
+    ///
+    /// # Example
+    /// ```
+    /// use sident_core::{framing::consts::{DLE, ETX, STX}, packet::RawPacketBody};
+    ///
+    /// let body = RawPacketBody { id: 0x70, data: vec![0x03, 0x4D] };
+    /// assert_eq!(body.serialize(), vec![STX, 0x70, DLE, 0x03, 0x4D, ETX]);
+    /// ```
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+
+        buffer.push(STX);
+        buffer.push(self.id);
+        if !is_extended_packet_id(self.id) {
+            // BASE
+            for byte in &self.data {
+                if *byte < 0x20 {
+                    buffer.push(DLE);
+                }
+                buffer.push(*byte);
+            }
+        } else {
+            // EXTENDED
+            buffer.push(self.data.len() as u8);
+            buffer.extend_from_slice(&self.data);
+            let mut crc_buffer: Vec<u8> = Vec::new();
+            crc_buffer.push(self.id);
+            crc_buffer.push(self.data.len() as u8);
+            crc_buffer.extend_from_slice(&self.data);
+            buffer.extend_from_slice(&crc16(&crc_buffer).to_be_bytes());
+        }
+        buffer.push(ETX);
+        return buffer;
+    }
+}
+
 /// Raw packet
 ///
 /// * `Nak` - NAK response
@@ -76,27 +117,10 @@ pub trait StationboundPacket: Packet {
     fn payload(&self) -> Vec<u8>;
     /// Serializes the packet to **ready-to-send format**
     fn serialize(&self) -> Vec<u8> {
-        let mut buffer: Vec<u8> = Vec::new();
-
-        let payload = self.payload();
-
-        buffer.push(STX);
-        buffer.push(Self::PACKET_ID);
-        if !is_extended_packet_id(Self::PACKET_ID) {
-            // BASE
-            // TODO: Use DEL
-            buffer.extend_from_slice(&payload);
-        } else {
-            // EXTENDED
-            buffer.push(payload.len() as u8);
-            buffer.extend_from_slice(&payload);
-            let mut crc_buffer: Vec<u8> = Vec::new();
-            crc_buffer.push(Self::PACKET_ID);
-            crc_buffer.push(payload.len() as u8);
-            crc_buffer.extend_from_slice(&payload);
-            buffer.extend_from_slice(&crc16(&crc_buffer).to_be_bytes());
+        return RawPacketBody {
+            id: Self::PACKET_ID,
+            data: self.payload(),
         }
-        buffer.push(ETX);
-        return buffer;
+        .serialize();
     }
 }
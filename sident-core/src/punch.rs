@@ -0,0 +1,420 @@
+use alloc::{vec, vec::Vec};
+use bitflags::bitflags;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+use crate::{
+    errors::DeserializePunchError,
+    time::{DayOfWeek, StationTime, TdByte, WeekOfMonth},
+};
+
+/// Timing part of the punch
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PunchTime {
+    pub time: NaiveTime,
+    pub day: DayOfWeek,
+    pub week: WeekOfMonth,
+    /// The punch has only the 12h time (without AM/PM, day and week), e.g. SI5.
+    /// `day` and `week` are meaningless then.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub twelve_hour_only: bool,
+    /// Sub second (1/256 s), if the card or the station recorded it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub subsecond: Option<u8>,
+}
+
+impl PunchTime {
+    /// Makes the punch time from the date and time. Day of week and the 4-week counter are computed,
+    /// the sub second is set if the date time has a fraction of a second.
+    ///
+    /// * `datetime` - Date and time
+    pub fn from_datetime(datetime: NaiveDateTime) -> Self {
+        let station_time = StationTime::from_datetime(datetime);
+
+        return Self {
+            time: station_time.datetime.time().with_nanosecond(0).unwrap(), // cant fail
+            day: station_time.day,
+            week: station_time.week,
+            twelve_hour_only: false,
+            subsecond: (datetime.nanosecond() != 0).then_some(station_time.subsecond),
+        };
+    }
+
+    /// Converts the time,day,week timing to absolute seconds
+    pub fn to_absolute_seconds(&self) -> i64 {
+        let week = self.week as i64;
+        let day = self.day as i64;
+
+        let days_in_total = week * 7 + day;
+        let seconds_in_day = self.time.num_seconds_from_midnight() as i64;
+
+        days_in_total * 86_400 + seconds_in_day
+    }
+
+    /// Converts the time,day,week timing to absolute duration with the millisecond precision (see `subsecond`)
+    pub fn to_duration(&self) -> chrono::Duration {
+        return Duration::seconds(self.to_absolute_seconds())
+            + Duration::milliseconds(self.subsecond_millis());
+    }
+
+    /// Duration since other Punch (with the millisecond precision, if both punches have the sub second)
+    ///
+    /// * `other` - Other punch
+    pub fn duration_since(&self, other: &Self) -> chrono::Duration {
+        self.to_duration() - other.to_duration()
+    }
+
+    /// Sub second in milliseconds (0 if there is no sub second)
+    fn subsecond_millis(&self) -> i64 {
+        return self
+            .subsecond
+            .map(|subsecond| subsecond as i64 * 1000 / 256)
+            .unwrap_or(0);
+    }
+
+    /// Time of the day with the sub second
+    fn time_with_subsecond(&self) -> NaiveTime {
+        return self.time + Duration::milliseconds(self.subsecond_millis());
+    }
+
+    /// Returns the possible date times of the punch around the reference (sorted).
+    ///
+    /// The full time repeats every 28 days (4-week counter and day of week), the 12h time repeats every 12 hours.
+    fn candidates(&self, reference: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let reference_date = reference.date();
+
+        if self.twelve_hour_only {
+            // the time is 0:00-11:59, so check the previous, same and next day (AM and PM)
+            return [-1, 0, 1]
+                .iter()
+                .flat_map(|days| {
+                    let datetime = (reference_date + Duration::days(*days))
+                        .and_time(self.time_with_subsecond());
+                    return [datetime, datetime + Duration::hours(12)];
+                })
+                .collect();
+        }
+
+        let cycle_position = |date: NaiveDate| -> i64 {
+            return (date - NaiveDate::from_ymd_opt(1970, 1, 4).unwrap()) // cant fail
+                .num_days()
+                .rem_euclid(CYCLE_DAYS);
+        };
+        let target = self.week as i64 * 7 + self.day as i64;
+        let delta = (target - cycle_position(reference_date)).rem_euclid(CYCLE_DAYS);
+        let date = reference_date + Duration::days(delta);
+
+        return vec![
+            (date - Duration::days(CYCLE_DAYS)).and_time(self.time_with_subsecond()),
+            date.and_time(self.time_with_subsecond()),
+        ];
+    }
+
+    /// Places the punch to the date time nearest to the reference.
+    ///
+    /// Punches with the day of week and the 4-week counter are placed in the 28 day window around the reference,
+    /// 12h only punches (see `twelve_hour_only`) in the 24 hour window around the reference.
+    ///
+    /// The 4-week counter is expected to be set by the station clock (see `WeekOfMonth::for_date`).
+    ///
+    /// * `reference` - Reference date time (e.g. the event date at noon)
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::{NaiveDate, NaiveTime};
+    /// use sident_core::{punch::PunchTime, time::{DayOfWeek, WeekOfMonth}};
+    ///
+    /// let reference = NaiveDate::from_ymd_opt(2025, 6, 14).unwrap().and_hms_opt(12, 0, 0).unwrap();
+    /// let punch_time = PunchTime {
+    ///     time: NaiveTime::from_hms_opt(10, 30, 0).unwrap(),
+    ///     day: DayOfWeek::Sunday,
+    ///     week: WeekOfMonth::for_date(NaiveDate::from_ymd_opt(2025, 6, 15).unwrap()),
+    ///     twelve_hour_only: false,
+    ///     subsecond: Some(128),
+    /// };
+    ///
+    /// assert_eq!(
+    ///     punch_time.resolve(reference),
+    ///     NaiveDate::from_ymd_opt(2025, 6, 15).unwrap().and_hms_milli_opt(10, 30, 0, 500).unwrap()
+    /// );
+    /// ```
+    pub fn resolve(&self, reference: NaiveDateTime) -> NaiveDateTime {
+        return self
+            .candidates(reference)
+            .into_iter()
+            .min_by_key(|candidate| (*candidate - reference).abs())
+            .unwrap(); // there are always candidates
+    }
+
+    /// Places the punch to the first date time at or after the previous punch.
+    ///
+    /// Useful for the 12h only punches in order (midnight crossings).
+    /// Punches with the full time are placed by `PunchTime::resolve`, because their date is known.
+    ///
+    /// * `previous` - Date time of the previous punch
+    pub fn resolve_after(&self, previous: NaiveDateTime) -> NaiveDateTime {
+        if !self.twelve_hour_only {
+            return self.resolve(previous);
+        }
+
+        return self
+            .candidates(previous)
+            .into_iter()
+            .find(|candidate| *candidate >= previous)
+            .unwrap(); // the next day PM candidate is always after the previous punch
+    }
+}
+
+/// Length of the day of week + 4-week counter cycle
+const CYCLE_DAYS: i64 = 28;
+
+/// Punch placed in the calendar (see `PunchTime::resolve`)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatedPunch {
+    pub station_code: u16,
+    pub datetime: NaiveDateTime,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Punch {
+    pub station_code: u16,
+    pub punch_time: PunchTime,
+}
+
+impl Punch {
+    /// Checks if data is null and deserializes a Punch
+    ///
+    /// * `data` - Punch data
+    pub fn deserialize_control_punch(
+        data: &[u8; 4],
+    ) -> Result<Option<Self>, DeserializePunchError> {
+        if *data == [0xEE, 0xEE, 0xEE, 0xEE] {
+            return Ok(None);
+        }
+
+        return Ok(Some(Self::deserialize(data)?));
+    }
+
+    /// Deserializes a Punch
+    ///
+    /// * `data` - Punch data
+    ///
+    /// Source: SPORTident.Communication.Communication._parseMemoryRecord
+    pub fn deserialize(data: &[u8; 4]) -> Result<Self, DeserializePunchError> {
+        let td_byte = data[0];
+        let cn_byte = data[1];
+        let th_byte = data[2];
+        let tl_byte = data[3];
+
+        if td_byte == 0xFF && cn_byte == 0xFF && th_byte == 0xFF && tl_byte == 0xFF {
+            return Err(DeserializePunchError::DataCleared);
+        };
+
+        let td = TdByte::deserialize(td_byte)?;
+
+        let high = (td_byte & 0b1100_0000) >> 6; // bits 6–7 → bits 0–1
+        let station_code = ((high as u16) << 8) | (cn_byte as u16);
+
+        let time_punched = td.time([th_byte, tl_byte])?;
+
+        return Ok(Self {
+            station_code,
+            punch_time: PunchTime {
+                time: time_punched,
+                day: td.day,
+                week: td.week,
+                twelve_hour_only: false,
+                subsecond: None,
+            },
+        });
+    }
+
+    /// Serializes the punch to TD, CN, TH, TL (the card punch format)
+    pub fn serialize(&self) -> [u8; 4] {
+        let seconds = self.punch_time.time.num_seconds_from_midnight();
+        let td = TdByte {
+            day: self.punch_time.day,
+            week: self.punch_time.week,
+            offset_12h: seconds >= 12 * 3600,
+        };
+        let td = td.serialize() | (((self.station_code >> 8) as u8) << 6);
+        let th_tl = ((seconds % (12 * 3600)) as u16).to_be_bytes();
+
+        return [td, self.station_code as u8, th_tl[0], th_tl[1]];
+    }
+
+    /// Returns the sub second from the reserve record of the punch.
+    ///
+    /// Stations recording sub seconds write the punch also to the reserve record,
+    /// but with the sub second (1/256 s) instead of the TD byte (TSS, CN, TH, TL).
+    /// Returns `None` if the reserve is just a copy of the punch or it belongs to another punch.
+    /// The sub second equal to the TD byte can not be told apart from the copy, so it is `None` too.
+    ///
+    /// * `reserve` - Reserve record
+    pub fn subsecond_from_reserve(&self, reserve: &[u8; 4]) -> Option<u8> {
+        let punch = self.serialize();
+        // The reserve does not say whether its first byte is TSS or TD. A reserve equal to the punch is taken
+        // as a copy, so a real TSS which happens to equal the TD byte is lost (`None` rather than a wrong sub second).
+        if reserve[1..] != punch[1..] || reserve[0] == punch[0] {
+            return None;
+        }
+        return Some(reserve[0]);
+    }
+}
+
+bitflags! {
+    pub struct PunchFlags: u8 {
+        const OFFSET_12H =  0b0000_0001;
+        const DAY_OF_WEEK = 0b0000_1110;
+        const WEEK   =      0b0011_0000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Datelike;
+
+    use super::*;
+
+    fn datetime(month: u32, day: u32, h: u32, m: u32, s: u32) -> NaiveDateTime {
+        return NaiveDate::from_ymd_opt(2025, month, day)
+            .unwrap()
+            .and_hms_opt(h, m, s)
+            .unwrap();
+    }
+
+    /// 12h only punch (e.g. SI5) at `h`:`m`
+    fn twelve_hour(h: u32, m: u32) -> PunchTime {
+        return PunchTime {
+            time: NaiveTime::from_hms_opt(h, m, 0).unwrap(),
+            twelve_hour_only: true,
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn resolve_every_day_of_the_cycle() {
+        // 8 weeks, so the 4-week counter wraps around twice
+        let reference = datetime(6, 14, 12, 0, 0);
+        for days in -13..=13 {
+            let datetime = reference + Duration::days(days) - Duration::hours(2);
+            let punch_time = PunchTime::from_datetime(datetime);
+            assert_eq!(punch_time.resolve(reference), datetime, "{datetime}");
+        }
+        for days in 0..56 {
+            let datetime = datetime(6, 1, 10, 30, 15) + Duration::days(days);
+            let punch_time = PunchTime::from_datetime(datetime);
+            assert_eq!(
+                punch_time.resolve(datetime.date().and_hms_opt(12, 0, 0).unwrap()),
+                datetime
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_over_week_boundary() {
+        // Saturday 2025-06-14 is the last day of the week, Sunday starts the next one
+        let saturday = PunchTime::from_datetime(datetime(6, 14, 23, 59, 59));
+        let sunday = PunchTime::from_datetime(datetime(6, 15, 0, 0, 1));
+        assert_eq!(saturday.day, DayOfWeek::Saturday);
+        assert_eq!(sunday.day, DayOfWeek::Sunday);
+        assert_ne!(saturday.week, sunday.week);
+
+        // reference just after midnight
+        let reference = datetime(6, 15, 0, 10, 0);
+        assert_eq!(saturday.resolve(reference), datetime(6, 14, 23, 59, 59));
+        assert_eq!(sunday.resolve(reference), datetime(6, 15, 0, 0, 1));
+        assert_eq!(sunday.duration_since(&saturday), Duration::seconds(2));
+
+        // the 4-week counter wraps from the last week to the first one
+        let last_week = (0..28)
+            .map(|days| NaiveDate::from_ymd_opt(2025, 6, 7).unwrap() + Duration::days(days))
+            .find(|date| {
+                return date.weekday() == chrono::Weekday::Sat
+                    && WeekOfMonth::for_date(*date) == WeekOfMonth::Week4;
+            })
+            .unwrap();
+        let before = last_week.and_hms_opt(23, 0, 0).unwrap();
+        let after = before + Duration::hours(2);
+        assert_eq!(PunchTime::from_datetime(after).week, WeekOfMonth::Week1);
+        assert_eq!(PunchTime::from_datetime(before).resolve(after), before);
+        assert_eq!(PunchTime::from_datetime(after).resolve(before), after);
+    }
+
+    #[test]
+    fn resolve_keeps_the_subsecond() {
+        let mut punch_time = PunchTime::from_datetime(datetime(6, 14, 15, 30, 0));
+        punch_time.subsecond = Some(64);
+        assert_eq!(
+            punch_time.resolve(datetime(6, 14, 12, 0, 0)),
+            datetime(6, 14, 15, 30, 0) + Duration::milliseconds(250)
+        );
+    }
+
+    #[test]
+    fn resolve_twelve_hour_only_over_midnight() {
+        // nearest to the reference - AM or PM of the previous, same or next day
+        assert_eq!(
+            twelve_hour(11, 30).resolve(datetime(6, 15, 0, 10, 0)),
+            datetime(6, 14, 23, 30, 0)
+        );
+        assert_eq!(
+            twelve_hour(0, 20).resolve(datetime(6, 14, 23, 50, 0)),
+            datetime(6, 15, 0, 20, 0)
+        );
+        assert_eq!(
+            twelve_hour(3, 0).resolve(datetime(6, 14, 12, 0, 0)),
+            datetime(6, 14, 15, 0, 0)
+        );
+    }
+
+    #[test]
+    fn resolve_after_the_previous_punch() {
+        // over noon
+        assert_eq!(
+            twelve_hour(0, 10).resolve_after(datetime(6, 14, 11, 50, 0)),
+            datetime(6, 14, 12, 10, 0)
+        );
+        // over midnight
+        assert_eq!(
+            twelve_hour(0, 5).resolve_after(datetime(6, 14, 23, 50, 0)),
+            datetime(6, 15, 0, 5, 0)
+        );
+        // the same time as the previous punch is not moved
+        assert_eq!(
+            twelve_hour(11, 50).resolve_after(datetime(6, 14, 11, 50, 0)),
+            datetime(6, 14, 11, 50, 0)
+        );
+
+        // the full time is placed by its day and week, even before the previous punch
+        let punch_time = PunchTime::from_datetime(datetime(6, 14, 10, 0, 0));
+        assert_eq!(
+            punch_time.resolve_after(datetime(6, 14, 11, 0, 0)),
+            datetime(6, 14, 10, 0, 0)
+        );
+    }
+
+    #[test]
+    fn subsecond_from_the_reserve() {
+        let punch = Punch::deserialize(&[0b0000_0010, 31, 0x0E, 0x10]).unwrap();
+        assert_eq!(
+            punch.subsecond_from_reserve(&[0x80, 31, 0x0E, 0x10]),
+            Some(0x80)
+        );
+        // copy of the punch - also a sub second equal to the TD byte
+        assert_eq!(
+            punch.subsecond_from_reserve(&[0b0000_0010, 31, 0x0E, 0x10]),
+            None
+        );
+        // reserve of another punch
+        assert_eq!(punch.subsecond_from_reserve(&[0x80, 31, 0x0E, 0x11]), None);
+    }
+}
@@ -0,0 +1,236 @@
+use alloc::vec::Vec;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use log::error;
+use num_enum::TryFromPrimitive;
+
+use crate::{errors::DeserializePunchError, punch::PunchFlags};
+
+/// Impl of the way SPORTident stores dates
+pub struct SIDate;
+
+impl SIDate {
+    pub fn deserialize(input: &[u8; 3]) -> Option<NaiveDate> {
+        let year = (input[0] as i32) + 2000;
+        let month = input[1] as u32;
+        let day = input[2] as u32;
+
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    pub fn to_bytes(input: NaiveDate) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.push((input.year() - 2000) as u8);
+        buffer.push(input.month() as u8);
+        buffer.push(input.day() as u8);
+
+        return buffer;
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, Default)]
+#[repr(u8)]
+/// Source: SI_cards_data_structure_developer.ods
+pub enum DayOfWeek {
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+    #[default]
+    Sunday = 0,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive, Default)]
+#[repr(u8)]
+/// 4-week counter of the station (it is not the week of the calendar month)
+pub enum WeekOfMonth {
+    #[default]
+    Week1 = 0,
+    Week2 = 1,
+    Week3 = 2,
+    Week4 = 3,
+}
+
+impl DayOfWeek {
+    pub fn from_weekday(weekday: Weekday) -> Self {
+        return match weekday {
+            Weekday::Mon => DayOfWeek::Monday,
+            Weekday::Tue => DayOfWeek::Tuesday,
+            Weekday::Wed => DayOfWeek::Wednesday,
+            Weekday::Thu => DayOfWeek::Thursday,
+            Weekday::Fri => DayOfWeek::Friday,
+            Weekday::Sat => DayOfWeek::Saturday,
+            Weekday::Sun => DayOfWeek::Sunday,
+        };
+    }
+}
+
+impl WeekOfMonth {
+    /// Returns the 4-week counter value for the date.
+    ///
+    /// The counter is counted in whole weeks (starting on Sunday) since Sunday 1970-01-04.
+    ///
+    /// * `date` - Date
+    pub fn for_date(date: NaiveDate) -> Self {
+        let reference = NaiveDate::from_ymd_opt(1970, 1, 4).unwrap(); // cant fail
+        let weeks = (date - reference).num_days().div_euclid(7);
+        return Self::try_from_primitive(weeks.rem_euclid(4) as u8).unwrap(); // always 0..=3
+    }
+}
+
+/// Decoded TD byte of the punch or of the station clock
+///
+/// Source: SI_cards_data_structure_developer.ods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TdByte {
+    pub day: DayOfWeek,
+    pub week: WeekOfMonth,
+    /// 12 hours are added to the 12h binary time (PM)
+    pub offset_12h: bool,
+}
+
+impl TdByte {
+    /// Decodes the TD byte. Bits 6-7 (station code high bits in punches) are ignored.
+    ///
+    /// * `td_byte` - TD byte
+    pub fn deserialize(td_byte: u8) -> Result<Self, DeserializePunchError> {
+        let day = (td_byte & PunchFlags::DAY_OF_WEEK.bits()) >> 1;
+        let day = DayOfWeek::try_from_primitive(day).map_err(|e| {
+            error!("{}", e);
+            return DeserializePunchError::InvalidDay;
+        })?;
+
+        let week = (td_byte & PunchFlags::WEEK.bits()) >> 4;
+        let week = WeekOfMonth::try_from_primitive(week)
+            .map_err(|_| return DeserializePunchError::InvalidWeek)?;
+
+        let offset_12h = (td_byte & PunchFlags::OFFSET_12H.bits()) == 1;
+
+        return Ok(Self {
+            day,
+            week,
+            offset_12h,
+        });
+    }
+
+    /// Encodes the TD byte (without the station code high bits)
+    pub fn serialize(&self) -> u8 {
+        return ((self.week as u8) << 4) | ((self.day as u8) << 1) | self.offset_12h as u8;
+    }
+
+    /// Makes the time of the day from the 12h binary time (TH, TL)
+    ///
+    /// * `th_tl` - 12h binary time (seconds)
+    pub fn time(&self, th_tl: [u8; 2]) -> Result<NaiveTime, DeserializePunchError> {
+        let seconds = u16::from_be_bytes(th_tl);
+        let mut time = NaiveTime::from_num_seconds_from_midnight_opt(seconds as u32, 0)
+            .ok_or(DeserializePunchError::InvalidTime)?;
+
+        if self.offset_12h {
+            time += chrono::Duration::hours(12);
+        }
+
+        return Ok(time);
+    }
+}
+
+/// Time of the station clock
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StationTime {
+    /// Date and time including the sub second part
+    pub datetime: NaiveDateTime,
+    pub day: DayOfWeek,
+    pub week: WeekOfMonth,
+    /// Sub second ticks (1/256 s)
+    pub subsecond: u8,
+}
+
+impl StationTime {
+    /// Makes the station time from the date and time. Day of week and the 4-week counter are computed.
+    ///
+    /// * `datetime` - Date and time (sub second precision is 1/256 s)
+    pub fn from_datetime(datetime: NaiveDateTime) -> Self {
+        let subsecond =
+            ((datetime.nanosecond().min(999_999_999) as u64 * 256) / 1_000_000_000) as u8;
+        let datetime = datetime
+            .with_nanosecond(Self::subsecond_to_nanos(subsecond))
+            .unwrap(); // always < 1s
+
+        return Self {
+            datetime,
+            day: DayOfWeek::from_weekday(datetime.weekday()),
+            week: WeekOfMonth::for_date(datetime.date()),
+            subsecond,
+        };
+    }
+
+    fn subsecond_to_nanos(subsecond: u8) -> u32 {
+        return ((subsecond as u64 * 1_000_000_000) / 256) as u32;
+    }
+
+    /// Deserializes the station time
+    ///
+    /// * `data` - YY, MM, DD, TD, TH, TL, TSS
+    pub fn deserialize(data: &[u8; 7]) -> Result<Self, DeserializePunchError> {
+        let date = SIDate::deserialize(&[data[0], data[1], data[2]])
+            .ok_or(DeserializePunchError::InvalidData)?;
+        let td = TdByte::deserialize(data[3])?;
+        let time = td.time([data[4], data[5]])?;
+        let subsecond = data[6];
+        let datetime = date
+            .and_time(time)
+            .with_nanosecond(Self::subsecond_to_nanos(subsecond))
+            .unwrap(); // always < 1s
+
+        return Ok(Self {
+            datetime,
+            day: td.day,
+            week: td.week,
+            subsecond,
+        });
+    }
+
+    /// Serializes the station time to YY, MM, DD, TD, TH, TL, TSS
+    pub fn serialize(&self) -> [u8; 7] {
+        let date = SIDate::to_bytes(self.datetime.date());
+        let seconds = self.datetime.num_seconds_from_midnight();
+        let td = TdByte {
+            day: self.day,
+            week: self.week,
+            offset_12h: seconds >= 12 * 3600,
+        };
+        let th_tl = ((seconds % (12 * 3600)) as u16).to_be_bytes();
+
+        return [
+            date[0],
+            date[1],
+            date[2],
+            td.serialize(),
+            th_tl[0],
+            th_tl[1],
+            self.subsecond,
+        ];
+    }
+}
+
+/// Station clock read by `Connection::get_time`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StationClock {
+    pub time: StationTime,
+    /// Difference between the station clock and the host clock in milliseconds.
+    /// Positive if the station clock is ahead.
+    pub drift_ms: i64,
+}
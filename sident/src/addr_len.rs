@@ -14,7 +14,7 @@
 //     }
 // }
 
-use std::ops::Index;
+use std::ops::{Index, IndexMut};
 
 pub mod presets {
     use super::AddrLen;
@@ -61,6 +61,42 @@ pub mod presets {
         pub fn simsrr_serial_number() -> AddrLen {
             return AddrLen::new(0x01, 0x03);
         }
+
+        /// High bytes of the backup memory pointer
+        pub fn backup_pointer_high() -> AddrLen {
+            return AddrLen::new(0x1C, 0x02);
+        }
+
+        /// Low bytes of the backup memory pointer
+        pub fn backup_pointer_low() -> AddrLen {
+            return AddrLen::new(0x21, 0x02);
+        }
+
+        pub fn srr_channel() -> AddrLen {
+            return AddrLen::new(0x34, 0x01);
+        }
+
+        pub fn operating_mode() -> AddrLen {
+            return AddrLen::new(0x71, 0x01);
+        }
+
+        pub fn station_code() -> AddrLen {
+            return AddrLen::new(0x72, 0x01);
+        }
+
+        /// Feedback flags and the station code high bits (6-7)
+        pub fn station_flags() -> AddrLen {
+            return AddrLen::new(0x73, 0x01);
+        }
+
+        /// Operating mode, station code, station flags and protocol config
+        pub fn station_settings() -> AddrLen {
+            return AddrLen::new(0x71, 0x04);
+        }
+
+        pub fn active_time() -> AddrLen {
+            return AddrLen::new(0x7E, 0x02);
+        }
     }
 }
 
@@ -105,3 +141,11 @@ impl<T> Index<AddrLen> for [T] {
         &self[start..end]
     }
 }
+
+impl<T> IndexMut<AddrLen> for [T] {
+    fn index_mut(&mut self, index: AddrLen) -> &mut Self::Output {
+        let start = index.address();
+        let end = start + index.length();
+        &mut self[start..end]
+    }
+}
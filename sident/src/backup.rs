@@ -0,0 +1,200 @@
+/*
+    SPORTident Station Backup Memory
+
+    The backup memory is read with the GetBackupData (0x81) command - ADR2, ADR1, ADR0, NUM (max 128 bytes).
+    Every integer is big endian encoded unless said differently in the desc.
+
+    ---- SYSTEM VALUES ----
+    0x1C..0x1D  Backup pointer - high bytes
+    0x21..0x22  Backup pointer - low bytes
+    ----            ----
+
+    The pointer points to the first free byte. Records start at 0x100 and are written one after another.
+    Erased memory is 0xFF.
+
+    || Standard record (8 bytes):
+    0x00..0x02  SIID - SI2, SI1, SI0
+    0x03..0x04  Date - bits 15-10 year (2000+x), bits 9-6 month, bits 5-1 day, bit 0 PM
+    0x05..0x06  12h binary time (seconds)
+    0x07        Sub second (1/256 s)
+    ||
+
+    || Extended record (16 bytes):
+    0x00..0x03  SIID - SI3, SI2, SI1, SI0
+    0x04..0x05  Date - same as in the standard record
+    0x06..0x07  12h binary time (seconds)
+    0x08        Sub second (1/256 s)
+    0x09..0x0A  Station code - CN1, CN0
+    0x0B..0x0F  Reserved
+    ||
+
+    The standard record does not contain the station code, so the code of the station is used.
+*/
+
+use chrono::{Datelike, NaiveDate, Timelike};
+
+use crate::{
+    addr_len::presets::SystemConfigAddrLen,
+    errors::DeserializePunchError,
+    extract_fixed,
+    punch::{Punch, PunchTime},
+    time::{DayOfWeek, TdByte, WeekOfMonth},
+};
+
+/// Address of the first backup record
+pub const BACKUP_MEMORY_START: u32 = 0x100;
+
+/// Format of the records in the backup memory
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackupRecordFormat {
+    /// 8 byte records without the station code
+    #[default]
+    Standard,
+    /// 16 byte records with the 4 byte SIID and the station code
+    Extended,
+}
+
+impl BackupRecordFormat {
+    /// Length of one record in bytes
+    pub fn record_len(&self) -> usize {
+        return match self {
+            BackupRecordFormat::Standard => 8,
+            BackupRecordFormat::Extended => 16,
+        };
+    }
+}
+
+/// Punch stored in the backup memory of the station
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy)]
+pub struct BackupRecord {
+    pub siid: u32,
+    pub punch: Punch,
+    /// Date of the punch. `None` if the station clock was not set.
+    pub date: Option<NaiveDate>,
+}
+
+impl BackupRecord {
+    /// Deserializes the backup record. Returns `Ok(None)` for erased memory (0xFF).
+    ///
+    /// * `data` - Record data (`BackupRecordFormat::record_len` bytes)
+    /// * `format` - Format of the record
+    /// * `station_code` - Code of the station (used if the record does not contain it)
+    pub fn deserialize(
+        data: &[u8],
+        format: BackupRecordFormat,
+        station_code: u16,
+    ) -> Result<Option<Self>, DeserializePunchError> {
+        if data.len() != format.record_len() {
+            return Err(DeserializePunchError::InvalidData);
+        }
+        if data.iter().all(|b| *b == 0xFF) {
+            return Ok(None);
+        }
+
+        let (siid, date_bytes, time_bytes, subsecond, station_code) = match format {
+            BackupRecordFormat::Standard => (
+                u32::from_be_bytes([0, data[0], data[1], data[2]]),
+                extract_fixed!(data, 0x03..0x04),
+                extract_fixed!(data, 0x05..0x06),
+                data[0x07],
+                station_code,
+            ),
+            BackupRecordFormat::Extended => (
+                u32::from_be_bytes(extract_fixed!(data, 0x00..0x03)),
+                extract_fixed!(data, 0x04..0x05),
+                extract_fixed!(data, 0x06..0x07),
+                data[0x08],
+                u16::from_be_bytes(extract_fixed!(data, 0x09..0x0A)),
+            ),
+        };
+
+        let packed_date = u16::from_be_bytes(date_bytes);
+        let date = NaiveDate::from_ymd_opt(
+            2000 + (packed_date >> 10) as i32,
+            ((packed_date >> 6) & 0x0F) as u32,
+            ((packed_date >> 1) & 0x1F) as u32,
+        );
+
+        let td = TdByte {
+            day: date
+                .map(|date| DayOfWeek::from_weekday(date.weekday()))
+                .unwrap_or_default(),
+            week: date.map(WeekOfMonth::for_date).unwrap_or_default(),
+            offset_12h: (packed_date & 1) == 1,
+        };
+
+        let punch = Punch {
+            station_code,
+            punch_time: PunchTime {
+                time: td.time(time_bytes)?,
+                day: td.day,
+                week: td.week,
+                // without the date the day and week are unknown
+                twelve_hour_only: date.is_none(),
+                subsecond: Some(subsecond),
+            },
+        };
+
+        return Ok(Some(Self { siid, punch, date }));
+    }
+
+    /// Serializes the backup record
+    ///
+    /// * `format` - Format of the record
+    pub fn serialize(&self, format: BackupRecordFormat) -> Vec<u8> {
+        let subsecond = self.punch.punch_time.subsecond.unwrap_or(0);
+        let seconds = self.punch.punch_time.time.num_seconds_from_midnight();
+        let mut packed_date = (seconds >= 12 * 3600) as u16;
+        if let Some(date) = self.date {
+            packed_date |= (((date.year() - 2000) as u16) << 10)
+                | ((date.month() as u16) << 6)
+                | ((date.day() as u16) << 1);
+        }
+        let time_bytes = ((seconds % (12 * 3600)) as u16).to_be_bytes();
+
+        let mut buffer = Vec::with_capacity(format.record_len());
+        match format {
+            BackupRecordFormat::Standard => {
+                buffer.extend_from_slice(&self.siid.to_be_bytes()[1..]);
+                buffer.extend_from_slice(&packed_date.to_be_bytes());
+                buffer.extend_from_slice(&time_bytes);
+                buffer.push(subsecond);
+            }
+            BackupRecordFormat::Extended => {
+                buffer.extend_from_slice(&self.siid.to_be_bytes());
+                buffer.extend_from_slice(&packed_date.to_be_bytes());
+                buffer.extend_from_slice(&time_bytes);
+                buffer.push(subsecond);
+                buffer.extend_from_slice(&self.punch.station_code.to_be_bytes());
+                buffer.resize(format.record_len(), 0xEE);
+            }
+        }
+
+        return buffer;
+    }
+}
+
+/// Makes the backup pointer from the system values
+///
+/// * `system_values` - The whole 128 byte system value area of the station
+pub fn backup_pointer(system_values: &[u8; 128]) -> u32 {
+    let high = &system_values[SystemConfigAddrLen::backup_pointer_high()];
+    let low = &system_values[SystemConfigAddrLen::backup_pointer_low()];
+    return u32::from_be_bytes([high[0], high[1], low[0], low[1]]);
+}
+
+/// Writes the backup pointer to the system values
+///
+/// * `system_values` - The whole 128 byte system value area of the station
+/// * `pointer` - Backup pointer
+pub fn set_backup_pointer(system_values: &mut [u8; 128], pointer: u32) {
+    let bytes = pointer.to_be_bytes();
+    system_values[SystemConfigAddrLen::backup_pointer_high()].copy_from_slice(&bytes[..2]);
+    system_values[SystemConfigAddrLen::backup_pointer_low()].copy_from_slice(&bytes[2..]);
+}
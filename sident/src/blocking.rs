@@ -0,0 +1,357 @@
+/*
+    Blocking facade of `connection::Connection`
+
+    Every connection owns a current-thread tokio runtime and blocks on it for every call,
+    so no runtime is needed by the caller. Do not use it from inside of an async runtime
+    (tokio panics when blocking inside of a runtime).
+*/
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    runtime::{Builder, Runtime},
+};
+
+use crate::{
+    Baudrate, MsMode, Protocol, StationConfig, SystemConfig,
+    backup::{BackupRecord, BackupRecordFormat},
+    card::CardPersonalData,
+    carddef::CardDefinition,
+    codec::SICodecTimeout,
+    connection::{self, ConnectionStream, ReadoutPreference, ReadoutResult, RetryPolicy},
+    errors::{
+        BackupMemoryError, ConnectionOperationError, NewConnectionError, ReadoutError,
+        ReceivePacketError, ReceiveRawPacketError, SimpleActionError, StationConfigError,
+        StationTimeError, WritePersonalDataError,
+    },
+    event::StationEvent,
+    packet::{HostboundPacket, RawPacket, StationboundPacket},
+    product::ProductModel,
+    time::{StationClock, StationTime},
+};
+
+/// Blocking connection to the station. It has the same methods as `connection::Connection`.
+///
+/// # Example
+/// ```
+/// use sident::{blocking::Connection, sim::VirtualStation};
+///
+/// let (station, stream) = VirtualStation::new();
+/// // the virtual station needs its own runtime
+/// std::thread::spawn(move || {
+///     tokio::runtime::Builder::new_current_thread()
+///         .enable_all()
+///         .build()
+///         .unwrap()
+///         .block_on(station.run())
+/// });
+///
+/// // Connection::new("COM4") for a real station
+/// let mut conn = Connection::from_stream(stream).unwrap();
+/// conn.beep_if_station_ready(1).unwrap();
+/// assert_eq!(conn.get_station_config().unwrap().station_code, 10);
+/// ```
+pub struct Connection<S = ConnectionStream> {
+    // dropped before the runtime, the stream may be registered in it
+    inner: connection::Connection<S>,
+    runtime: Runtime,
+}
+
+fn new_runtime() -> std::io::Result<Runtime> {
+    return Builder::new_current_thread().enable_all().build();
+}
+
+impl Connection<ConnectionStream> {
+    /// Tries to connect to the port and returns a new connection. See `connection::Connection::new`.
+    ///
+    /// * `port_name` - Name of the port to connect to (Not on Android)
+    pub fn new(
+        #[cfg(not(target_os = "android"))] port_name: &str,
+    ) -> Result<Self, NewConnectionError> {
+        let runtime = new_runtime()?;
+        #[cfg(not(target_os = "android"))]
+        let inner = runtime.block_on(connection::Connection::new(port_name))?;
+        #[cfg(target_os = "android")]
+        let inner = runtime.block_on(connection::Connection::new())?;
+
+        return Ok(Self { inner, runtime });
+    }
+
+    /// Tries to set the baudrate of the station and then of the port.
+    ///
+    /// * `baudrate` - The desired baudrate
+    ///
+    /// Returns `true` if success
+    pub fn set_baudrate(&mut self, baudrate: Baudrate) -> Result<bool, ConnectionOperationError> {
+        return self.runtime.block_on(self.inner.set_baudrate(baudrate));
+    }
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Connects to the station over an already-open stream. See `connection::Connection::from_stream`.
+    ///
+    /// * `stream` - Already-open stream (TCP socket, pipe, in-memory duplex, ...)
+    pub fn from_stream(stream: S) -> Result<Self, NewConnectionError> {
+        let runtime = new_runtime()?;
+        let inner = runtime.block_on(connection::Connection::from_stream(stream))?;
+
+        return Ok(Self { inner, runtime });
+    }
+
+    /// Returns the async connection
+    pub fn get_async(&mut self) -> &mut connection::Connection<S> {
+        return &mut self.inner;
+    }
+
+    /// Consumes the connection and returns the async connection
+    pub fn into_async(self) -> connection::Connection<S> {
+        return self.inner;
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        return self.inner.get_ref();
+    }
+
+    /// Returns the protocol used by the station.
+    pub fn get_protocol(&self) -> Protocol {
+        return self.inner.get_protocol();
+    }
+
+    /// Returns the retry policy of the block readout
+    pub fn get_retry_policy(&self) -> RetryPolicy {
+        return self.inner.get_retry_policy();
+    }
+
+    /// Sets the retry policy of the block readout (see `RetryPolicy`)
+    ///
+    /// * `retry_policy` - Retry policy
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.inner.set_retry_policy(retry_policy);
+    }
+
+    /// Returns the system config loaded when connecting. `None` in the base protocol.
+    pub fn get_system_config(&self) -> Option<&SystemConfig> {
+        return self.inner.get_system_config();
+    }
+
+    /// Returns the model of the connected device.
+    pub fn get_product_model(&self) -> Option<ProductModel> {
+        return self.inner.get_product_model();
+    }
+
+    /// Reads the configurable settings of the station (operating mode, station code, feedback, ...).
+    pub fn get_station_config(&mut self) -> Result<StationConfig, StationConfigError> {
+        return self.runtime.block_on(self.inner.get_station_config());
+    }
+
+    /// Applies the config to the station and verifies it by reading it back.
+    /// See `connection::Connection::apply_config`.
+    ///
+    /// * `config` - The desired config (see `Connection::get_station_config`)
+    pub fn apply_config(&mut self, config: &StationConfig) -> Result<(), StationConfigError> {
+        return self.runtime.block_on(self.inner.apply_config(config));
+    }
+
+    /// Reads the station clock and computes its drift against the host clock (`chrono::Local`).
+    pub fn get_time(&mut self) -> Result<StationClock, StationTimeError> {
+        return self.runtime.block_on(self.inner.get_time());
+    }
+
+    /// Sets the station clock. Day of week and the 4-week counter are computed from the date.
+    ///
+    /// Returns the time the station has set.
+    ///
+    /// * `datetime` - Local date and time (`chrono::Local::now().naive_local()` to sync with the host)
+    pub fn set_time(
+        &mut self,
+        datetime: chrono::NaiveDateTime,
+    ) -> Result<StationTime, StationTimeError> {
+        return self.runtime.block_on(self.inner.set_time(datetime));
+    }
+
+    /// Reads all of the punches stored in the backup memory of the station.
+    ///
+    /// * `format` - Format of the records (depends on the station firmware)
+    pub fn read_backup_memory(
+        &mut self,
+        format: BackupRecordFormat,
+    ) -> Result<Vec<BackupRecord>, BackupMemoryError> {
+        return self.runtime.block_on(self.inner.read_backup_memory(format));
+    }
+
+    /// Reads all of the punches stored in the backup memory of the station.
+    ///
+    /// * `format` - Format of the records (depends on the station firmware)
+    /// * `progress` - Called with the number of read bytes and the total number of bytes after every read
+    pub fn read_backup_memory_with_progress<F: FnMut(u32, u32)>(
+        &mut self,
+        format: BackupRecordFormat,
+        progress: F,
+    ) -> Result<Vec<BackupRecord>, BackupMemoryError> {
+        return self.runtime.block_on(
+            self.inner
+                .read_backup_memory_with_progress(format, progress),
+        );
+    }
+
+    /// Waits for the next station event (without a timeout). See `connection::Connection::event_stream`.
+    pub fn next_event(&mut self) -> StationEvent {
+        return self.runtime.block_on(self.inner.next_event());
+    }
+
+    /// Waits for the card to be inserted and returns the SIID
+    ///
+    /// *This supports all cards*
+    pub fn wait_for_card_insert(&mut self) -> Result<u32, ReceivePacketError> {
+        return self.runtime.block_on(self.inner.wait_for_card_insert());
+    }
+
+    /// Makes the station beep, if it is ready.
+    ///
+    /// * `beep_count` - Beep count
+    pub fn beep_if_station_ready(&mut self, beep_count: u8) -> Result<(), SimpleActionError> {
+        return self
+            .runtime
+            .block_on(self.inner.beep_if_station_ready(beep_count));
+    }
+
+    /// Tries to set the M/S mode.
+    ///
+    /// * `mode` - The desired M/S mode
+    ///
+    /// Returns `true` if success
+    pub fn set_ms_mode(&mut self, mode: MsMode) -> Result<bool, ConnectionOperationError> {
+        return self.runtime.block_on(self.inner.set_ms_mode(mode));
+    }
+
+    /// Sends a packet.
+    ///
+    /// * `packet` - Packet to be sent
+    pub fn send_packet<P: StationboundPacket>(&mut self, packet: &P) -> Result<(), std::io::Error> {
+        return self.runtime.block_on(self.inner.send_packet(packet));
+    }
+
+    /// Receives a raw packet. With custom timeout.
+    ///
+    /// * `stx_timeout` - Timeout for the first `STX` byte
+    /// * `timeout` - Timeout for reading next byte
+    pub fn receive_raw_packet_custom(
+        &mut self,
+        stx_timeout: SICodecTimeout,
+        timeout: SICodecTimeout,
+    ) -> Result<RawPacket, ReceiveRawPacketError> {
+        return self
+            .runtime
+            .block_on(self.inner.receive_raw_packet_custom(stx_timeout, timeout));
+    }
+
+    /// Receives a raw packet. With default timeout.
+    pub fn receive_raw_packet(&mut self) -> Result<RawPacket, ReceiveRawPacketError> {
+        return self.runtime.block_on(self.inner.receive_raw_packet());
+    }
+
+    /// Receives and tries to parse a packet. With custom timeout.
+    ///
+    /// * `<P: HostboundPacket>` - Expected packet (generic)
+    /// * `stx_timeout` - Timeout for the first `STX` byte
+    /// * `timeout` - Timeout for reading next byte
+    pub fn receive_packet_custom<P: HostboundPacket>(
+        &mut self,
+        stx_timeout: SICodecTimeout,
+        timeout: SICodecTimeout,
+    ) -> Result<P, ReceivePacketError> {
+        return self
+            .runtime
+            .block_on(self.inner.receive_packet_custom(stx_timeout, timeout));
+    }
+
+    /// Receives and tries to parse a packet. With default timeout.
+    ///
+    /// * `<P: HostboundPacket>` - Expected packet (generic)
+    pub fn receive_packet<P: HostboundPacket>(&mut self) -> Result<P, ReceivePacketError> {
+        return self.runtime.block_on(self.inner.receive_packet());
+    }
+
+    /// Receives a raw packet and ignores it. With custom timeout.
+    ///
+    /// * `stx_timeout` - Timeout for the first `STX` byte
+    /// * `timeout` - Timeout for reading next byte
+    pub fn receive_and_ignore_packet_custom(
+        &mut self,
+        stx_timeout: SICodecTimeout,
+        timeout: SICodecTimeout,
+    ) -> Result<(), ReceiveRawPacketError> {
+        return self.runtime.block_on(
+            self.inner
+                .receive_and_ignore_packet_custom(stx_timeout, timeout),
+        );
+    }
+
+    /// Receives a raw packet and ignores it. With default timeout.
+    pub fn receive_and_ignore_packet(&mut self) -> Result<(), ReceiveRawPacketError> {
+        return self
+            .runtime
+            .block_on(self.inner.receive_and_ignore_packet());
+    }
+
+    /// Writes the personal data to the card. See `connection::Connection::write_personal_data`.
+    ///
+    /// * `siid` - SIID (card id)
+    /// * `data` - Personal data
+    pub fn write_personal_data(
+        &mut self,
+        siid: u32,
+        data: &CardPersonalData,
+    ) -> Result<(), WritePersonalDataError> {
+        return self
+            .runtime
+            .block_on(self.inner.write_personal_data(siid, data));
+    }
+
+    /// Writes the personal data to the card using the specified `CardDefinition`.
+    ///
+    /// * `<T: CardDefinition>` - Card definition (generic)
+    /// * `siid` - SIID (card id)
+    /// * `data` - Personal data
+    pub fn write_personal_data_generic<T: CardDefinition>(
+        &mut self,
+        siid: u32,
+        data: &CardPersonalData,
+    ) -> Result<(), WritePersonalDataError> {
+        return self
+            .runtime
+            .block_on(self.inner.write_personal_data_generic::<T>(siid, data));
+    }
+
+    /// Reads out the card. See `connection::Connection::read_out`.
+    ///
+    /// * `preferences` - Readout preferences (see the `ReadoutPreference` enum for more info)
+    /// * `siid` - SIID (card id)
+    pub fn read_out(
+        &mut self,
+        preferences: &[ReadoutPreference],
+        siid: u32,
+    ) -> Result<ReadoutResult, ReadoutError> {
+        return self
+            .runtime
+            .block_on(self.inner.read_out(preferences, siid));
+    }
+
+    /// Reads out the card to the specified `CardDefinition`.
+    ///
+    /// * `<T: CardDefinition>` - Card definition (generic)
+    /// * `preferences` - Readout preferences (see the `ReadoutPreference` enum for more info)
+    /// * `siid` - SIID (card id)
+    pub fn read_out_generic<T: CardDefinition>(
+        &mut self,
+        preferences: &[ReadoutPreference],
+        siid: u32,
+    ) -> Result<T, ReadoutError> {
+        return self
+            .runtime
+            .block_on(self.inner.read_out_generic::<T>(preferences, siid));
+    }
+}
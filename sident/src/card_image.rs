@@ -0,0 +1,261 @@
+/*
+    Raw card image - the blocks as they came off the card
+
+    ---- BINARY FORMAT ----
+    0x00..0x03  Magic - "SIDI"
+    0x04        Format version - 1
+    0x05        Card type (see CARD_TYPE_CODES)
+    0x06..0x09  SIID
+    0x0A..0x0B  Block count - 256 at most, there are only 256 block numbers
+    0x0C..      Blocks - block number (1 byte) + data (128 bytes), ascending by the block number
+    ----            ----
+
+    ---- JSON FORMAT ---- (serde feature)
+    {
+        "version": 1,
+        "card_type": "Card10",
+        "siid": 7123456,
+        "blocks": { "0": "<256 hex chars>", "1": "..." }
+    }
+    ----            ----
+*/
+
+use std::collections::BTreeMap;
+
+use crate::{
+    card::CardType,
+    carddef::{
+        CardDefinition, comcardpro::ComCardProDef, comcardup::ComCardUpDef, fcard::FCardDef,
+        pcard::PCardDef, si5::Card5Def, si6::Card6Def, si8::Card8Def, si9::Card9Def,
+        si10::Card10Def, si11::Card11Def, siac::ActiveCardDef, tcard::TCardDef,
+    },
+    connection::ReadoutResult,
+    errors::{CardImageError, FeedBlockError},
+};
+
+const MAGIC: &[u8; 4] = b"SIDI";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = 12;
+const BLOCK_RECORD_LEN: usize = 129;
+
+/// Card type codes of the binary format. Never reorder, only append.
+const CARD_TYPE_CODES: [CardType; 15] = [
+    CardType::Card5,
+    CardType::Card6,
+    CardType::Card8,
+    CardType::Card9,
+    CardType::Card10,
+    CardType::Card11,
+    CardType::PCard,
+    CardType::Card5U,
+    CardType::Card5R,
+    CardType::TCard,
+    CardType::FCard,
+    CardType::ActiveCard,
+    CardType::ComCardUp,
+    CardType::ComCardPro,
+    CardType::ComCardAir,
+];
+
+/// Raw blocks of the card (see `ToCardImage::to_image`).
+///
+/// The image can be stored (`to_bytes`, `to_json`) and loaded back to the card definition (`load`).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "CardImageRepr", into = "CardImageRepr")
+)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardImage {
+    pub card_type: CardType,
+    pub siid: u32,
+    /// Block number -> data
+    pub blocks: BTreeMap<u8, [u8; 128]>,
+}
+
+impl CardImage {
+    /// Feeds the blocks to the card definition of the card type
+    pub fn load(&self) -> Result<ReadoutResult, CardImageError> {
+        let result = match self.card_type {
+            CardType::ActiveCard => {
+                ReadoutResult::ActiveCard(self.load_generic::<ActiveCardDef>()?)
+            }
+            CardType::Card11 => ReadoutResult::Card11(self.load_generic::<Card11Def>()?),
+            CardType::Card10 => ReadoutResult::Card10(self.load_generic::<Card10Def>()?),
+            CardType::Card9 => ReadoutResult::Card9(self.load_generic::<Card9Def>()?),
+            CardType::Card8 => ReadoutResult::Card8(self.load_generic::<Card8Def>()?),
+            CardType::ComCardPro => {
+                ReadoutResult::ComCardPro(self.load_generic::<ComCardProDef>()?)
+            }
+            CardType::ComCardUp => ReadoutResult::ComCardUp(self.load_generic::<ComCardUpDef>()?),
+            CardType::Card5 => ReadoutResult::Card5(self.load_generic::<Card5Def>()?),
+            CardType::Card6 => ReadoutResult::Card6(self.load_generic::<Card6Def>()?),
+            CardType::PCard => ReadoutResult::PCard(self.load_generic::<PCardDef>()?),
+            CardType::TCard => ReadoutResult::TCard(self.load_generic::<TCardDef>()?),
+            CardType::FCard => ReadoutResult::FCard(self.load_generic::<FCardDef>()?),
+            card_type => return Err(CardImageError::CardNotSupported(card_type)),
+        };
+
+        return Ok(result);
+    }
+
+    /// Feeds the blocks to the specified card definition
+    pub fn load_generic<T: CardDefinition>(&self) -> Result<T, FeedBlockError> {
+        let mut carddef = T::new_empty();
+        for (block_id, data) in &self.blocks {
+            carddef.feed_block(*block_id, data)?;
+        }
+
+        return Ok(carddef);
+    }
+
+    /// Serializes the image to the binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let card_type_code = CARD_TYPE_CODES
+            .iter()
+            .position(|card_type| *card_type == self.card_type)
+            .unwrap() as u8; // every card type has a code
+
+        let mut buffer = Vec::with_capacity(HEADER_LEN + self.blocks.len() * BLOCK_RECORD_LEN);
+        buffer.extend_from_slice(MAGIC);
+        buffer.push(FORMAT_VERSION);
+        buffer.push(card_type_code);
+        buffer.extend_from_slice(&self.siid.to_be_bytes());
+        buffer.extend_from_slice(&(self.blocks.len() as u16).to_be_bytes()); // keys are u8, max 256
+        for (block_id, data) in &self.blocks {
+            buffer.push(*block_id);
+            buffer.extend_from_slice(data);
+        }
+
+        return buffer;
+    }
+
+    /// Deserializes the image from the binary format
+    ///
+    /// * `data` - Image made by `to_bytes`
+    pub fn from_bytes(data: &[u8]) -> Result<Self, CardImageError> {
+        if data.len() < HEADER_LEN {
+            return Err(CardImageError::InvalidLength);
+        }
+        if &data[0..4] != MAGIC {
+            return Err(CardImageError::InvalidMagic);
+        }
+        if data[4] != FORMAT_VERSION {
+            return Err(CardImageError::UnsupportedVersion(data[4]));
+        }
+        let card_type = *CARD_TYPE_CODES
+            .get(data[5] as usize)
+            .ok_or(CardImageError::UnknownCardType(data[5]))?;
+        let siid = u32::from_be_bytes([data[6], data[7], data[8], data[9]]);
+
+        let block_count = u16::from_be_bytes([data[10], data[11]]) as usize;
+        let records = &data[HEADER_LEN..];
+        if records.len() != block_count * BLOCK_RECORD_LEN {
+            return Err(CardImageError::InvalidLength);
+        }
+        let blocks = records
+            .chunks_exact(BLOCK_RECORD_LEN)
+            .map(|record| (record[0], record[1..].try_into().unwrap())) // cant fail, chunk is 129 bytes
+            .collect();
+
+        return Ok(Self {
+            card_type,
+            siid,
+            blocks,
+        });
+    }
+
+    /// Serializes the image to JSON
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, CardImageError> {
+        return Ok(serde_json::to_string_pretty(self)?);
+    }
+
+    /// Deserializes the image from JSON
+    ///
+    /// * `json` - Image made by `to_json`
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, CardImageError> {
+        // not through `Deserialize`, so the errors of the blocks are not wrapped in `JsonError`
+        let repr: CardImageRepr = serde_json::from_str(json)?;
+        return repr.try_into();
+    }
+}
+
+/// Makes the `CardImage` from the raw blocks of any `CardDefinition`
+pub trait ToCardImage {
+    /// Makes the `CardImage` from the raw blocks. `None` if the SIID or the card type is unknown.
+    fn to_image(&self) -> Option<CardImage>;
+}
+
+impl<T: CardDefinition> ToCardImage for T {
+    fn to_image(&self) -> Option<CardImage> {
+        let siid = self.get_siid()?;
+        return Some(CardImage {
+            card_type: CardType::from_siid(siid)?,
+            siid,
+            blocks: self.raw_blocks().clone(),
+        });
+    }
+}
+
+/// JSON representation of `CardImage` (blocks as hex strings)
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CardImageRepr {
+    version: u8,
+    card_type: CardType,
+    siid: u32,
+    blocks: BTreeMap<u8, String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<CardImage> for CardImageRepr {
+    fn from(value: CardImage) -> Self {
+        let blocks = value
+            .blocks
+            .iter()
+            .map(|(block_id, data)| {
+                let hex = data.iter().map(|byte| format!("{:02X}", byte)).collect();
+                (*block_id, hex)
+            })
+            .collect();
+
+        return Self {
+            version: FORMAT_VERSION,
+            card_type: value.card_type,
+            siid: value.siid,
+            blocks,
+        };
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CardImageRepr> for CardImage {
+    type Error = CardImageError;
+
+    fn try_from(value: CardImageRepr) -> Result<Self, Self::Error> {
+        if value.version != FORMAT_VERSION {
+            return Err(CardImageError::UnsupportedVersion(value.version));
+        }
+
+        let mut blocks = BTreeMap::new();
+        for (block_id, hex) in value.blocks {
+            let invalid = || CardImageError::InvalidBlock(block_id);
+            if hex.len() != 256 || !hex.is_ascii() {
+                return Err(invalid());
+            }
+            let mut data = [0u8; 128];
+            for (i, byte) in data.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+            }
+            blocks.insert(block_id, data);
+        }
+
+        return Ok(Self {
+            card_type: value.card_type,
+            siid: value.siid,
+            blocks,
+        });
+    }
+}
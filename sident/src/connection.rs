@@ -1,61 +1,90 @@
-use std::time::Duration;
+use std::{
+    collections::{BTreeMap, btree_map::Entry},
+    time::Duration,
+};
 
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use futures::Stream;
 use log::{debug, info, warn};
-use tokio::io::AsyncWriteExt;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    sync::mpsc,
+};
 
 #[cfg(not(target_os = "android"))]
 use tokio_serial::{SerialPort, SerialPortBuilderExt};
 
 use crate::{
-    Baudrate, MsMode, SUPPORTED_CARDS, SystemConfig,
+    Baudrate, MsMode, Protocol, SUPPORTED_CARDS, StationConfig, SystemConfig,
     addr_len::presets::SystemConfigAddrLen,
+    backup::{BACKUP_MEMORY_START, BackupRecord, BackupRecordFormat, backup_pointer},
     card::{CardPersonalData, CardType},
+    card_image::{CardImage, ToCardImage},
     carddef::{
-        BlockNeededIntention, BlockNeededResult, CardDefinition, comcardpro::ComCardProDef,
-        comcardup::ComCardUpDef, si8::Card8Def, si9::Card9Def, si10::Card10Def, si11::Card11Def,
-        siac::ActiveCardDef,
+        BlockNeededIntention, BlockNeededResult, CardDefinition, ReadoutCommand,
+        comcardpro::ComCardProDef, comcardup::ComCardUpDef, fcard::FCardDef, pcard::PCardDef,
+        si5::Card5Def, si6::Card6Def, si8::Card8Def, si9::Card9Def, si10::Card10Def,
+        si11::Card11Def, siac::ActiveCardDef, tcard::TCardDef,
     },
     codec::{SICodec, SICodecTimeout, consts::STX},
     dedup_enum_array,
     errors::{
-        ConnectionOperationError, DeserializePacketError, NewConnectionError, ReadoutError,
-        ReadoutResultTransformationError, ReceivePacketError, ReceiveRawPacketError,
-        SimpleActionError,
+        BackupMemoryError, ConnectionOperationError, DeserializePacketError,
+        DeserializeRawPacketError, NewConnectionError, ReadoutError,
+        ReadoutResultTransformationError, ReadoutServiceError, ReceivePacketError,
+        ReceiveRawPacketError, SimpleActionError, StationConfigError, StationTimeError,
+        WritePersonalDataError,
     },
+    event::{EventStream, StationEvent},
     generate_readout_fn,
     packet::{HostboundPacket, Packet, RawPacket, StationboundPacket},
     packets::{
         hostbound::{
-            GetSICardNewerResponse, GetSystemValueResponse, SICard5Detected, SICard6Detected,
-            SICardNewerDetected, SICardRemoved, SetMsModeResponse,
+            BaseGetSICard5Response, BaseGetSICard6Response, BaseSICard5Detected,
+            BaseSICard6Detected, BaseSICardRemoved, BaseSetBaudrateResponse, BaseSetMsModeResponse,
+            BaseTransmitRecord, GetBackupDataResponse, GetSICard5Response, GetSICard6Response,
+            GetSICardNewerResponse, GetSystemValueResponse, GetTimeResponse, SICard5Detected,
+            SICard6Detected, SICardNewerDetected, SICardRemoved, SetBaudrateResponse,
+            SetMsModeResponse, SetSystemValueResponse, SetTimeResponse, TransmitRecord,
+            WriteSICard6Response, WriteSICardNewerResponse,
+        },
+        stationbound::{
+            BaseGetSICard5, BaseGetSICard6, BaseSetBaudrate, BaseSetMsMode, BeepIfStationReady,
+            GetBackupData, GetSICard5, GetSICard6, GetSICardNewer, GetSystemValue, GetTime,
+            SetBaudrate, SetMsMode, SetSystemValue, SetTime, WriteSICard6, WriteSICardNewer,
         },
-        stationbound::{BeepIfStationReady, GetSICardNewer, GetSystemValue, SetMsMode},
     },
     product::ProductModel,
-    punch::Punch,
+    punch::{DatedPunch, Punch},
+    time::{StationClock, StationTime},
 };
 
 /// `ConnectionStream` differs on other platforms. For this platform it is `siacom::SIAndroidCom`.
 ///
 /// **Note: Android communication is EXPERIMENTAL.**
 #[cfg(target_os = "android")]
-type ConnectionStream = siacom::SIAndroidCom;
+pub type ConnectionStream = siacom::SIAndroidCom;
 
 /// `ConnectionStream` differs on other platforms. For this platform it is `tokio_serial::SerialStream`.
 #[cfg(not(target_os = "android"))]
-type ConnectionStream = tokio_serial::SerialStream;
+pub type ConnectionStream = tokio_serial::SerialStream;
 
 /// Struct for controlling the communication with the SPORTident station.
-pub struct Connection {
-    stream: ConnectionStream,
+///
+/// The connection is generic over its transport. By default it is `ConnectionStream` (the serial port),
+/// but any `AsyncRead + AsyncWrite + Unpin` stream can be used (see `Connection::from_stream`).
+pub struct Connection<S = ConnectionStream> {
+    stream: S,
     ms_mode: MsMode,
+    protocol: Protocol,
     system_config: Option<SystemConfig>,
+    retry_policy: RetryPolicy,
 }
 
 pub static TIMEOUT_DEFAULT: once_cell::sync::Lazy<SICodecTimeout> =
     once_cell::sync::Lazy::new(|| SICodecTimeout::Finite(Duration::from_millis(2500)));
 
-impl Connection {
+impl Connection<ConnectionStream> {
     /// Tries to connect to the port and returns a new connection.
     ///
     /// * `port_name` - Name of the port to connect to (Not on Android)
@@ -72,16 +101,6 @@ impl Connection {
     pub async fn new(
         #[cfg(not(target_os = "android"))] port_name: &str,
     ) -> Result<Self, NewConnectionError> {
-        /// Set the M/S mode to Master.
-        ///
-        /// If `set_ms_mode` is `Err` or `false` then returns `NewConnectionError::FailedToSetMsMode`
-        async fn msmmaster(conn: &mut Connection) -> Result<(), NewConnectionError> {
-            if !conn.set_ms_mode(MsMode::Master).await? {
-                return Err(NewConnectionError::FailedToSetMsMode);
-            }
-            return Ok(());
-        }
-
         #[cfg(target_os = "android")]
         info!("trying to connect to SI");
 
@@ -115,19 +134,12 @@ impl Connection {
             port.set_baud_rate(Baudrate::High.actual_baudrate())?;
         }
 
-        let mut conn = Connection {
-            stream: port,
-            ms_mode: MsMode::Master,
-            system_config: None,
-        };
+        let mut conn = Connection::with_stream(port);
 
-        debug!("try 1 - high baudrate, extended protocol");
-        debug!("sending 0xFF and STX");
-        conn.stream.write_all(&[0xFF]).await?;
-        tokio::time::sleep(Duration::from_millis(10)).await;
-        conn.stream.write_all(&[STX]).await?;
+        debug!("try 1 - high baudrate");
+        conn.wake_up().await?;
 
-        let msm_result = msmmaster(&mut conn).await;
+        let msm_result = conn.detect_protocol().await;
 
         if msm_result.is_err() {
             debug!(
@@ -136,25 +148,55 @@ impl Connection {
             );
             debug!("trying low baudrate fallback");
             conn.set_stream_baudrate(Baudrate::Low).await?;
-            msmmaster(&mut conn).await?;
+            conn.detect_protocol().await?;
             debug!("got response at low baudrate");
         } else {
             debug!("got response at high baudrate");
         }
 
-        debug!("getting protocol config");
-        conn.send_packet(&GetSystemValue {
-            addr_len: SystemConfigAddrLen::full(),
-        })
-        .await?;
-        let sysv_response = conn.receive_packet::<GetSystemValueResponse>().await?;
-        let sysv = SystemConfig::deserialize(sysv_response.data.as_slice().try_into()?)?;
-        conn.system_config = Some(sysv);
+        conn.load_system_config().await?;
 
         info!("connected successfully");
         Ok(conn)
     }
 
+    /// Tries to set the baudrate of the station and then of the port.
+    ///
+    /// * `baudrate` - The desired baudrate
+    ///
+    /// Returns `true` if success
+    pub async fn set_baudrate(
+        &mut self,
+        baudrate: Baudrate,
+    ) -> Result<bool, ConnectionOperationError> {
+        info!("changing baudrate to {:?}", baudrate);
+
+        let response_baudrate = match self.protocol {
+            Protocol::Extended => {
+                self.send_packet(&SetBaudrate { baudrate }).await?;
+                self.receive_packet::<SetBaudrateResponse>().await?.baudrate
+            }
+            Protocol::Base => {
+                self.send_packet(&BaseSetBaudrate { baudrate }).await?;
+                self.receive_packet::<BaseSetBaudrateResponse>()
+                    .await?
+                    .baudrate
+            }
+        };
+
+        if response_baudrate != baudrate {
+            warn!(
+                "Failed to switch to baudrate {:?}, returning Ok(false)",
+                baudrate
+            );
+            return Ok(false);
+        }
+
+        self.set_stream_baudrate(baudrate).await?;
+        info!("successfully switched to baudrate {:?}", baudrate);
+        return Ok(true);
+    }
+
     async fn set_stream_baudrate(&mut self, baudrate: Baudrate) -> std::io::Result<()> {
         #[cfg(target_os = "android")]
         return self.stream.set_baudrate(baudrate.actual_baudrate()).await;
@@ -164,6 +206,133 @@ impl Connection {
             return Ok(());
         }
     }
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Connects to the station over an already-open stream and returns a new connection.
+    ///
+    /// Runs the same handshake as `Connection::new` (wake up, protocol detection, M/S mode to Master, system config),
+    /// but without the baudrate fallback, because the stream is not necessarily a serial port.
+    ///
+    /// * `stream` - Already-open stream (TCP socket, pipe, in-memory duplex, ...)
+    pub async fn from_stream(stream: S) -> Result<Self, NewConnectionError> {
+        info!("trying to connect to SI over a custom stream");
+
+        let mut conn = Connection::with_stream(stream);
+        conn.wake_up().await?;
+        conn.detect_protocol().await?;
+        conn.load_system_config().await?;
+
+        info!("connected successfully");
+        Ok(conn)
+    }
+
+    /// Wraps the stream without any communication with the station.
+    fn with_stream(stream: S) -> Self {
+        Self {
+            stream,
+            ms_mode: MsMode::Master,
+            protocol: Protocol::Extended,
+            system_config: None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Sends the wakeup byte (0xFF) followed by `STX`.
+    async fn wake_up(&mut self) -> std::io::Result<()> {
+        debug!("sending 0xFF and STX");
+        self.stream.write_all(&[0xFF]).await?;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        self.stream.write_all(&[STX]).await?;
+        return Ok(());
+    }
+
+    /// Set the M/S mode to Master.
+    ///
+    /// If `set_ms_mode` is `Err` or `false` then returns `NewConnectionError::FailedToSetMsMode`
+    async fn set_ms_mode_master(&mut self) -> Result<(), NewConnectionError> {
+        if !self.set_ms_mode(MsMode::Master).await? {
+            return Err(NewConnectionError::FailedToSetMsMode);
+        }
+        return Ok(());
+    }
+
+    /// Detects the protocol of the station by setting the M/S mode to Master.
+    ///
+    /// Extended protocol is tried first, then the base (legacy) protocol.
+    async fn detect_protocol(&mut self) -> Result<(), NewConnectionError> {
+        debug!("trying extended protocol");
+        self.protocol = Protocol::Extended;
+        let ext_result = self.set_ms_mode_master().await;
+        if ext_result.is_ok() {
+            return Ok(());
+        }
+
+        debug!(
+            "did not get response in extended protocol: {:?}",
+            ext_result.err().unwrap()
+        );
+        debug!("trying base protocol");
+        self.protocol = Protocol::Base;
+        self.set_ms_mode_master().await?;
+        warn!("station uses the base (legacy) protocol");
+        return Ok(());
+    }
+
+    /// Reads the whole system config of the station and stores it in the connection.
+    ///
+    /// System values are not available in the base protocol, so this does nothing there.
+    async fn load_system_config(&mut self) -> Result<(), NewConnectionError> {
+        if self.protocol == Protocol::Base {
+            debug!("base protocol - skipping system config");
+            return Ok(());
+        }
+
+        debug!("getting protocol config");
+        self.send_packet(&GetSystemValue {
+            addr_len: SystemConfigAddrLen::full(),
+        })
+        .await?;
+        let sysv_response = self.receive_packet::<GetSystemValueResponse>().await?;
+        let sysv = SystemConfig::deserialize(sysv_response.data.as_slice().try_into()?)?;
+        self.system_config = Some(sysv);
+        return Ok(());
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        return &self.stream;
+    }
+
+    /// Consumes the connection and returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        return self.stream;
+    }
+
+    /// Returns the protocol used by the station.
+    pub fn get_protocol(&self) -> Protocol {
+        return self.protocol;
+    }
+
+    /// Returns the retry policy of the block readout
+    pub fn get_retry_policy(&self) -> RetryPolicy {
+        return self.retry_policy;
+    }
+
+    /// Sets the retry policy of the block readout (see `RetryPolicy`)
+    ///
+    /// * `retry_policy` - Retry policy
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Returns the system config loaded when connecting. `None` in the base protocol.
+    pub fn get_system_config(&self) -> Option<&SystemConfig> {
+        return self.system_config.as_ref();
+    }
 
     /// Returns the model of the connected device.
     pub fn get_product_model(&self) -> Option<ProductModel> {
@@ -171,9 +340,353 @@ impl Connection {
         return Some(sys_conf.model);
     }
 
+    /// Reads the whole 128 byte system value area of the station.
+    async fn read_system_values(&mut self) -> Result<[u8; 128], StationConfigError> {
+        if self.protocol == Protocol::Base {
+            return Err(StationConfigError::BaseNotSupported);
+        }
+
+        self.send_packet(&GetSystemValue {
+            addr_len: SystemConfigAddrLen::full(),
+        })
+        .await?;
+        let response = self.receive_packet::<GetSystemValueResponse>().await?;
+        return response
+            .data
+            .as_slice()
+            .try_into()
+            .map_err(|_| StationConfigError::WrongSystemValues);
+    }
+
+    /// Reads the configurable settings of the station (operating mode, station code, feedback, ...).
+    pub async fn get_station_config(&mut self) -> Result<StationConfig, StationConfigError> {
+        let values = self.read_system_values().await?;
+        return StationConfig::deserialize(&values);
+    }
+
+    /// Applies the config to the station and verifies it by reading it back.
+    ///
+    /// Only the values which are a part of `StationConfig` are changed.
+    ///
+    /// **Note: The station answers in the base protocol after turning off
+    /// `ProtocolConfig::EXTENDED_PROTOCOL`, so the verification fails.**
+    ///
+    /// * `config` - The desired config (see `Connection::get_station_config`)
+    ///
+    /// # Example
+    /// ```
+    /// use sident::{OperatingMode, StationFeedback, connection::Connection, sim::VirtualStation};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let (station, stream) = VirtualStation::new();
+    /// tokio::spawn(station.run());
+    /// let mut conn = Connection::from_stream(stream).await.unwrap();
+    ///
+    /// let mut config = conn.get_station_config().await.unwrap();
+    /// config.operating_mode = OperatingMode::Control;
+    /// config.station_code = 300;
+    /// config.feedback = StationFeedback::BEEP;
+    /// conn.apply_config(&config).await.unwrap();
+    ///
+    /// assert_eq!(conn.get_station_config().await.unwrap(), config);
+    /// # }
+    /// ```
+    pub async fn apply_config(&mut self, config: &StationConfig) -> Result<(), StationConfigError> {
+        let mut values = self.read_system_values().await?;
+        config.serialize_into(&mut values)?;
+
+        for addr_len in StationConfig::addr_lens() {
+            debug!("writing system values at 0x{:02X}", addr_len.address_byte());
+            self.send_packet(&SetSystemValue {
+                address: addr_len.address_byte(),
+                data: values[addr_len].to_vec(),
+            })
+            .await?;
+            let response = self.receive_packet::<SetSystemValueResponse>().await?;
+            if response.address != addr_len.address_byte() {
+                return Err(StationConfigError::WrongSystemValues);
+            }
+        }
+
+        let applied = self.get_station_config().await?;
+        // srr channel is not written if it is None
+        let expected = StationConfig {
+            srr_channel: config.srr_channel.or(applied.srr_channel),
+            ..config.clone()
+        };
+        if applied != expected {
+            return Err(StationConfigError::VerificationFailed);
+        }
+
+        info!("station config applied");
+        return Ok(());
+    }
+
+    /// Reads the station clock and computes its drift against the host clock (`chrono::Local`).
+    ///
+    /// The host time is taken in the middle of the request and the response.
+    pub async fn get_time(&mut self) -> Result<StationClock, StationTimeError> {
+        if self.protocol == Protocol::Base {
+            return Err(StationTimeError::BaseNotSupported);
+        }
+
+        let sent_at = chrono::Local::now().naive_local();
+        self.send_packet(&GetTime).await?;
+        let response = self.receive_packet::<GetTimeResponse>().await?;
+        let received_at = chrono::Local::now().naive_local();
+
+        let time = StationTime::deserialize(&response.time)?;
+        let host_time = sent_at + (received_at - sent_at) / 2;
+        let drift_ms = (time.datetime - host_time).num_milliseconds();
+        debug!("station time {}, drift {} ms", time.datetime, drift_ms);
+
+        return Ok(StationClock { time, drift_ms });
+    }
+
+    /// Sets the station clock. Day of week and the 4-week counter are computed from the date.
+    ///
+    /// Returns the time the station has set.
+    ///
+    /// * `datetime` - Local date and time (`chrono::Local::now().naive_local()` to sync with the host)
+    pub async fn set_time(
+        &mut self,
+        datetime: chrono::NaiveDateTime,
+    ) -> Result<StationTime, StationTimeError> {
+        if self.protocol == Protocol::Base {
+            return Err(StationTimeError::BaseNotSupported);
+        }
+        if !(2000..=2255).contains(&datetime.year()) {
+            return Err(StationTimeError::OutOfRange(datetime));
+        }
+
+        let time = StationTime::from_datetime(datetime);
+        self.send_packet(&SetTime {
+            time: time.serialize(),
+        })
+        .await?;
+        let response = self.receive_packet::<SetTimeResponse>().await?;
+
+        info!("station time set to {}", time.datetime);
+        return Ok(StationTime::deserialize(&response.time)?);
+    }
+
+    /// Reads all of the punches stored in the backup memory of the station.
+    ///
+    /// * `format` - Format of the records (depends on the station firmware)
+    pub async fn read_backup_memory(
+        &mut self,
+        format: BackupRecordFormat,
+    ) -> Result<Vec<BackupRecord>, BackupMemoryError> {
+        return self
+            .read_backup_memory_with_progress(format, |_, _| {})
+            .await;
+    }
+
+    /// Reads all of the punches stored in the backup memory of the station.
+    ///
+    /// * `format` - Format of the records (depends on the station firmware)
+    /// * `progress` - Called with the number of read bytes and the total number of bytes after every read
+    pub async fn read_backup_memory_with_progress<F: FnMut(u32, u32)>(
+        &mut self,
+        format: BackupRecordFormat,
+        mut progress: F,
+    ) -> Result<Vec<BackupRecord>, BackupMemoryError> {
+        if self.protocol == Protocol::Base {
+            return Err(BackupMemoryError::BaseNotSupported);
+        }
+
+        let values = self.read_system_values().await?;
+        let station_code = StationConfig::deserialize(&values)?.station_code;
+        let memory_size = values[SystemConfigAddrLen::memory_kb()][0] as u32 * 1024;
+
+        let mut end = backup_pointer(&values);
+        if memory_size > BACKUP_MEMORY_START {
+            end = end.min(memory_size);
+        }
+        let record_len = format.record_len() as u32;
+        let total = (end.saturating_sub(BACKUP_MEMORY_START) / record_len) * record_len;
+        debug!("backup memory: {} bytes", total);
+
+        let mut records = Vec::new();
+        let mut read = 0;
+        while read < total {
+            let address = BACKUP_MEMORY_START + read;
+            let len = (total - read).min(128);
+
+            self.send_packet(&GetBackupData {
+                address,
+                len: len as u8,
+            })
+            .await?;
+            let response = self.receive_packet::<GetBackupDataResponse>().await?;
+            if response.address != address {
+                return Err(BackupMemoryError::WrongAddress(address, response.address));
+            }
+            if response.data.len() != len as usize {
+                return Err(BackupMemoryError::WrongDataLen(
+                    len as usize,
+                    response.data.len(),
+                ));
+            }
+
+            for (i, record) in response.data.chunks(record_len as usize).enumerate() {
+                let record_address = address + i as u32 * record_len;
+                let record = BackupRecord::deserialize(record, format, station_code)
+                    .map_err(|e| BackupMemoryError::InvalidRecord(record_address, e))?;
+                if let Some(record) = record {
+                    records.push(record);
+                }
+            }
+
+            read += len;
+            progress(read, total);
+        }
+
+        info!("read {} backup records", records.len());
+        return Ok(records);
+    }
+
+    /// Returns a stream of the punches sent by the station in the autosend mode
+    /// (`ProtocolConfig::AUTO_SEND_OUT`). Other packets are skipped.
+    ///
+    /// Base protocol records are converted to `TransmitRecord`, their punches have no sub second.
+    ///
+    /// The stream waits for the punches without a timeout. It ends after an IO error (e.g. disconnected station).
+    pub fn punch_stream(
+        &mut self,
+    ) -> impl Stream<Item = Result<TransmitRecord, ReceivePacketError>> + '_ {
+        return futures::stream::unfold(Some(self), |conn| async move {
+            let conn = conn?;
+            loop {
+                let raw = match conn
+                    .receive_raw_packet_custom(SICodecTimeout::Infinite, crate::td())
+                    .await
+                {
+                    Ok(raw) => raw,
+                    Err(ReceiveRawPacketError::DeserializeRawPacketError(
+                        DeserializeRawPacketError::IoError(e),
+                    )) => {
+                        warn!("punch stream ended: {}", e);
+                        let e = ReceiveRawPacketError::from(DeserializeRawPacketError::from(e));
+                        return Some((Err(e.into()), None));
+                    }
+                    Err(e) => return Some((Err(e.into()), Some(conn))),
+                };
+
+                let RawPacket::Body(body) = &raw else {
+                    continue;
+                };
+                let record = match body.id {
+                    TransmitRecord::PACKET_ID => raw.deserialize_packet::<TransmitRecord>(),
+                    BaseTransmitRecord::PACKET_ID => raw
+                        .deserialize_packet::<BaseTransmitRecord>()
+                        .map(TransmitRecord::from),
+                    _ => {
+                        debug!("punch stream: skipping packet 0x{:02X}", body.id);
+                        continue;
+                    }
+                };
+
+                return Some((record.map_err(ReceivePacketError::from), Some(conn)));
+            }
+        });
+    }
+
+    /// Returns a continuous stream of the station events (card inserted/removed, punches, ...).
+    ///
+    /// Unlike `Connection::wait_for_card_insert` it does not fail on removals or unexpected packets.
+    /// The stream waits for the events without a timeout and it ends after `StationEvent::Disconnected`.
+    pub fn event_stream(&mut self) -> EventStream<'_>
+    where
+        S: Send,
+    {
+        return EventStream::new(futures::stream::unfold(Some(self), |conn| async move {
+            let conn = conn?;
+            let event = conn.next_event().await;
+            if matches!(event, StationEvent::Disconnected(_)) {
+                return Some((event, None));
+            }
+            return Some((event, Some(conn)));
+        }));
+    }
+
+    /// Waits for the next station event (without a timeout). See `Connection::event_stream`.
+    pub async fn next_event(&mut self) -> StationEvent {
+        return match self
+            .receive_raw_packet_custom(SICodecTimeout::Infinite, crate::td())
+            .await
+        {
+            Ok(raw) => Self::raw_packet_to_event(raw),
+            Err(ReceiveRawPacketError::DeserializeRawPacketError(
+                DeserializeRawPacketError::IoError(e),
+            )) => {
+                warn!("station disconnected: {}", e);
+                StationEvent::Disconnected(e)
+            }
+            Err(ReceiveRawPacketError::DeserializeRawPacketError(e)) => StationEvent::Garbage(e),
+        };
+    }
+
+    /// Converts the received raw packet to the station event
+    fn raw_packet_to_event(raw: RawPacket) -> StationEvent {
+        let RawPacket::Body(body) = &raw else {
+            return StationEvent::Nak;
+        };
+
+        let inserted = |siid: u32, station_code: u16| StationEvent::CardInserted {
+            siid,
+            card_type: CardType::from_siid(siid),
+            station_code,
+        };
+        let event = match body.id {
+            SICardNewerDetected::PACKET_ID => raw
+                .deserialize_packet::<SICardNewerDetected>()
+                .map(|p| inserted(p.siid, p.station_code)),
+            SICard6Detected::PACKET_ID => raw
+                .deserialize_packet::<SICard6Detected>()
+                .map(|p| inserted(p.siid, p.station_code)),
+            SICard5Detected::PACKET_ID => raw
+                .deserialize_packet::<SICard5Detected>()
+                .map(|p| inserted(p.siid, p.station_code)),
+            BaseSICard6Detected::PACKET_ID => raw
+                .deserialize_packet::<BaseSICard6Detected>()
+                .map(|p| inserted(p.siid, p.station_code)),
+            BaseSICard5Detected::PACKET_ID => raw
+                .deserialize_packet::<BaseSICard5Detected>()
+                .map(|p| inserted(p.siid, p.station_code)),
+            SICardRemoved::PACKET_ID => {
+                raw.deserialize_packet::<SICardRemoved>()
+                    .map(|p| StationEvent::CardRemoved {
+                        siid: p.siid,
+                        station_code: p.station_code,
+                    })
+            }
+            BaseSICardRemoved::PACKET_ID => {
+                raw.deserialize_packet::<BaseSICardRemoved>()
+                    .map(|p| StationEvent::CardRemoved {
+                        siid: p.siid,
+                        station_code: p.station_code,
+                    })
+            }
+            TransmitRecord::PACKET_ID => raw
+                .deserialize_packet::<TransmitRecord>()
+                .map(StationEvent::Punch),
+            BaseTransmitRecord::PACKET_ID => raw
+                .deserialize_packet::<BaseTransmitRecord>()
+                .map(|p| StationEvent::Punch(p.into())),
+            _ => return StationEvent::Unexpected(body.clone()),
+        };
+
+        return event.unwrap_or_else(|e| {
+            warn!("could not deserialize the event packet: {}", e);
+            return StationEvent::Unexpected(body.clone());
+        });
+    }
+
     /// Waits for the card to be inserted and returns the SIID
     ///
-    /// *This supports all cards*
+    /// *This supports all cards*, in the base protocol only Card 5 and Card 6 are detected by the station
     pub async fn wait_for_card_insert(&mut self) -> Result<u32, ReceivePacketError> {
         let raw = self
             .receive_raw_packet_custom(SICodecTimeout::Infinite, crate::td())
@@ -185,6 +698,12 @@ impl Connection {
                 }
                 SICard6Detected::PACKET_ID => raw.deserialize_packet::<SICard6Detected>()?.siid,
                 SICard5Detected::PACKET_ID => raw.deserialize_packet::<SICard5Detected>()?.siid,
+                BaseSICard6Detected::PACKET_ID => {
+                    raw.deserialize_packet::<BaseSICard6Detected>()?.siid
+                }
+                BaseSICard5Detected::PACKET_ID => {
+                    raw.deserialize_packet::<BaseSICard5Detected>()?.siid
+                }
                 _ => {
                     return Err(DeserializePacketError::Other(
                         "Received packet is not a card detected packet".into(),
@@ -215,10 +734,18 @@ impl Connection {
     pub async fn set_ms_mode(&mut self, mode: MsMode) -> Result<bool, ConnectionOperationError> {
         info!("changing msmode to {:?}", mode);
 
-        self.send_packet(&SetMsMode { mode }).await?;
-        let response: SetMsModeResponse = self.receive_packet().await?;
-        self.ms_mode = response.mode;
-        if response.mode != mode {
+        let response_mode = match self.protocol {
+            Protocol::Extended => {
+                self.send_packet(&SetMsMode { mode }).await?;
+                self.receive_packet::<SetMsModeResponse>().await?.mode
+            }
+            Protocol::Base => {
+                self.send_packet(&BaseSetMsMode { mode }).await?;
+                self.receive_packet::<BaseSetMsModeResponse>().await?.mode
+            }
+        };
+        self.ms_mode = response_mode;
+        if response_mode != mode {
             warn!("Failed to switch to msmode {:?}, returning Ok(false)", mode);
             return Ok(false);
         }
@@ -323,10 +850,328 @@ impl Connection {
     generate_readout_fn!(readout_card8, Card8, Card8Def);
     generate_readout_fn!(readout_comcardpro, ComCardPro, ComCardProDef);
     generate_readout_fn!(readout_comcardup, ComCardUp, ComCardUpDef);
+    generate_readout_fn!(readout_card5, Card5, Card5Def);
+    generate_readout_fn!(readout_card6, Card6, Card6Def);
+    generate_readout_fn!(readout_pcard, PCard, PCardDef);
+    generate_readout_fn!(readout_tcard, TCard, TCardDef);
+    generate_readout_fn!(readout_fcard, FCard, FCardDef);
+
+    /// Reads one block of the card.
+    ///
+    /// * `command` - Command used for reading (see `CardDefinition::READOUT_COMMAND`)
+    /// * `block_number` - Block number (ignored for Card 5)
+    ///
+    /// Returns the block number and the block data.
+    async fn read_block(
+        &mut self,
+        command: ReadoutCommand,
+        block_number: u8,
+    ) -> Result<(u8, [u8; 128]), ReadoutError> {
+        if self.protocol == Protocol::Base {
+            return self.read_block_base(command, block_number).await;
+        }
+
+        match command {
+            ReadoutCommand::GetSICard5 => self.send_packet(&GetSICard5).await?,
+            ReadoutCommand::GetSICard6 => self.send_packet(&GetSICard6 { block_number }).await?,
+            ReadoutCommand::GetSICardNewer => {
+                self.send_packet(&GetSICardNewer { block_number }).await?
+            }
+        }
+
+        let raw_response = match self.receive_raw_packet().await? {
+            RawPacket::Body(ok) => ok,
+            RawPacket::Nak => return Err(ReadoutError::NakResponse),
+        };
+
+        let (block_number, data) = match raw_response.id {
+            GetSICardNewerResponse::PACKET_ID => {
+                let response = GetSICardNewerResponse::deserialize(raw_response.data)?;
+                (response.block_number, response.data)
+            }
+            GetSICard6Response::PACKET_ID => {
+                let response = GetSICard6Response::deserialize(raw_response.data)?;
+                (response.block_number, response.data)
+            }
+            GetSICard5Response::PACKET_ID => {
+                // Card 5 has only one block
+                (0, GetSICard5Response::deserialize(raw_response.data)?.data)
+            }
+            SICardRemoved::PACKET_ID => return Err(ReadoutError::CardRemoved),
+            _ => return Err(ReadoutError::UnexpectedPacket),
+        };
+
+        return Ok((block_number, data));
+    }
+
+    /// Reads one block of the card in the base protocol (only Card 5 and Card 6).
+    ///
+    /// * `command` - Command used for reading (see `CardDefinition::READOUT_COMMAND`)
+    /// * `block_number` - Block number (ignored for Card 5)
+    ///
+    /// Returns the block number and the block data.
+    async fn read_block_base(
+        &mut self,
+        command: ReadoutCommand,
+        block_number: u8,
+    ) -> Result<(u8, [u8; 128]), ReadoutError> {
+        match command {
+            ReadoutCommand::GetSICard5 => self.send_packet(&BaseGetSICard5).await?,
+            ReadoutCommand::GetSICard6 => {
+                self.send_packet(&BaseGetSICard6 { block_number }).await?
+            }
+            ReadoutCommand::GetSICardNewer => return Err(ReadoutError::BaseNotSupported),
+        }
+
+        let raw_response = match self.receive_raw_packet().await? {
+            RawPacket::Body(ok) => ok,
+            RawPacket::Nak => return Err(ReadoutError::NakResponse),
+        };
+
+        let (block_number, data) = match raw_response.id {
+            BaseGetSICard6Response::PACKET_ID => {
+                let response = BaseGetSICard6Response::deserialize(raw_response.data)?;
+                (response.block_number, response.data)
+            }
+            BaseGetSICard5Response::PACKET_ID => {
+                // Card 5 has only one block
+                (
+                    0,
+                    BaseGetSICard5Response::deserialize(raw_response.data)?.data,
+                )
+            }
+            BaseSICardRemoved::PACKET_ID => return Err(ReadoutError::CardRemoved),
+            _ => return Err(ReadoutError::UnexpectedPacket),
+        };
+
+        return Ok((block_number, data));
+    }
+
+    /// Reads one block of the card, retried according to the `RetryPolicy`.
+    ///
+    /// After a failed attempt the pending input is drained (see `Connection::drain_input`) before the block is requested again.
+    /// Card removal is never retried.
+    ///
+    /// * `command` - Command used for reading (see `CardDefinition::READOUT_COMMAND`)
+    /// * `block_number` - Block number (ignored for Card 5)
+    async fn read_block_with_retry(
+        &mut self,
+        command: ReadoutCommand,
+        block_number: u8,
+    ) -> Result<(u8, [u8; 128]), ReadoutError> {
+        let mut attempt = 1;
+        loop {
+            let error = match self.read_block(command, block_number).await {
+                Ok(block) => return Ok(block),
+                Err(e) => e,
+            };
+            if !is_retryable(&error) || attempt >= self.retry_policy.attempts {
+                return Err(error);
+            }
+
+            warn!(
+                "reading block {} failed ({}), retrying ({}/{})",
+                block_number, error, attempt, self.retry_policy.attempts
+            );
+            self.drain_input().await?;
+            tokio::time::sleep(self.retry_policy.backoff_for(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Discards the packets (and the garbage) sent by the station until it goes quiet,
+    /// so the rest of a broken packet is not taken as the response to the next command.
+    ///
+    /// Returns `ReadoutError::CardRemoved` if the card removal was among the discarded packets.
+    async fn drain_input(&mut self) -> Result<(), ReadoutError> {
+        let quiet = SICodecTimeout::Finite(DRAIN_QUIET_TIME);
+        for _ in 0..DRAIN_MAX_PACKETS {
+            match self.receive_raw_packet_custom(quiet, quiet).await {
+                Ok(RawPacket::Body(body))
+                    if body.id == SICardRemoved::PACKET_ID
+                        || body.id == BaseSICardRemoved::PACKET_ID =>
+                {
+                    return Err(ReadoutError::CardRemoved);
+                }
+                Ok(raw) => debug!("discarding {:?}", raw),
+                Err(ReceiveRawPacketError::DeserializeRawPacketError(
+                    DeserializeRawPacketError::TimedOut,
+                )) => return Ok(()),
+                Err(ReceiveRawPacketError::DeserializeRawPacketError(
+                    DeserializeRawPacketError::IoError(e),
+                )) => return Err(e.into()),
+                Err(e) => debug!("discarding garbage ({})", e),
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Writes one block of the card.
+    ///
+    /// * `command` - Command used for reading the card (see `CardDefinition::READOUT_COMMAND`)
+    /// * `block_number` - Block number
+    /// * `data` - Block data
+    async fn write_block(
+        &mut self,
+        command: ReadoutCommand,
+        block_number: u8,
+        data: &[u8; 128],
+    ) -> Result<(), WritePersonalDataError> {
+        let data = *data;
+        match command {
+            ReadoutCommand::GetSICard5 => return Err(WritePersonalDataError::WriteNotSupported),
+            ReadoutCommand::GetSICard6 => {
+                self.send_packet(&WriteSICard6 { block_number, data })
+                    .await?
+            }
+            ReadoutCommand::GetSICardNewer => {
+                self.send_packet(&WriteSICardNewer { block_number, data })
+                    .await?
+            }
+        }
+
+        let raw_response = match self.receive_raw_packet().await? {
+            RawPacket::Body(ok) => ok,
+            RawPacket::Nak => return Err(WritePersonalDataError::NakResponse),
+        };
+
+        let written_block_number = match raw_response.id {
+            WriteSICardNewerResponse::PACKET_ID => {
+                WriteSICardNewerResponse::deserialize(raw_response.data)?.block_number
+            }
+            WriteSICard6Response::PACKET_ID => {
+                WriteSICard6Response::deserialize(raw_response.data)?.block_number
+            }
+            SICardRemoved::PACKET_ID => return Err(WritePersonalDataError::CardRemoved),
+            _ => return Err(WritePersonalDataError::UnexpectedPacket),
+        };
+
+        if written_block_number != block_number {
+            return Err(WritePersonalDataError::UnexpectedPacket);
+        }
+
+        return Ok(());
+    }
+
+    /// Writes the personal data of the runner to the card and verifies them by reading them back.
+    ///
+    /// Supported cards: Card 6, Card 8, Card 9, Card 10, Card 11, SIAC (and ComCards).
+    ///
+    /// * `siid` - SIID (card id) of the inserted card
+    /// * `data` - Personal data to be written
+    pub async fn write_personal_data(
+        &mut self,
+        siid: u32,
+        data: &CardPersonalData,
+    ) -> Result<(), WritePersonalDataError> {
+        let card_type =
+            CardType::from_siid(siid).ok_or(WritePersonalDataError::CouldNotGetCardType)?;
+
+        return match card_type {
+            CardType::ActiveCard => {
+                self.write_personal_data_generic::<ActiveCardDef>(siid, data)
+                    .await
+            }
+            CardType::Card11 => {
+                self.write_personal_data_generic::<Card11Def>(siid, data)
+                    .await
+            }
+            CardType::Card10 => {
+                self.write_personal_data_generic::<Card10Def>(siid, data)
+                    .await
+            }
+            CardType::Card9 => {
+                self.write_personal_data_generic::<Card9Def>(siid, data)
+                    .await
+            }
+            CardType::Card8 => {
+                self.write_personal_data_generic::<Card8Def>(siid, data)
+                    .await
+            }
+            CardType::ComCardPro => {
+                self.write_personal_data_generic::<ComCardProDef>(siid, data)
+                    .await
+            }
+            CardType::ComCardUp => {
+                self.write_personal_data_generic::<ComCardUpDef>(siid, data)
+                    .await
+            }
+            CardType::Card6 => {
+                self.write_personal_data_generic::<Card6Def>(siid, data)
+                    .await
+            }
+            _ => Err(WritePersonalDataError::CardNotSupported(card_type)),
+        };
+    }
+
+    /// Writes the personal data of the runner to the card using the specified `CardDefinition`.
+    ///
+    /// Only the personal data regions (see `CardDefinition::PERSONAL_DATA_REGIONS`) are changed,
+    /// the rest of the blocks is written back as it was read.
+    ///
+    /// * `<T: CardDefinition>` - Card definition (generic)
+    /// * `siid` - SIID (card id) of the inserted card
+    /// * `data` - Personal data to be written
+    pub async fn write_personal_data_generic<T: CardDefinition>(
+        &mut self,
+        siid: u32,
+        data: &CardPersonalData,
+    ) -> Result<(), WritePersonalDataError> {
+        if self.protocol == Protocol::Base {
+            return Err(WritePersonalDataError::BaseNotSupported);
+        }
+
+        let card_type =
+            CardType::from_siid(siid).ok_or(WritePersonalDataError::CouldNotGetCardType)?;
+
+        if T::PERSONAL_DATA_REGIONS.is_empty() {
+            return Err(WritePersonalDataError::CardNotSupported(card_type));
+        }
+
+        let serialized = T::serialize_personal_data(data)?;
+
+        // read the blocks first, so the data around the personal data stay untouched
+        let mut blocks: BTreeMap<u8, [u8; 128]> = BTreeMap::new();
+        for region in T::PERSONAL_DATA_REGIONS {
+            if let Entry::Vacant(entry) = blocks.entry(region.block) {
+                let (_, block) = self.read_block(T::READOUT_COMMAND, region.block).await?;
+                entry.insert(block);
+            }
+        }
+
+        let mut position = 0;
+        for region in T::PERSONAL_DATA_REGIONS {
+            let block = blocks.get_mut(&region.block).unwrap(); // was read above
+            let offset = region.offset as usize;
+            let len = region.len as usize;
+            block[offset..offset + len].copy_from_slice(&serialized[position..position + len]);
+            position += len;
+        }
+
+        for (block_number, block) in &blocks {
+            debug!("writing block {}", block_number);
+            self.write_block(T::READOUT_COMMAND, *block_number, block)
+                .await?;
+        }
+
+        // verify
+        for (block_number, block) in &blocks {
+            let (_, written) = self.read_block(T::READOUT_COMMAND, *block_number).await?;
+            if written != *block {
+                return Err(WritePersonalDataError::VerificationFailed(*block_number));
+            }
+        }
+
+        info!("personal data written to card {}", siid);
+
+        return Ok(());
+    }
 
     /// Reads out the card.
     ///
     /// **Note: The card series must be one of the supported ones** (see sident::SUPPORTED_CARDS).
+    /// In the base protocol only Card 5 and Card 6 can be read out (`ReadoutError::BaseNotSupported` otherwise).
     ///
     /// * `preferences` - Readout preferences (see the `ReadoutPreference` enum for more info)
     /// * `siid` - SIID (card id)
@@ -357,6 +1202,11 @@ impl Connection {
             CardType::ComCardUp => {
                 ReadoutResult::ComCardUp(self.readout_comcardup(preferences, siid).await?)
             }
+            CardType::Card5 => ReadoutResult::Card5(self.readout_card5(preferences, siid).await?),
+            CardType::Card6 => ReadoutResult::Card6(self.readout_card6(preferences, siid).await?),
+            CardType::PCard => ReadoutResult::PCard(self.readout_pcard(preferences, siid).await?),
+            CardType::TCard => ReadoutResult::TCard(self.readout_tcard(preferences, siid).await?),
+            CardType::FCard => ReadoutResult::FCard(self.readout_fcard(preferences, siid).await?),
             _ => return Err(ReadoutError::CardNotSupported(card_type)),
         };
 
@@ -366,6 +1216,7 @@ impl Connection {
     /// Reads out the card to the specified `CardDefinition`.
     ///
     /// **Note: The card series must be one of the supported ones** (see sident::SUPPORTED_CARDS).
+    /// In the base protocol only the definitions read with `GetSICard5` and `GetSICard6` (Card 5 and Card 6) are supported.
     ///
     /// * `<T: CardDefinition>` - Card definition (generic)
     /// * `preferences` - Readout preferences (see the `ReadoutPreference` enum for more info)
@@ -377,6 +1228,10 @@ impl Connection {
         preferences: &[ReadoutPreference],
         siid: u32,
     ) -> Result<T, ReadoutError> {
+        if self.protocol == Protocol::Base && T::READOUT_COMMAND == ReadoutCommand::GetSICardNewer {
+            return Err(ReadoutError::BaseNotSupported);
+        }
+
         let card_type = CardType::from_siid(siid).ok_or(ReadoutError::CouldNotGetCardType)?;
 
         if !SUPPORTED_CARDS.contains(&card_type) {
@@ -387,10 +1242,10 @@ impl Connection {
 
         let mut carddef = T::new_empty();
 
-        async fn satisfy<TX: CardDefinition>(
+        async fn satisfy<TX: CardDefinition, SX: AsyncRead + AsyncWrite + Unpin>(
             carddef: &mut TX,
             intention: BlockNeededIntention,
-            conn: &mut Connection,
+            conn: &mut Connection<SX>,
         ) -> Result<(), ReadoutError> {
             debug!("trying to satisfy intention {:?}", intention);
             while carddef.block_needed(&intention) != BlockNeededResult::NoNeed {
@@ -402,26 +1257,12 @@ impl Connection {
 
                 debug!("need block {} ({:?})", block_needed, intention);
 
-                conn.send_packet(&GetSICardNewer {
-                    block_number: block_needed,
-                })
-                .await?;
+                let (block_number, data) = conn
+                    .read_block_with_retry(TX::READOUT_COMMAND, block_needed)
+                    .await?;
 
-                let raw_response = match conn.receive_raw_packet().await? {
-                    RawPacket::Body(ok) => ok,
-                    RawPacket::Nak => return Err(ReadoutError::NakResponse),
-                };
-
-                let response: GetSICardNewerResponse = match raw_response.id {
-                    GetSICardNewerResponse::PACKET_ID => {
-                        GetSICardNewerResponse::deserialize(raw_response.data)?
-                    }
-                    SICardRemoved::PACKET_ID => return Err(ReadoutError::CardRemoved),
-                    _ => return Err(ReadoutError::UnexpectedPacket),
-                };
-
-                debug!("feeding carddef with block {}", &response.block_number);
-                carddef.feed_block(response.block_number, &response.data)?;
+                debug!("feeding carddef with block {}", &block_number);
+                carddef.feed_block(block_number, &data)?;
             }
 
             return Ok(());
@@ -449,6 +1290,67 @@ impl Connection {
     }
 }
 
+/// How long the station has to be quiet to consider the input drained
+const DRAIN_QUIET_TIME: Duration = Duration::from_millis(100);
+/// Maximum of the packets discarded while draining (the station may keep sending punches)
+const DRAIN_MAX_PACKETS: usize = 16;
+
+/// Retry policy of the block readout (see `Connection::set_retry_policy`).
+///
+/// CRC errors, timeouts, NAKs and unexpected packets re-request just the failed block.
+/// Card removal and IO errors are never retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts per block (including the first one). 1 means no retry.
+    pub attempts: u8,
+    /// Delay before the first retry, doubled with every next retry
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// No retry - every error aborts the readout
+    pub fn none() -> Self {
+        return Self {
+            attempts: 1,
+            backoff: Duration::ZERO,
+        };
+    }
+
+    /// Returns the delay after the failed attempt
+    ///
+    /// * `attempt` - Number of the failed attempt (starting from 1)
+    pub fn backoff_for(&self, attempt: u8) -> Duration {
+        return self.backoff * 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        return Self {
+            attempts: 3,
+            backoff: Duration::from_millis(50),
+        };
+    }
+}
+
+/// Returns true if the readout error may be fixed by requesting the block again
+fn is_retryable(error: &ReadoutError) -> bool {
+    return match error {
+        ReadoutError::NakResponse
+        | ReadoutError::UnexpectedPacket
+        | ReadoutError::DeserializePacketError(_) => true,
+        ReadoutError::ReceiveRawPacketError(ReceiveRawPacketError::DeserializeRawPacketError(
+            e,
+        )) => matches!(
+            e,
+            DeserializeRawPacketError::TimedOut
+                | DeserializeRawPacketError::ParseError
+                | DeserializeRawPacketError::CrcError
+        ),
+        _ => false,
+    };
+}
+
 /// `ReadoutPreference` is used to specify which blocks should sident readout to make the process faster.
 ///
 /// Simply - Thanks to `ReadoutPreference` sident reads out only blocks you need.
@@ -456,6 +1358,8 @@ impl Connection {
 pub enum ReadoutPreference {
     CardPersonalData,
     Punches,
+    /// Also needed for the sub seconds of the start and finish of Card 10/11 and SIAC
+    /// (they are in the reserve records next to the exclusives)
     CardExclusives,
 }
 
@@ -483,6 +1387,11 @@ pub enum ReadoutResult {
     Card8(Card8Def),
     ComCardPro(ComCardProDef),
     ComCardUp(ComCardUpDef),
+    Card5(Card5Def),
+    Card6(Card6Def),
+    PCard(PCardDef),
+    TCard(TCardDef),
+    FCard(FCardDef),
 }
 
 impl ReadoutResult {
@@ -491,6 +1400,24 @@ impl ReadoutResult {
         let x: GeneralReadout = self.try_into()?;
         return Ok(x);
     }
+
+    /// Makes the `CardImage` from the raw blocks of the card definition (see `CardDefinition::to_image`)
+    pub fn to_image(&self) -> Option<CardImage> {
+        return match self {
+            ReadoutResult::ActiveCard(def) => def.to_image(),
+            ReadoutResult::Card11(def) => def.to_image(),
+            ReadoutResult::Card10(def) => def.to_image(),
+            ReadoutResult::Card9(def) => def.to_image(),
+            ReadoutResult::Card8(def) => def.to_image(),
+            ReadoutResult::ComCardPro(def) => def.to_image(),
+            ReadoutResult::ComCardUp(def) => def.to_image(),
+            ReadoutResult::Card5(def) => def.to_image(),
+            ReadoutResult::Card6(def) => def.to_image(),
+            ReadoutResult::PCard(def) => def.to_image(),
+            ReadoutResult::TCard(def) => def.to_image(),
+            ReadoutResult::FCard(def) => def.to_image(),
+        };
+    }
 }
 
 /// `GeneralReadout` is just the general information from any `CardDefinition`.
@@ -498,7 +1425,7 @@ impl ReadoutResult {
 /// `GeneralReadout` contains:
 /// * SIID
 /// * Personal Data (optional)
-/// * Clear/Check punch
+/// * Clear/Check punch (optional)
 /// * Start punch (optional)
 /// * Finish punch (optional)
 /// * Punches
@@ -509,12 +1436,62 @@ impl ReadoutResult {
 pub struct GeneralReadout {
     pub siid: u32,
     pub personal_data: Option<CardPersonalData>,
-    pub clear_check: Punch,
+    pub clear_check: Option<Punch>,
     pub start: Option<Punch>,
     pub finish: Option<Punch>,
     pub punches: Vec<Punch>,
 }
 
+impl GeneralReadout {
+    /// Places all of the punches in the calendar.
+    ///
+    /// The first punch (usually the clear/check) is placed nearest to the event date (noon), every next punch
+    /// (start, punches, finish) after the previous one, so the 12h only punches crossing midnight
+    /// or multi-day events get the right date (see `PunchTime::resolve` and `PunchTime::resolve_after`).
+    ///
+    /// * `event_date` - Date of the event
+    pub fn resolve_times(&self, event_date: NaiveDate) -> ResolvedTimes {
+        let reference = event_date.and_hms_opt(12, 0, 0).unwrap(); // cant fail
+
+        let mut previous: Option<NaiveDateTime> = None;
+        let mut resolve = |punch: &Punch| -> DatedPunch {
+            let datetime = match previous {
+                Some(previous) => punch.punch_time.resolve_after(previous),
+                None => punch.punch_time.resolve(reference),
+            };
+            previous = Some(datetime);
+            return DatedPunch {
+                station_code: punch.station_code,
+                datetime,
+            };
+        };
+
+        let clear_check = self.clear_check.as_ref().map(&mut resolve);
+        let start = self.start.as_ref().map(&mut resolve);
+        let punches = self.punches.iter().map(&mut resolve).collect();
+        let finish = self.finish.as_ref().map(&mut resolve);
+
+        return ResolvedTimes {
+            clear_check,
+            start,
+            finish,
+            punches,
+        };
+    }
+}
+
+/// Punches of `GeneralReadout` placed in the calendar (see `GeneralReadout::resolve_times`)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedTimes {
+    pub clear_check: Option<DatedPunch>,
+    pub start: Option<DatedPunch>,
+    pub finish: Option<DatedPunch>,
+    pub punches: Vec<DatedPunch>,
+}
+
 impl TryFrom<ReadoutResult> for GeneralReadout {
     type Error = ReadoutResultTransformationError;
 
@@ -564,6 +1541,211 @@ impl TryFrom<&ReadoutResult> for GeneralReadout {
             X::Card10(def) | X::ComCardPro(def) => inner(def),
             X::Card9(def) => inner(def),
             X::Card8(def) | X::ComCardUp(def) => inner(def),
+            X::Card5(def) => inner(def),
+            X::Card6(def) => inner(def),
+            X::PCard(def) => inner(def),
+            X::TCard(def) | X::FCard(def) => inner(def),
         };
     }
 }
+
+/// Event emitted by `ReadoutService`
+#[derive(Debug)]
+pub enum ReadoutEvent {
+    /// The card was read out. The readout is boxed, because it is much larger than `Failed`
+    /// and every event would take its size otherwise (`clippy::large_enum_variant`).
+    Completed(Box<GeneralReadout>),
+    Failed {
+        siid: u32,
+        error: ReadoutServiceError,
+    },
+}
+
+/// Configuration of `ReadoutService`
+#[derive(Debug, Clone)]
+pub struct ReadoutServiceConfig {
+    pub preferences: Vec<ReadoutPreference>,
+    /// Beep count after a successful readout (`None` - no beep)
+    pub success_beeps: Option<u8>,
+    /// Beep count after a failed readout (`None` - no beep)
+    pub error_beeps: Option<u8>,
+    /// How long to wait for the card to be reinserted, if it was removed while reading
+    pub reinsert_timeout: Duration,
+}
+
+impl Default for ReadoutServiceConfig {
+    fn default() -> Self {
+        return Self {
+            preferences: ReadoutPreference::all().to_vec(),
+            success_beeps: Some(1),
+            error_beeps: Some(3),
+            reinsert_timeout: Duration::from_secs(5),
+        };
+    }
+}
+
+/// Automatic readout loop - waits for the card, reads it out, beeps and emits `ReadoutEvent`.
+///
+/// If the card is removed while reading and reinserted (within `ReadoutServiceConfig::reinsert_timeout`),
+/// the readout is retried once.
+///
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use sident::{
+///     connection::{Connection, ReadoutEvent, ReadoutService, ReadoutServiceConfig},
+///     sim::{VirtualCard, VirtualStation},
+/// };
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (station, stream) = VirtualStation::new();
+/// let handle = station.handle();
+/// tokio::spawn(station.run());
+///
+/// let conn = Connection::from_stream(stream).await.unwrap();
+/// let (service, mut events) = ReadoutService::new(conn, ReadoutServiceConfig::default());
+/// tokio::spawn(service.run());
+///
+/// // Card 8 without any block - the station answers with NAK
+/// handle.insert_card(VirtualCard::new(2_000_123, BTreeMap::new()));
+///
+/// match events.recv().await.unwrap() {
+///     ReadoutEvent::Failed { siid, .. } => assert_eq!(siid, 2_000_123),
+///     ReadoutEvent::Completed(_) => unreachable!(),
+/// }
+/// # }
+/// ```
+pub struct ReadoutService<S = ConnectionStream> {
+    conn: Connection<S>,
+    config: ReadoutServiceConfig,
+    events: mpsc::UnboundedSender<ReadoutEvent>,
+    /// Card inserted while waiting for the reinsert of another card
+    pending_siid: Option<u32>,
+}
+
+impl<S> ReadoutService<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Creates a new readout service.
+    ///
+    /// Returns the service and the receiver of the readout events.
+    ///
+    /// * `conn` - Connection to the station
+    /// * `config` - Configuration of the service
+    pub fn new(
+        conn: Connection<S>,
+        config: ReadoutServiceConfig,
+    ) -> (Self, mpsc::UnboundedReceiver<ReadoutEvent>) {
+        let (events, events_rx) = mpsc::unbounded_channel();
+
+        let service = Self {
+            conn,
+            config,
+            events,
+            pending_siid: None,
+        };
+
+        return (service, events_rx);
+    }
+
+    /// Runs the readout loop.
+    ///
+    /// Returns `Ok` after the receiver of the events is dropped (also while waiting for a card)
+    /// or `Err` if the station is disconnected.
+    pub async fn run(mut self) -> Result<(), ReadoutServiceError> {
+        info!("readout service started");
+
+        loop {
+            let siid = match self.pending_siid.take() {
+                Some(siid) => siid,
+                None => {
+                    let event = tokio::select! {
+                        event = self.conn.next_event() => event,
+                        _ = self.events.closed() => {
+                            info!("readout service stopped, the receiver was dropped");
+                            return Ok(());
+                        }
+                    };
+
+                    match event {
+                        StationEvent::CardInserted { siid, .. } => siid,
+                        StationEvent::Disconnected(e) => return Err(e.into()),
+                        event => {
+                            debug!("readout service: ignoring {:?}", event);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let event = match self.read_out(siid).await {
+                Ok(readout) => ReadoutEvent::Completed(Box::new(readout)),
+                Err(error) => {
+                    warn!("readout of {} failed: {}", siid, error);
+                    ReadoutEvent::Failed { siid, error }
+                }
+            };
+
+            let beeps = match event {
+                ReadoutEvent::Completed(_) => self.config.success_beeps,
+                ReadoutEvent::Failed { .. } => self.config.error_beeps,
+            };
+            if let Some(beeps) = beeps
+                && let Err(e) = self.conn.beep_if_station_ready(beeps).await
+            {
+                warn!("could not beep: {}", e);
+            }
+
+            if self.events.send(event).is_err() {
+                info!("readout service stopped, the receiver was dropped");
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reads out the card, retries once if the card is reinserted
+    async fn read_out(&mut self, siid: u32) -> Result<GeneralReadout, ReadoutServiceError> {
+        let mut retried = false;
+
+        loop {
+            match self.conn.read_out(&self.config.preferences, siid).await {
+                Ok(result) => return Ok(result.to_general_readout()?),
+                Err(ReadoutError::CardRemoved) if !retried => {
+                    retried = true;
+                    info!("card {} removed while reading, waiting for reinsert", siid);
+                    if !self.wait_for_reinsert(siid).await? {
+                        return Err(ReadoutError::CardRemoved.into());
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Returns `true` if the same card was reinserted
+    async fn wait_for_reinsert(&mut self, siid: u32) -> Result<bool, ReadoutServiceError> {
+        let wait = async {
+            loop {
+                match self.conn.next_event().await {
+                    StationEvent::CardInserted { siid: inserted, .. } => return Ok(inserted),
+                    StationEvent::Disconnected(e) => return Err(e),
+                    _ => continue,
+                }
+            }
+        };
+
+        let inserted = match tokio::time::timeout(self.config.reinsert_timeout, wait).await {
+            Ok(inserted) => inserted?,
+            Err(_) => return Ok(false),
+        };
+        if inserted != siid {
+            self.pending_siid = Some(inserted);
+            return Ok(false);
+        }
+
+        return Ok(true);
+    }
+}
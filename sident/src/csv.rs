@@ -0,0 +1,496 @@
+/*
+    SI Config+ / SportSoftware compatible CSV
+
+    Semicolon separated, first line is the header. Fields containing the separator, quotes or new lines are quoted ("" is an escaped quote).
+    Times are hh:mm:ss, with the day of week prefix (Mo 10:32:15) if `CsvConfig::day_prefix` is set
+    (12h only punches never have the prefix). Missing punches are empty cells.
+
+    || Readouts (one row per card):
+    No;SIID
+    Check CN;Check time                                 CsvColumns::CLEAR_CHECK
+    Start CN;Start time                                 CsvColumns::START
+    Finish CN;Finish time                               CsvColumns::FINISH
+    First name;Last name;Club;Country;Email;Phone;
+    City;Street;ZIP;Date of birth;Sex                   CsvColumns::PERSONAL_DATA
+    No. of punches;C1;T1;C2;T2;...                      CsvColumns::PUNCHES
+    ||
+
+    || Backup memory (one row per record):
+    No;SIID;CN;Date;Time
+    ||
+
+    Columns are found by the header when reading, so the column sets can differ and unknown columns are ignored.
+    Without the day prefix the day of week is unknown, so the read punches are `twelve_hour_only` (same as the backup
+    records without the date). The 4-week counter is never written, so even with the prefix the read punches
+    are in `WeekOfMonth::Week1` and `PunchTime::resolve` places them in the first week of the counter.
+*/
+
+use bitflags::bitflags;
+use chrono::{Datelike, NaiveDate, NaiveTime};
+
+use crate::{
+    backup::BackupRecord,
+    card::CardPersonalData,
+    connection::GeneralReadout,
+    errors::CsvError,
+    punch::{Punch, PunchTime},
+    time::{DayOfWeek, WeekOfMonth},
+};
+
+const SEPARATOR: char = ';';
+
+bitflags! {
+    /// Optional column sets of the readout CSV
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CsvColumns: u8 {
+        const CLEAR_CHECK =     0b0000_0001;
+        const START =           0b0000_0010;
+        const FINISH =          0b0000_0100;
+        const PERSONAL_DATA =   0b0000_1000;
+        const PUNCHES =         0b0001_0000;
+    }
+}
+
+/// Configuration of the CSV export
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvConfig {
+    /// Columns of the readout CSV
+    pub columns: CsvColumns,
+    /// Prefix the times with the day of week (multi-day events)
+    pub day_prefix: bool,
+}
+
+impl Default for CsvConfig {
+    fn default() -> Self {
+        return Self {
+            columns: CsvColumns::all(),
+            day_prefix: false,
+        };
+    }
+}
+
+const PERSONAL_DATA_COLUMNS: [&str; 11] = [
+    "First name",
+    "Last name",
+    "Club",
+    "Country",
+    "Email",
+    "Phone",
+    "City",
+    "Street",
+    "ZIP",
+    "Date of birth",
+    "Sex",
+];
+
+fn personal_data_fields(data: &mut CardPersonalData) -> [&mut Option<String>; 11] {
+    return [
+        &mut data.first_name,
+        &mut data.last_name,
+        &mut data.club,
+        &mut data.country,
+        &mut data.email,
+        &mut data.phone,
+        &mut data.city,
+        &mut data.street,
+        &mut data.zipcode,
+        &mut data.birthdate,
+        &mut data.gender,
+    ];
+}
+
+/// Writes the readouts as CSV
+///
+/// * `readouts` - Readouts
+/// * `config` - Configuration
+///
+/// # Example
+/// ```
+/// use sident::csv::{CsvColumns, CsvConfig, write_readouts};
+/// # use sident::{connection::GeneralReadout, punch::Punch};
+/// # let readout = GeneralReadout {
+/// #     siid: 8123456,
+/// #     personal_data: None,
+/// #     clear_check: None,
+/// #     start: None,
+/// #     finish: None,
+/// #     punches: vec![Punch { station_code: 31, ..Default::default() }],
+/// # };
+///
+/// let config = CsvConfig {
+///     columns: CsvColumns::PUNCHES,
+///     day_prefix: false,
+/// };
+///
+/// assert_eq!(
+///     write_readouts(&[readout], &config),
+///     "No;SIID;No. of punches;C1;T1\r\n1;8123456;1;31;00:00:00\r\n"
+/// );
+/// ```
+pub fn write_readouts(readouts: &[GeneralReadout], config: &CsvConfig) -> String {
+    let columns = config.columns;
+    let max_punches = readouts.iter().map(|r| r.punches.len()).max().unwrap_or(0);
+
+    let mut header = vec!["No".to_string(), "SIID".to_string()];
+    for (flag, name) in [
+        (CsvColumns::CLEAR_CHECK, "Check"),
+        (CsvColumns::START, "Start"),
+        (CsvColumns::FINISH, "Finish"),
+    ] {
+        if columns.contains(flag) {
+            header.push(format!("{} CN", name));
+            header.push(format!("{} time", name));
+        }
+    }
+    if columns.contains(CsvColumns::PERSONAL_DATA) {
+        header.extend(PERSONAL_DATA_COLUMNS.map(String::from));
+    }
+    if columns.contains(CsvColumns::PUNCHES) {
+        header.push("No. of punches".to_string());
+        for i in 1..=max_punches {
+            header.push(format!("C{}", i));
+            header.push(format!("T{}", i));
+        }
+    }
+
+    let mut output = String::new();
+    write_row(&mut output, &header);
+
+    for (i, readout) in readouts.iter().enumerate() {
+        let mut row = vec![(i + 1).to_string(), readout.siid.to_string()];
+        for (flag, punch) in [
+            (CsvColumns::CLEAR_CHECK, readout.clear_check.as_ref()),
+            (CsvColumns::START, readout.start.as_ref()),
+            (CsvColumns::FINISH, readout.finish.as_ref()),
+        ] {
+            if columns.contains(flag) {
+                row.extend(punch_cells(punch, config.day_prefix));
+            }
+        }
+        if columns.contains(CsvColumns::PERSONAL_DATA) {
+            let mut personal_data = readout.personal_data.clone().unwrap_or_default();
+            row.extend(
+                personal_data_fields(&mut personal_data)
+                    .map(|field| field.clone().unwrap_or_default()),
+            );
+        }
+        if columns.contains(CsvColumns::PUNCHES) {
+            row.push(readout.punches.len().to_string());
+            for punch in &readout.punches {
+                row.extend(punch_cells(Some(punch), config.day_prefix));
+            }
+            // every row has the same number of cells
+            row.resize(
+                row.len() + (max_punches - readout.punches.len()) * 2,
+                String::new(),
+            );
+        }
+        write_row(&mut output, &row);
+    }
+
+    return output;
+}
+
+/// Reads the readouts from CSV (see `write_readouts`)
+///
+/// * `csv` - CSV
+pub fn read_readouts(csv: &str) -> Result<Vec<GeneralReadout>, CsvError> {
+    let mut rows = parse_rows(csv).into_iter();
+    let header = Header::new(rows.next().unwrap_or_default());
+    header.required("SIID")?;
+
+    let mut readouts = Vec::new();
+    for (i, row) in rows.enumerate() {
+        let line = i + 2;
+        let cells = Cells {
+            header: &header,
+            row: &row,
+            line,
+        };
+
+        let mut personal_data = CardPersonalData::default();
+        for (name, field) in PERSONAL_DATA_COLUMNS
+            .iter()
+            .zip(personal_data_fields(&mut personal_data))
+        {
+            *field = cells.get(name).filter(|v| !v.is_empty()).map(String::from);
+        }
+        let has_personal_data = personal_data != CardPersonalData::default();
+
+        let mut punches = Vec::new();
+        for n in 1.. {
+            let (code, time) = (format!("C{}", n), format!("T{}", n));
+            if header.find(&code).is_none() {
+                break;
+            }
+            if let Some(punch) = cells.punch(&code, &time)? {
+                punches.push(punch);
+            }
+        }
+
+        readouts.push(GeneralReadout {
+            siid: cells.parse("SIID", cells.get("SIID").unwrap_or_default())?,
+            personal_data: has_personal_data.then_some(personal_data),
+            clear_check: cells.punch("Check CN", "Check time")?,
+            start: cells.punch("Start CN", "Start time")?,
+            finish: cells.punch("Finish CN", "Finish time")?,
+            punches,
+        });
+    }
+
+    return Ok(readouts);
+}
+
+/// Writes the backup memory records as CSV
+///
+/// * `records` - Backup records
+/// * `config` - Configuration (only `day_prefix` is used)
+pub fn write_backup_records(records: &[BackupRecord], config: &CsvConfig) -> String {
+    let mut output = String::new();
+    write_row(
+        &mut output,
+        &["No", "SIID", "CN", "Date", "Time"].map(String::from),
+    );
+
+    for (i, record) in records.iter().enumerate() {
+        let row = [
+            (i + 1).to_string(),
+            record.siid.to_string(),
+            record.punch.station_code.to_string(),
+            record.date.map(|date| date.to_string()).unwrap_or_default(),
+            format_time(&record.punch.punch_time, config.day_prefix),
+        ];
+        write_row(&mut output, &row);
+    }
+
+    return output;
+}
+
+/// Reads the backup memory records from CSV (see `write_backup_records`)
+///
+/// * `csv` - CSV
+pub fn read_backup_records(csv: &str) -> Result<Vec<BackupRecord>, CsvError> {
+    let mut rows = parse_rows(csv).into_iter();
+    let header = Header::new(rows.next().unwrap_or_default());
+    for column in ["SIID", "CN", "Time"] {
+        header.required(column)?;
+    }
+
+    let mut records = Vec::new();
+    for (i, row) in rows.enumerate() {
+        let cells = Cells {
+            header: &header,
+            row: &row,
+            line: i + 2,
+        };
+
+        let date = match cells.get("Date").filter(|v| !v.is_empty()) {
+            Some(value) => Some(cells.parse::<NaiveDate>("Date", value)?),
+            None => None,
+        };
+        let mut punch = cells.punch("CN", "Time")?.ok_or(CsvError::InvalidValue(
+            cells.line,
+            "Time".to_string(),
+            String::new(),
+        ))?;
+        match date {
+            Some(date) => {
+                punch.punch_time.day = DayOfWeek::from_weekday(date.weekday());
+                punch.punch_time.week = WeekOfMonth::for_date(date);
+                punch.punch_time.twelve_hour_only = false;
+            }
+            // same as in the backup memory, without the date the day and week are unknown
+            None => punch.punch_time.twelve_hour_only = true,
+        }
+
+        records.push(BackupRecord {
+            siid: cells.parse("SIID", cells.get("SIID").unwrap_or_default())?,
+            punch,
+            date,
+        });
+    }
+
+    return Ok(records);
+}
+
+fn punch_cells(punch: Option<&Punch>, day_prefix: bool) -> [String; 2] {
+    return match punch {
+        Some(punch) => [
+            punch.station_code.to_string(),
+            format_time(&punch.punch_time, day_prefix),
+        ],
+        None => [String::new(), String::new()],
+    };
+}
+
+fn day_abbreviation(day: DayOfWeek) -> &'static str {
+    return match day {
+        DayOfWeek::Monday => "Mo",
+        DayOfWeek::Tuesday => "Tu",
+        DayOfWeek::Wednesday => "We",
+        DayOfWeek::Thursday => "Th",
+        DayOfWeek::Friday => "Fr",
+        DayOfWeek::Saturday => "Sa",
+        DayOfWeek::Sunday => "Su",
+    };
+}
+
+/// Formats the time the SI way - hh:mm:ss, optionally with the day of week prefix
+fn format_time(punch_time: &PunchTime, day_prefix: bool) -> String {
+    let time = punch_time.time.format("%H:%M:%S").to_string();
+    if !day_prefix || punch_time.twelve_hour_only {
+        return time;
+    }
+    return format!("{} {}", day_abbreviation(punch_time.day), time);
+}
+
+/// Parses the time made by `format_time`. The time without the day prefix is `twelve_hour_only`.
+fn parse_time(value: &str) -> Option<PunchTime> {
+    let (day, time) = match value.split_once(' ') {
+        Some((day, time)) => (Some(day), time),
+        None => (None, value),
+    };
+    let day = match day {
+        Some(day) => Some(
+            [
+                DayOfWeek::Monday,
+                DayOfWeek::Tuesday,
+                DayOfWeek::Wednesday,
+                DayOfWeek::Thursday,
+                DayOfWeek::Friday,
+                DayOfWeek::Saturday,
+                DayOfWeek::Sunday,
+            ]
+            .into_iter()
+            .find(|d| day_abbreviation(*d).eq_ignore_ascii_case(day))?,
+        ),
+        None => None,
+    };
+    let time = NaiveTime::parse_from_str(time, "%H:%M:%S").ok()?;
+
+    return Some(PunchTime {
+        time,
+        day: day.unwrap_or_default(),
+        // without the day of week the punch can not be placed in the 4-week cycle
+        twelve_hour_only: day.is_none(),
+        ..Default::default()
+    });
+}
+
+fn write_row(output: &mut String, row: &[String]) {
+    let cells: Vec<String> = row
+        .iter()
+        .map(|cell| {
+            if cell.contains([SEPARATOR, '"', '\r', '\n']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect();
+    output.push_str(&cells.join(&SEPARATOR.to_string()));
+    output.push_str("\r\n");
+}
+
+/// Splits the CSV into rows and cells (handles the quoted cells)
+fn parse_rows(csv: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = csv.trim_start_matches('\u{FEFF}').chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match (ch, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (SEPARATOR, false) => row.push(std::mem::take(&mut cell)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            (ch, _) => cell.push(ch),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    // skip empty lines
+    rows.retain(|row| row.iter().any(|cell| !cell.is_empty()));
+    return rows;
+}
+
+struct Header {
+    columns: Vec<String>,
+}
+
+impl Header {
+    fn new(columns: Vec<String>) -> Self {
+        return Self {
+            columns: columns.into_iter().map(|c| c.trim().to_string()).collect(),
+        };
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        return self
+            .columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(name));
+    }
+
+    fn required(&self, name: &'static str) -> Result<usize, CsvError> {
+        return self.find(name).ok_or(CsvError::MissingColumn(name));
+    }
+}
+
+/// Cells of one row
+struct Cells<'a> {
+    header: &'a Header,
+    row: &'a [String],
+    line: usize,
+}
+
+impl Cells<'_> {
+    fn get(&self, name: &str) -> Option<&str> {
+        let index = self.header.find(name)?;
+        return Some(self.row.get(index).map(|v| v.trim()).unwrap_or_default());
+    }
+
+    fn parse<T: std::str::FromStr>(&self, name: &str, value: &str) -> Result<T, CsvError> {
+        return value
+            .trim()
+            .parse()
+            .map_err(|_| CsvError::InvalidValue(self.line, name.to_string(), value.to_string()));
+    }
+
+    /// Returns `None` if the columns are missing or empty
+    fn punch(&self, code_column: &str, time_column: &str) -> Result<Option<Punch>, CsvError> {
+        let time = match self.get(time_column).filter(|v| !v.is_empty()) {
+            Some(time) => time,
+            None => return Ok(None),
+        };
+        let punch_time = parse_time(time).ok_or(CsvError::InvalidValue(
+            self.line,
+            time_column.to_string(),
+            time.to_string(),
+        ))?;
+        let station_code = match self.get(code_column).filter(|v| !v.is_empty()) {
+            Some(code) => self.parse(code_column, code)?,
+            None => 0,
+        };
+
+        return Ok(Some(Punch {
+            station_code,
+            punch_time,
+        }));
+    }
+}
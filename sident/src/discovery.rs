@@ -0,0 +1,89 @@
+use log::{debug, warn};
+use tokio_serial::{SerialPortInfo, SerialPortType};
+
+use crate::{
+    connection::Connection, errors::DiscoveryError, firmware::FirmwareVersion,
+    product::ProductModel,
+};
+
+/// USB vendor ID of the SPORTident stations (Silicon Labs CP210x)
+pub const SPORTIDENT_VID: u16 = 0x10C4;
+/// USB product ID of the SPORTident stations
+pub const SPORTIDENT_PID: u16 = 0x800A;
+
+/// Station found by `list_stations`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredStation {
+    /// Name of the port (pass it to `Connection::new`)
+    pub port: String,
+    pub model: ProductModel,
+    pub serial: u32,
+    pub firmware: FirmwareVersion,
+}
+
+fn is_sportident_port(port: &SerialPortInfo) -> bool {
+    return match &port.port_type {
+        SerialPortType::UsbPort(usb) => usb.vid == SPORTIDENT_VID && usb.pid == SPORTIDENT_PID,
+        _ => false,
+    };
+}
+
+/// Returns the names of the serial ports with the SPORTident USB VID/PID. The ports are not opened.
+pub fn list_candidate_ports() -> Result<Vec<String>, DiscoveryError> {
+    let ports = tokio_serial::available_ports()?;
+    return Ok(ports
+        .into_iter()
+        .filter(is_sportident_port)
+        .map(|port| port.port_name)
+        .collect());
+}
+
+/// Finds the connected SPORTident stations.
+///
+/// Every candidate port (see `list_candidate_ports`) is probed with the same handshake as `Connection::new`.
+/// Ports which do not answer (or are used by another program) are skipped. Stations in the base protocol
+/// are skipped too, because their system config can not be read.
+///
+/// # Example
+/// ```no_run
+/// # async fn example() {
+/// use sident::{connection::Connection, discovery::list_stations};
+///
+/// let stations = list_stations().await.expect("Failed to list the ports");
+/// let station = stations.first().expect("No station connected");
+/// let conn = Connection::new(&station.port).await.expect("Failed to connect");
+/// # }
+/// ```
+pub async fn list_stations() -> Result<Vec<DiscoveredStation>, DiscoveryError> {
+    let probes = list_candidate_ports()?.into_iter().map(probe);
+    let stations = futures::future::join_all(probes).await;
+
+    return Ok(stations.into_iter().flatten().collect());
+}
+
+/// Connects to the port and returns the station info. The port is closed afterwards.
+async fn probe(port: String) -> Option<DiscoveredStation> {
+    debug!("probing port {}", port);
+    let conn = match Connection::new(&port).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            warn!("port {} did not answer: {}", port, e);
+            return None;
+        }
+    };
+
+    let Some(config) = conn.get_system_config() else {
+        warn!("station on port {} uses the base protocol, skipping", port);
+        return None;
+    };
+
+    return Some(DiscoveredStation {
+        model: config.model,
+        serial: config.serial,
+        firmware: config.firmware_ver,
+        port,
+    });
+}
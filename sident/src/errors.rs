@@ -3,13 +3,19 @@ use std::{array::TryFromSliceError, num::ParseIntError};
 use num_enum::TryFromPrimitiveError;
 use thiserror::Error;
 
+pub use sident_core::errors::{
+    DeserializeBlockError, DeserializeCardPersonalDataError, DeserializePacketError,
+    DeserializePunchError, FeedBlockError, FrameError, SerializeCardPersonalDataError,
+};
+
 use crate::{card::CardType, product::ProductModel};
 
 macro_rules! _sident_err_gen {
-    ({ $( $variant:ident => ($source:ty, $msg:literal) ),* $(,)? }) => {
+    ({ $( $(#[$attr:meta])* $variant:ident => ($source:ty, $msg:literal) ),* $(,)? }) => {
         #[derive(Debug, thiserror::Error)]
         pub enum SidentError {
             $(
+                $(#[$attr])*
                 #[error($msg)]
                 $variant(#[from] $source),
             )*
@@ -26,13 +32,25 @@ _sident_err_gen!({
     FirmwareVersionCodecError => (FirmwareVersionCodecError, "firmware version codec error: {0}"),
     MakeSystemConfigError => (MakeSystemConfigError, "make sysconfig error: {0}"),
     ReadoutError => (ReadoutError, "readout error: {0}"),
+    WritePersonalDataError => (WritePersonalDataError, "write personal data error: {0}"),
     DeserializePunchError => (DeserializePunchError, "deserialize punch error: {0}"),
     DeserializeCardPersonalDataError => (DeserializeCardPersonalDataError, "deserialize card personal data error: {0}"),
+    SerializeCardPersonalDataError => (SerializeCardPersonalDataError, "serialize card personal data error: {0}"),
     FeedBlockError => (FeedBlockError, "feed block error: {0}"),
     DeserializeBlockError => (DeserializeBlockError, "deserialize block error: {0}"),
     ConnectionOperationError => (ConnectionOperationError, "connection op error: {0}"),
+    StationConfigError => (StationConfigError, "station config error: {0}"),
+    StationTimeError => (StationTimeError, "station time error: {0}"),
+    BackupMemoryError => (BackupMemoryError, "backup memory error: {0}"),
     ReadoutResultTransformationError => (ReadoutResultTransformationError, "readout result transf error: {0}"),
+    ReadoutServiceError => (ReadoutServiceError, "readout service error: {0}"),
     SimpleActionError => (SimpleActionError, "simple action error: {0}"),
+    CsvError => (CsvError, "csv error: {0}"),
+    CardImageError => (CardImageError, "card image error: {0}"),
+    #[cfg(not(target_os = "android"))]
+    DiscoveryError => (DiscoveryError, "discovery error: {0}"),
+    #[cfg(feature = "iof")]
+    IofError => (IofError, "iof error: {0}"),
     IoError => (std::io::Error, "io error: {0}")
 });
 
@@ -60,18 +78,6 @@ pub enum NewConnectionError {
     FailedToSetMsMode,
 }
 
-#[derive(Debug, Error)]
-pub enum DeserializePacketError {
-    #[error("receive raw packet error {0}")]
-    DeserializeRawPacketError(#[from] DeserializeRawPacketError),
-    #[error("response is NAK")]
-    ResponseIsNak,
-    #[error("data len is wrong")]
-    WrongDataLen,
-    #[error("{0}")]
-    Other(String),
-}
-
 #[derive(Debug, Error)]
 pub enum ReceivePacketError {
     #[error("receive raw packet error {0}")]
@@ -103,6 +109,15 @@ pub enum DeserializeRawPacketError {
     CrcError,
 }
 
+impl From<FrameError> for DeserializeRawPacketError {
+    fn from(value: FrameError) -> Self {
+        return match value {
+            FrameError::ParseError => Self::ParseError,
+            FrameError::CrcError => Self::CrcError,
+        };
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum FirmwareVersionCodecError {
     #[error("{0}")]
@@ -126,7 +141,7 @@ pub enum MakeSystemConfigError {
 #[derive(Debug, Error)]
 pub enum ReadoutError {
     #[error(
-        "Base (legacy) protocol is not supported. Please check the Extended protocol flag in SIConfig+ or use a newer station"
+        "Only Card 5 and Card 6 can be read out in the base (legacy) protocol. Please check the Extended protocol flag in SIConfig+ or use a newer station"
     )]
     BaseNotSupported,
     #[error("{0} is not supported.")]
@@ -154,55 +169,105 @@ pub enum ReadoutError {
 }
 
 #[derive(Debug, Error)]
-pub enum DeserializePunchError {
-    #[error("Invalid punch time")]
-    InvalidTime,
-    #[error("Invalid day")]
-    InvalidDay,
-    #[error("Invalid week")]
-    InvalidWeek,
-    #[error("The input is cleared (0xFF)")]
-    DataCleared,
-    #[error("Invalid data")]
-    InvalidData,
+pub enum WritePersonalDataError {
+    #[error(
+        "Base (legacy) protocol is not supported. Please check the Extended protocol flag in SIConfig+ or use a newer station"
+    )]
+    BaseNotSupported,
+    #[error("{0} is not supported.")]
+    CardNotSupported(CardType),
+    #[error("Could not get the card type")]
+    CouldNotGetCardType,
+    #[error("Writing is not supported by the readout command")]
+    WriteNotSupported,
+    #[error("Serialize card personal data error: {0}")]
+    SerializeCardPersonalDataError(#[from] SerializeCardPersonalDataError),
+    #[error("Readout error: {0}")]
+    ReadoutError(#[from] ReadoutError),
+    #[error("Receieve RawPacket error: {0}")]
+    ReceiveRawPacketError(#[from] ReceiveRawPacketError),
+    #[error("deserialize packet error: {0}")]
+    DeserializePacketError(#[from] DeserializePacketError),
+    #[error("io error {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Got NAK response")]
+    NakResponse,
+    #[error("Got unexpected packet")]
+    UnexpectedPacket,
+    #[error("Card removed while writing")]
+    CardRemoved,
+    #[error("Block {0} does not match the written data")]
+    VerificationFailed(u8),
 }
 
 #[derive(Debug, Error)]
-pub enum DeserializeCardPersonalDataError {
-    #[error("Required fields (first and last name) are empty")]
-    RequiredFieldsAreEmpty,
-    #[error("From UTF8 error: {0}")]
-    FromUtf8Error(#[from] std::string::FromUtf8Error),
-    #[error("Data is too long")]
-    DataTooLong,
+pub enum ConnectionOperationError {
+    #[error("io error {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("receive packet error: {0}")]
+    ReceivePacketError(#[from] ReceivePacketError),
+    #[error("receive raw packet error {0}")]
+    ReceiveRawPacketError(#[from] ReceiveRawPacketError),
 }
 
 #[derive(Debug, Error)]
-pub enum FeedBlockError {
-    #[error("The provided block buffer does not match with the block id")]
-    BlockIdAndBufferNotMatching,
-    #[error("This card definition does no have that block")]
-    BlockDoesNotExist,
-    #[error("Deserialize block error: {0}")]
-    DeserializeBlockError(#[from] DeserializeBlockError),
+pub enum StationConfigError {
+    #[error("System values are not available in the base (legacy) protocol")]
+    BaseNotSupported,
+    #[error("io error {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("receive packet error: {0}")]
+    ReceivePacketError(#[from] ReceivePacketError),
+    #[error("Invalid operating mode 0x{0:02X}")]
+    InvalidOperatingMode(u8),
+    #[error("Invalid station code {0} (1-1023)")]
+    InvalidStationCode(u16),
+    #[error("Station returned wrong system values")]
+    WrongSystemValues,
+    #[error("The config read back from the station does not match the applied config")]
+    VerificationFailed,
 }
 
 #[derive(Debug, Error)]
-pub enum DeserializeBlockError {
-    #[error("Deserialize punch error {0}")]
-    DeserializePunchError(#[from] DeserializePunchError),
-    #[error("Could not deserialize a date")]
-    DateError,
+pub enum StationTimeError {
+    #[error("Station clock is not available in the base (legacy) protocol")]
+    BaseNotSupported,
+    #[error("io error {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("receive packet error: {0}")]
+    ReceivePacketError(#[from] ReceivePacketError),
+    #[error("Station returned invalid time: {0}")]
+    InvalidTime(#[from] DeserializePunchError),
+    #[error("Time {0} is out of the station range (2000-2255)")]
+    OutOfRange(chrono::NaiveDateTime),
 }
 
 #[derive(Debug, Error)]
-pub enum ConnectionOperationError {
+pub enum BackupMemoryError {
+    #[error("Backup memory is not available in the base (legacy) protocol")]
+    BaseNotSupported,
     #[error("io error {0}")]
     IoError(#[from] std::io::Error),
     #[error("receive packet error: {0}")]
     ReceivePacketError(#[from] ReceivePacketError),
-    #[error("receive raw packet error {0}")]
-    ReceiveRawPacketError(#[from] ReceiveRawPacketError),
+    #[error("station config error: {0}")]
+    StationConfigError(#[from] StationConfigError),
+    #[error("Station returned data of address 0x{1:06X} instead of 0x{0:06X}")]
+    WrongAddress(u32, u32),
+    #[error("Station returned {1} bytes instead of {0}")]
+    WrongDataLen(usize, usize),
+    #[error("Invalid record at 0x{0:06X}: {1}")]
+    InvalidRecord(u32, DeserializePunchError),
+}
+
+#[derive(Debug, Error)]
+pub enum ReadoutServiceError {
+    #[error("Readout error: {0}")]
+    ReadoutError(#[from] ReadoutError),
+    #[error("Readout result transformation error: {0}")]
+    ReadoutResultTransformationError(#[from] ReadoutResultTransformationError),
+    #[error("io error {0}")]
+    IoError(#[from] std::io::Error),
 }
 
 #[derive(Debug, Error)]
@@ -226,3 +291,54 @@ pub enum SimpleActionError {
     #[error("receive raw packet error {0}")]
     ReceiveRawPacketError(#[from] ReceiveRawPacketError),
 }
+
+#[cfg(not(target_os = "android"))]
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("SerialPort error: {0}")]
+    SerialportError(#[from] tokio_serial::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum CardImageError {
+    #[error("Invalid magic")]
+    InvalidMagic,
+    #[error("Unsupported format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Unknown card type code {0}")]
+    UnknownCardType(u8),
+    #[error("Invalid length of the image")]
+    InvalidLength,
+    #[error("Invalid data of block {0}")]
+    InvalidBlock(u8),
+    #[error("Card {0:?} is not supported")]
+    CardNotSupported(CardType),
+    #[error("Feed block error: {0}")]
+    FeedBlockError(#[from] FeedBlockError),
+    #[cfg(feature = "serde")]
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum CsvError {
+    #[error("Missing column {0}")]
+    MissingColumn(&'static str),
+    #[error("Invalid value of {1} on line {0}: {2}")]
+    InvalidValue(usize, String, String),
+}
+
+#[cfg(feature = "iof")]
+#[derive(Debug, Error)]
+pub enum IofError {
+    #[error("XML error: {0}")]
+    XmlError(#[from] quick_xml::Error),
+    #[error("XML encoding error: {0}")]
+    EncodingError(#[from] quick_xml::encoding::EncodingError),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid value of <{0}>: {1}")]
+    InvalidValue(&'static str, String),
+    #[error("Missing <{0}>")]
+    MissingValue(&'static str),
+}
@@ -0,0 +1,55 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+
+use crate::{
+    card::CardType, errors::DeserializeRawPacketError, packet::RawPacketBody,
+    packets::hostbound::TransmitRecord,
+};
+
+/// Event sent by the station (see `Connection::event_stream`)
+#[derive(Debug)]
+pub enum StationEvent {
+    /// Card was inserted into the station
+    CardInserted {
+        siid: u32,
+        card_type: Option<CardType>,
+        station_code: u16,
+    },
+    /// Card was removed from the station
+    CardRemoved { siid: u32, station_code: u16 },
+    /// Punch sent in the autosend mode
+    Punch(TransmitRecord),
+    /// Station sent `NAK`
+    Nak,
+    /// Received bytes could not be parsed as a packet (invalid CRC, timeout in the middle of the packet, ...)
+    Garbage(DeserializeRawPacketError),
+    /// Valid packet which is not an event (or it could not be deserialized)
+    Unexpected(RawPacketBody),
+    /// The stream was closed. This is the last event.
+    Disconnected(std::io::Error),
+}
+
+/// Stream of `StationEvent`s (see `Connection::event_stream`)
+pub struct EventStream<'a> {
+    inner: Pin<Box<dyn Stream<Item = StationEvent> + Send + 'a>>,
+}
+
+impl<'a> EventStream<'a> {
+    pub(crate) fn new(inner: impl Stream<Item = StationEvent> + Send + 'a) -> Self {
+        return Self {
+            inner: Box::pin(inner),
+        };
+    }
+}
+
+impl Stream for EventStream<'_> {
+    type Item = StationEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        return self.inner.as_mut().poll_next(cx);
+    }
+}
@@ -0,0 +1,625 @@
+/*
+    IOF XML 3.0 (https://orienteering.sport/iof/it/data-standard-3-0/)
+
+    ---- RESULT LIST ----
+    Readouts are written as a ResultList with one ClassResult:
+
+    <ResultList iofVersion="3.0">
+      <Event>
+        <Name/>
+        <StartTime><Date/></StartTime>
+      </Event>
+      <ClassResult>
+        <Class><Name/></Class>
+        <PersonResult>                          one for every readout
+          <Person><Name><Family/><Given/></Name></Person>
+          <Organisation><Name/></Organisation>  club from the personal data (optional)
+          <Result>
+            <StartTime/>                        absolute date time (optional)
+            <FinishTime/>                       absolute date time (optional)
+            <Time/>                             seconds from start to finish (optional)
+            <Status/>                           Finished or DidNotFinish
+            <SplitTime>                         one for every punch
+              <ControlCode/>
+              <Time/>                           seconds from the start (missing if the card has no start)
+            </SplitTime>
+            <ControlCard punchingSystem="SI"/>  SIID
+          </Result>
+        </PersonResult>
+      </ClassResult>
+    </ResultList>
+
+    IOF XML has no clear/check and no station codes of the start and the finish. When reading,
+    the start and the finish get the station code 0 and there is no clear/check.
+    Split times are relative to the start, so punches of the cards without the start are lost.
+    ----            ----
+
+    ---- CARD READ LIST ----
+    IOF XML 3.0 has no message for the card readouts, so the readouts are also written as a list
+    of CardRead elements. It is not an IOF document - it has its own namespace (`CARD_READ_LIST_NAMESPACE`),
+    only the ControlCard, Person and Organisation elements are borrowed from IOF. Only the personal data
+    other than the name and the club are lost, the times are written as resolved (see `GeneralReadout::resolve_times`):
+
+    <CardReadList xmlns="urn:sident:card-read-list">
+      <CardRead>                                one for every readout
+        <ControlCard punchingSystem="SI"/>      SIID
+        <Person><Name><Family/><Given/></Name></Person>  (optional)
+        <Organisation><Name/></Organisation>    club from the personal data (optional)
+        <ClearCheck>
+          <ControlCode/>
+          <Time/>                               absolute date time
+        </ClearCheck>
+        <Start/>                                same as ClearCheck (optional)
+        <Finish/>                               same as ClearCheck (optional)
+        <Punch/>                                same as ClearCheck, one for every punch
+      </CardRead>
+    </CardReadList>
+    ----            ----
+*/
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use quick_xml::{
+    Reader, Writer,
+    escape::resolve_predefined_entity,
+    events::{BytesDecl, BytesText, Event},
+};
+
+use crate::{
+    card::CardPersonalData,
+    connection::GeneralReadout,
+    errors::IofError,
+    punch::{DatedPunch, Punch, PunchTime},
+};
+
+const IOF_NAMESPACE: &str = "http://www.orienteering.org/datastandard/3.0";
+/// Namespace of the card read list, which is not a part of IOF XML
+const CARD_READ_LIST_NAMESPACE: &str = "urn:sident:card-read-list";
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// Event written to the IOF result list
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IofEvent {
+    pub name: String,
+    /// Date of the event (punches are placed in the calendar around it, see `GeneralReadout::resolve_times`)
+    pub date: NaiveDate,
+    /// Name of the class all of the readouts are written to
+    pub class_name: String,
+}
+
+/// Writes the readouts as IOF XML 3.0 `ResultList`
+///
+/// The result list can not hold everything of the readout, `read_result_list` gets back only
+/// the SIID, the name, the club, the start, the finish and the punches with the control code
+/// and the time relative to the start. Use `write_card_read_list` to store the readouts.
+///
+/// * `event` - Event
+/// * `readouts` - Readouts
+pub fn write_result_list(
+    event: &IofEvent,
+    readouts: &[GeneralReadout],
+) -> Result<String, IofError> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+    let create_time = create_time();
+    writer
+        .create_element("ResultList")
+        .with_attributes([
+            ("xmlns", IOF_NAMESPACE),
+            ("iofVersion", "3.0"),
+            ("createTime", create_time.as_str()),
+            ("creator", "sident"),
+            ("status", "Snapshot"),
+        ])
+        .write_inner_content(|writer| {
+            writer
+                .create_element("Event")
+                .write_inner_content(|writer| {
+                    write_text(writer, "Name", &event.name)?;
+                    writer
+                        .create_element("StartTime")
+                        .write_inner_content(|writer| {
+                            write_text(writer, "Date", &event.date.to_string())
+                        })?;
+                    return Ok(());
+                })?;
+
+            writer
+                .create_element("ClassResult")
+                .write_inner_content(|writer| {
+                    writer
+                        .create_element("Class")
+                        .write_inner_content(|writer| {
+                            write_text(writer, "Name", &event.class_name)
+                        })?;
+                    for readout in readouts {
+                        write_person_result(writer, readout, event.date)?;
+                    }
+                    return Ok(());
+                })?;
+            return Ok(());
+        })?;
+
+    // the writer writes only valid UTF-8
+    return Ok(String::from_utf8(writer.into_inner()).unwrap());
+}
+
+/// Writes the readouts as a list of `CardRead` elements (see the module docs).
+/// The list is not an IOF XML document, other IOF software will not read it.
+///
+/// Unlike the result list, the card read list keeps the clear/check, the station codes of the start
+/// and the finish and the punches of the cards without the start. Only the personal data other than
+/// the name and the club are lost.
+///
+/// * `event_date` - Date of the event (punches are placed in the calendar around it, see `GeneralReadout::resolve_times`)
+/// * `readouts` - Readouts
+pub fn write_card_read_list(
+    event_date: NaiveDate,
+    readouts: &[GeneralReadout],
+) -> Result<String, IofError> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+    let create_time = create_time();
+    writer
+        .create_element("CardReadList")
+        .with_attributes([
+            ("xmlns", CARD_READ_LIST_NAMESPACE),
+            ("createTime", create_time.as_str()),
+            ("creator", "sident"),
+        ])
+        .write_inner_content(|writer| {
+            for readout in readouts {
+                write_card_read(writer, readout, event_date)?;
+            }
+            return Ok(());
+        })?;
+
+    // the writer writes only valid UTF-8
+    return Ok(String::from_utf8(writer.into_inner()).unwrap());
+}
+
+fn create_time() -> String {
+    return Local::now()
+        .naive_local()
+        .format(DATETIME_FORMAT)
+        .to_string();
+}
+
+fn write_person_result(
+    writer: &mut Writer<Vec<u8>>,
+    readout: &GeneralReadout,
+    event_date: NaiveDate,
+) -> std::io::Result<()> {
+    let times = readout.resolve_times(event_date);
+    let personal_data = readout.personal_data.clone().unwrap_or_default();
+
+    writer
+        .create_element("PersonResult")
+        .write_inner_content(|writer| {
+            write_person(writer, &personal_data)?;
+
+            writer
+                .create_element("Result")
+                .write_inner_content(|writer| {
+                    let start = times.start.as_ref().map(|start| start.datetime);
+                    let finish = times.finish.as_ref().map(|finish| finish.datetime);
+
+                    if let Some(start) = start {
+                        write_text(
+                            writer,
+                            "StartTime",
+                            &start.format(DATETIME_FORMAT).to_string(),
+                        )?;
+                    }
+                    if let Some(finish) = finish {
+                        write_text(
+                            writer,
+                            "FinishTime",
+                            &finish.format(DATETIME_FORMAT).to_string(),
+                        )?;
+                    }
+                    if let (Some(start), Some(finish)) = (start, finish) {
+                        write_text(writer, "Time", &format_seconds(finish - start))?;
+                    }
+                    let status = match finish {
+                        Some(_) => "Finished",
+                        None => "DidNotFinish",
+                    };
+                    write_text(writer, "Status", status)?;
+
+                    for punch in &times.punches {
+                        writer
+                            .create_element("SplitTime")
+                            .write_inner_content(|writer| {
+                                write_text(writer, "ControlCode", &punch.station_code.to_string())?;
+                                if let Some(start) = start {
+                                    write_text(
+                                        writer,
+                                        "Time",
+                                        &format_seconds(punch.datetime - start),
+                                    )?;
+                                }
+                                return Ok(());
+                            })?;
+                    }
+                    // IOF XML 3.0 has the control card after the split times
+                    write_control_card(writer, readout.siid)?;
+                    return Ok(());
+                })?;
+            return Ok(());
+        })?;
+
+    return Ok(());
+}
+
+fn write_card_read(
+    writer: &mut Writer<Vec<u8>>,
+    readout: &GeneralReadout,
+    event_date: NaiveDate,
+) -> std::io::Result<()> {
+    let times = readout.resolve_times(event_date);
+
+    writer
+        .create_element("CardRead")
+        .write_inner_content(|writer| {
+            write_control_card(writer, readout.siid)?;
+            if let Some(personal_data) = &readout.personal_data {
+                write_person(writer, personal_data)?;
+            }
+
+            if let Some(clear_check) = &times.clear_check {
+                write_dated_punch(writer, "ClearCheck", clear_check)?;
+            }
+            if let Some(start) = &times.start {
+                write_dated_punch(writer, "Start", start)?;
+            }
+            if let Some(finish) = &times.finish {
+                write_dated_punch(writer, "Finish", finish)?;
+            }
+            for punch in &times.punches {
+                write_dated_punch(writer, "Punch", punch)?;
+            }
+            return Ok(());
+        })?;
+
+    return Ok(());
+}
+
+fn write_person(
+    writer: &mut Writer<Vec<u8>>,
+    personal_data: &CardPersonalData,
+) -> std::io::Result<()> {
+    writer
+        .create_element("Person")
+        .write_inner_content(|writer| {
+            writer
+                .create_element("Name")
+                .write_inner_content(|writer| {
+                    write_text(
+                        writer,
+                        "Family",
+                        personal_data.last_name.as_deref().unwrap_or_default(),
+                    )?;
+                    write_text(
+                        writer,
+                        "Given",
+                        personal_data.first_name.as_deref().unwrap_or_default(),
+                    )?;
+                    return Ok(());
+                })?;
+            return Ok(());
+        })?;
+
+    if let Some(club) = &personal_data.club {
+        writer
+            .create_element("Organisation")
+            .write_inner_content(|writer| write_text(writer, "Name", club))?;
+    }
+
+    return Ok(());
+}
+
+fn write_control_card(writer: &mut Writer<Vec<u8>>, siid: u32) -> std::io::Result<()> {
+    writer
+        .create_element("ControlCard")
+        .with_attribute(("punchingSystem", "SI"))
+        .write_text_content(BytesText::new(&siid.to_string()))?;
+    return Ok(());
+}
+
+fn write_dated_punch(
+    writer: &mut Writer<Vec<u8>>,
+    name: &str,
+    punch: &DatedPunch,
+) -> std::io::Result<()> {
+    writer.create_element(name).write_inner_content(|writer| {
+        write_text(writer, "ControlCode", &punch.station_code.to_string())?;
+        write_text(
+            writer,
+            "Time",
+            &punch.datetime.format(DATETIME_FORMAT).to_string(),
+        )?;
+        return Ok(());
+    })?;
+    return Ok(());
+}
+
+fn write_text(writer: &mut Writer<Vec<u8>>, name: &str, text: &str) -> std::io::Result<()> {
+    writer
+        .create_element(name)
+        .write_text_content(BytesText::new(text))?;
+    return Ok(());
+}
+
+/// Seconds with the millisecond precision (IOF `Time` is a double)
+fn format_seconds(duration: Duration) -> String {
+    return (duration.num_milliseconds() as f64 / 1000.0).to_string();
+}
+
+/// Element of the document holding one readout (`PersonResult`, `CardRead`)
+trait Record: Default {
+    const ELEMENT: &'static str;
+
+    /// Child element `name` of the record started
+    fn start(&mut self, name: &str);
+
+    /// Child element `name` of the record ended
+    ///
+    /// * `parent` - Name of the parent element
+    /// * `name` - Name of the element
+    /// * `text` - Trimmed text of the element
+    fn end(&mut self, parent: &str, name: &str, text: &str) -> Result<(), IofError>;
+
+    fn into_readout(self) -> Result<GeneralReadout, IofError>;
+}
+
+/// Split being read
+#[derive(Default)]
+struct SplitTime {
+    control_code: Option<u16>,
+    time: Option<Duration>,
+}
+
+/// Person result being read
+#[derive(Default)]
+struct PersonResult {
+    siid: u32,
+    personal_data: CardPersonalData,
+    start: Option<NaiveDateTime>,
+    finish: Option<NaiveDateTime>,
+    splits: Vec<SplitTime>,
+}
+
+impl Record for PersonResult {
+    const ELEMENT: &'static str = "PersonResult";
+
+    fn start(&mut self, name: &str) {
+        if name == "SplitTime" {
+            self.splits.push(SplitTime::default());
+        }
+    }
+
+    fn end(&mut self, parent: &str, name: &str, text: &str) -> Result<(), IofError> {
+        match (parent, name) {
+            ("Result", "ControlCard") => self.siid = parse("ControlCard", text)?,
+            ("Result", "StartTime") => self.start = Some(parse_datetime("StartTime", text)?),
+            ("Result", "FinishTime") => self.finish = Some(parse_datetime("FinishTime", text)?),
+            ("SplitTime", "ControlCode") => {
+                if let Some(split) = self.splits.last_mut() {
+                    split.control_code = Some(parse("ControlCode", text)?);
+                }
+            }
+            ("SplitTime", "Time") => {
+                if let Some(split) = self.splits.last_mut() {
+                    let seconds: f64 = parse("Time", text)?;
+                    split.time = Some(Duration::milliseconds((seconds * 1000.0).round() as i64));
+                }
+            }
+            _ => read_person_text(&mut self.personal_data, parent, name, text),
+        }
+
+        return Ok(());
+    }
+
+    fn into_readout(self) -> Result<GeneralReadout, IofError> {
+        let start = self.start.map(|start| dated_punch(0, start));
+        let punches = match self.start {
+            Some(start_time) => self
+                .splits
+                .into_iter()
+                .filter_map(|split| {
+                    Some(dated_punch(split.control_code?, start_time + split.time?))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let has_personal_data = self.personal_data != CardPersonalData::default();
+
+        return Ok(GeneralReadout {
+            siid: self.siid,
+            personal_data: has_personal_data.then_some(self.personal_data),
+            // the result list has no clear/check
+            clear_check: None,
+            start,
+            finish: self.finish.map(|finish| dated_punch(0, finish)),
+            punches,
+        });
+    }
+}
+
+/// Card read being read
+#[derive(Default)]
+struct CardRead {
+    siid: Option<u32>,
+    personal_data: CardPersonalData,
+    clear_check: Option<Punch>,
+    start: Option<Punch>,
+    finish: Option<Punch>,
+    punches: Vec<Punch>,
+    /// Control code and time of the punch element being read
+    control_code: Option<u16>,
+    time: Option<NaiveDateTime>,
+}
+
+impl CardRead {
+    /// Takes the control code and the time read since the last punch element
+    fn take_punch(&mut self) -> Result<Punch, IofError> {
+        let control_code = self
+            .control_code
+            .take()
+            .ok_or(IofError::MissingValue("ControlCode"))?;
+        let time = self.time.take().ok_or(IofError::MissingValue("Time"))?;
+        return Ok(dated_punch(control_code, time));
+    }
+}
+
+impl Record for CardRead {
+    const ELEMENT: &'static str = "CardRead";
+
+    fn start(&mut self, _name: &str) {}
+
+    fn end(&mut self, parent: &str, name: &str, text: &str) -> Result<(), IofError> {
+        match (parent, name) {
+            ("CardRead", "ControlCard") => self.siid = Some(parse("ControlCard", text)?),
+            ("CardRead", "ClearCheck") => self.clear_check = Some(self.take_punch()?),
+            ("CardRead", "Start") => self.start = Some(self.take_punch()?),
+            ("CardRead", "Finish") => self.finish = Some(self.take_punch()?),
+            ("CardRead", "Punch") => {
+                let punch = self.take_punch()?;
+                self.punches.push(punch);
+            }
+            (_, "ControlCode") => self.control_code = Some(parse("ControlCode", text)?),
+            (_, "Time") => self.time = Some(parse_datetime("Time", text)?),
+            _ => read_person_text(&mut self.personal_data, parent, name, text),
+        }
+
+        return Ok(());
+    }
+
+    fn into_readout(self) -> Result<GeneralReadout, IofError> {
+        let has_personal_data = self.personal_data != CardPersonalData::default();
+
+        return Ok(GeneralReadout {
+            siid: self.siid.ok_or(IofError::MissingValue("ControlCard"))?,
+            personal_data: has_personal_data.then_some(self.personal_data),
+            clear_check: self.clear_check,
+            start: self.start,
+            finish: self.finish,
+            punches: self.punches,
+        });
+    }
+}
+
+fn dated_punch(station_code: u16, datetime: NaiveDateTime) -> Punch {
+    return Punch {
+        station_code,
+        punch_time: PunchTime::from_datetime(datetime),
+    };
+}
+
+fn read_person_text(personal_data: &mut CardPersonalData, parent: &str, name: &str, text: &str) {
+    let value = || (!text.is_empty()).then(|| text.to_string());
+
+    match (parent, name) {
+        ("Name", "Family") => personal_data.last_name = value(),
+        ("Name", "Given") => personal_data.first_name = value(),
+        ("Organisation", "Name") => personal_data.club = value(),
+        _ => {}
+    }
+}
+
+/// Reads the readouts from IOF XML 3.0 `ResultList` (see `write_result_list`)
+///
+/// The result list holds only a part of the readout:
+/// * there is no clear/check
+/// * the start and the finish have the station code 0
+/// * punches are lost if the result has no start, the split times are relative to it
+/// * the personal data are only the name and the club
+///
+/// Use `read_card_read_list` to get the whole readouts back.
+///
+/// * `xml` - IOF XML
+pub fn read_result_list(xml: &str) -> Result<Vec<GeneralReadout>, IofError> {
+    return read_records::<PersonResult>(xml);
+}
+
+/// Reads the readouts from the list of `CardRead` elements (see `write_card_read_list`)
+///
+/// * `xml` - IOF XML
+pub fn read_card_read_list(xml: &str) -> Result<Vec<GeneralReadout>, IofError> {
+    return read_records::<CardRead>(xml);
+}
+
+fn read_records<R: Record>(xml: &str) -> Result<Vec<GeneralReadout>, IofError> {
+    // text is trimmed when the element ends (trimming the events would eat the spaces around the entities)
+    let mut reader = Reader::from_str(xml);
+
+    let mut readouts = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut record: Option<R> = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+                if name == R::ELEMENT {
+                    record = Some(R::default());
+                } else if let Some(record) = record.as_mut() {
+                    record.start(&name);
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Text(content) => text.push_str(&content.decode()?),
+            Event::CData(content) => text.push_str(&content.decode()?),
+            Event::GeneralRef(reference) => {
+                if let Some(ch) = reference.resolve_char_ref()? {
+                    text.push(ch);
+                } else if let Some(resolved) = resolve_predefined_entity(&reference.decode()?) {
+                    text.push_str(resolved);
+                }
+            }
+            Event::End(_) => {
+                let Some(name) = path.pop() else {
+                    continue;
+                };
+                if name == R::ELEMENT {
+                    if let Some(record) = record.take() {
+                        readouts.push(record.into_readout()?);
+                    }
+                } else if let Some(record) = record.as_mut() {
+                    let parent = path.last().map(String::as_str).unwrap_or_default();
+                    record.end(parent, &name, text.trim())?;
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    return Ok(readouts);
+}
+
+fn parse<T: std::str::FromStr>(name: &'static str, text: &str) -> Result<T, IofError> {
+    return text
+        .parse()
+        .map_err(|_| IofError::InvalidValue(name, text.to_string()));
+}
+
+fn parse_datetime(name: &'static str, text: &str) -> Result<NaiveDateTime, IofError> {
+    // the time zone is ignored, punches are in the local time of the station
+    let local = text
+        .find(['Z', '+'])
+        .or_else(|| text.rfind('-').filter(|i| *i > 10))
+        .map(|i| &text[..i])
+        .unwrap_or(text);
+
+    return NaiveDateTime::parse_from_str(local, DATETIME_FORMAT)
+        .map_err(|_| IofError::InvalidValue(name, text.to_string()));
+}
@@ -1,21 +1,32 @@
-pub mod card;
-pub mod carddef;
+pub mod backup;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod card_image;
 pub mod codec;
 pub mod connection;
-pub mod crc;
+pub mod csv;
+#[cfg(not(target_os = "android"))]
+pub mod discovery;
 pub mod errors;
+pub mod event;
 pub mod firmware;
+#[cfg(feature = "iof")]
+pub mod iof;
 pub mod macros;
-pub mod packet;
 pub mod packets;
+#[cfg(not(target_os = "android"))]
+pub mod pool;
 pub mod product;
-pub mod punch;
-pub mod time;
+pub mod results;
+#[cfg(feature = "sim")]
+pub mod sim;
 
 mod baudrate;
 pub use baudrate::*;
 mod ms_mode;
 pub use ms_mode::*;
+mod protocol;
+pub use protocol::*;
 mod protocol_config;
 pub use protocol_config::*;
 pub mod addr_len;
@@ -23,25 +34,15 @@ mod sys_config;
 
 pub use sys_config::*;
 
-use crate::{card::CardType, codec::SICodecTimeout};
+pub use sident_core::{
+    card, carddef, check_vec_len, crc, extract_fixed, framing, is_extended_packet_id, packet,
+    punch, time,
+};
 
-/// Returns if the input packet ID is a packet in extended protocol
-///
-/// # Example
-/// ```
-/// use sident::is_extended_packet_id;
-///
-/// assert!(is_extended_packet_id(0xFE));
-/// ```
-pub fn is_extended_packet_id(id: u8) -> bool {
-    if id < 0x80 || id == 0xC4 {
-        return false;
-    }
-    true
-}
+use crate::{card::CardType, codec::SICodecTimeout};
 
 /// Array of cards supported by this library
-pub const SUPPORTED_CARDS: [CardType; 7] = [
+pub const SUPPORTED_CARDS: [CardType; 12] = [
     CardType::ActiveCard,
     CardType::Card11,
     CardType::Card10,
@@ -49,6 +50,11 @@ pub const SUPPORTED_CARDS: [CardType; 7] = [
     CardType::Card8,
     CardType::ComCardPro,
     CardType::ComCardUp,
+    CardType::Card5,
+    CardType::Card6,
+    CardType::PCard,
+    CardType::TCard,
+    CardType::FCard,
 ];
 
 /// Returns default SICodec timeout
@@ -1,12 +1,3 @@
-#[macro_export]
-macro_rules! check_vec_len {
-    ($vec:expr, $expected_len:expr, $err_variant:expr) => {
-        if $vec.len() != $expected_len as usize {
-            return Err($err_variant);
-        }
-    };
-}
-
 #[macro_export]
 macro_rules! define_data_address_and_length {
     (
@@ -63,15 +54,6 @@ macro_rules! define_data_address_and_length {
     };
 }
 
-#[macro_export]
-macro_rules! extract_fixed {
-    ($data:expr, $range:expr) => {{
-        const LEN: usize = $range.end - $range.start + 1;
-        let slice = $data.get($range.start..=$range.end).unwrap();
-        <[u8; LEN]>::try_from(slice).unwrap()
-    }};
-}
-
 #[macro_export]
 macro_rules! dedup_enum_array {
     ($slice:expr) => {{
@@ -0,0 +1,36 @@
+use crate::{
+    errors::DeserializePacketError,
+    packet::{HostboundPacket, Packet},
+};
+
+/// Response to `GetBackupData`
+#[derive(Debug)]
+pub struct GetBackupDataResponse {
+    pub station_code: u16,
+    /// Address in the backup memory (3 bytes)
+    pub address: u32,
+    pub data: Vec<u8>,
+}
+
+impl Packet for GetBackupDataResponse {
+    const PACKET_ID: u8 = 0x81;
+}
+
+impl HostboundPacket for GetBackupDataResponse {
+    const EXPECTED_DATA_LEN: u8 = 0;
+    const EXPECTING_DATA_LEN: bool = false;
+
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        if data.len() < 5 {
+            return Err(DeserializePacketError::WrongDataLen);
+        }
+
+        return Ok(Self {
+            station_code: u16::from_be_bytes([data[0], data[1]]),
+            address: u32::from_be_bytes([0, data[2], data[3], data[4]]),
+            data: data[5..].to_vec(),
+        });
+    }
+}
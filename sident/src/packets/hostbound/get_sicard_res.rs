@@ -76,3 +76,55 @@ impl HostboundPacket for GetSICard5Response {
         Ok(Self { station_code, data })
     }
 }
+
+/// Response to `BaseGetSICard5` (base protocol)
+#[derive(Debug)]
+pub struct BaseGetSICard5Response {
+    pub station_code: u16,
+    pub data: [u8; 128],
+}
+
+impl Packet for BaseGetSICard5Response {
+    const PACKET_ID: u8 = 0x31;
+}
+
+impl HostboundPacket for BaseGetSICard5Response {
+    const EXPECTED_DATA_LEN: u8 = 129;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, crate::errors::DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN0, 128 bytes of the card (base protocol has only the lower byte of the station code)
+        return Ok(Self {
+            station_code: data[0] as u16,
+            data: data[1..].try_into().unwrap(), // should not fail because of the checks
+        });
+    }
+}
+
+/// Response to `BaseGetSICard6` (base protocol)
+#[derive(Debug)]
+pub struct BaseGetSICard6Response {
+    pub station_code: u16,
+    pub block_number: u8,
+    pub data: [u8; 128],
+}
+
+impl Packet for BaseGetSICard6Response {
+    const PACKET_ID: u8 = 0x61;
+}
+
+impl HostboundPacket for BaseGetSICard6Response {
+    const EXPECTED_DATA_LEN: u8 = 130;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, crate::errors::DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN0, BN, 128 bytes of the block
+        return Ok(Self {
+            station_code: data[0] as u16,
+            block_number: data[1],
+            data: data[2..].try_into().unwrap(), // should not fail because of the checks
+        });
+    }
+}
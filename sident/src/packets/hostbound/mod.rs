@@ -8,3 +8,15 @@ mod get_sicard_res;
 pub use get_sicard_res::*;
 mod si_card_removed;
 pub use si_card_removed::*;
+mod set_baudrate_res;
+pub use set_baudrate_res::*;
+mod write_sicard_res;
+pub use write_sicard_res::*;
+mod set_system_value_res;
+pub use set_system_value_res::*;
+mod time_res;
+pub use time_res::*;
+mod get_backup_data_res;
+pub use get_backup_data_res::*;
+mod transmit_record;
+pub use transmit_record::*;
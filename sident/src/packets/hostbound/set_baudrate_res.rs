@@ -0,0 +1,61 @@
+use crate::{
+    Baudrate,
+    errors::DeserializePacketError,
+    packet::{HostboundPacket, Packet},
+};
+
+/// Response to `SetBaudrate`
+#[derive(Debug)]
+pub struct SetBaudrateResponse {
+    pub station_code: u16,
+    pub baudrate: Baudrate,
+}
+
+impl Packet for SetBaudrateResponse {
+    const PACKET_ID: u8 = 0xFE;
+}
+
+impl HostboundPacket for SetBaudrateResponse {
+    const EXPECTED_DATA_LEN: u8 = 3;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        let station_code = u16::from_be_bytes([data[0], data[1]]);
+        let baudrate = Baudrate::try_from(data[2])
+            .map_err(|_| DeserializePacketError::Other("Baudrate is invalid".into()))?;
+
+        return Ok(Self {
+            station_code,
+            baudrate,
+        });
+    }
+}
+
+/// Response to `BaseSetBaudrate` (base protocol)
+#[derive(Debug)]
+pub struct BaseSetBaudrateResponse {
+    pub station_code: u16,
+    pub baudrate: Baudrate,
+}
+
+impl Packet for BaseSetBaudrateResponse {
+    const PACKET_ID: u8 = 0x7E;
+}
+
+impl HostboundPacket for BaseSetBaudrateResponse {
+    const EXPECTED_DATA_LEN: u8 = 2;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        let station_code = data[0] as u16;
+        let baudrate = Baudrate::try_from(data[1])
+            .map_err(|_| DeserializePacketError::Other("Baudrate is invalid".into()))?;
+
+        return Ok(Self {
+            station_code,
+            baudrate,
+        });
+    }
+}
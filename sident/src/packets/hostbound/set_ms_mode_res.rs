@@ -27,3 +27,34 @@ impl HostboundPacket for SetMsModeResponse {
         });
     }
 }
+
+/// Response to `BaseSetMsMode` (base protocol)
+#[derive(Debug)]
+pub struct BaseSetMsModeResponse {
+    pub station_code: u16,
+    pub mode: MsMode,
+}
+
+impl Packet for BaseSetMsModeResponse {
+    const PACKET_ID: u8 = 0x70;
+}
+
+impl HostboundPacket for BaseSetMsModeResponse {
+    const EXPECTED_DATA_LEN: u8 = 0;
+    const EXPECTING_DATA_LEN: bool = false;
+    fn deserialize(data: Vec<u8>) -> Result<Self, crate::errors::DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN0, MS (base protocol has only the lower byte of the station code)
+        let [cn, mode] = data[..] else {
+            return Err(crate::errors::DeserializePacketError::WrongDataLen);
+        };
+
+        return Ok(Self {
+            station_code: cn as u16,
+            mode: MsMode::from_u8(mode).ok_or(crate::errors::DeserializePacketError::Other(
+                "MsMode is invalid".into(),
+            ))?,
+        });
+    }
+}
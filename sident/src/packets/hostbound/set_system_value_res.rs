@@ -0,0 +1,28 @@
+use crate::{
+    errors::DeserializePacketError,
+    packet::{HostboundPacket, Packet},
+};
+
+/// Response to `SetSystemValue`
+#[derive(Debug)]
+pub struct SetSystemValueResponse {
+    pub station_code: u16,
+    pub address: u8,
+}
+
+impl Packet for SetSystemValueResponse {
+    const PACKET_ID: u8 = 0x82;
+}
+
+impl HostboundPacket for SetSystemValueResponse {
+    const EXPECTED_DATA_LEN: u8 = 3;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        return Ok(Self {
+            station_code: u16::from_be_bytes([data[0], data[1]]),
+            address: data[2],
+        });
+    }
+}
@@ -1,4 +1,21 @@
-use crate::packet::{HostboundPacket, Packet};
+use crate::{
+    carddef::si5::Card5Def,
+    packet::{HostboundPacket, Packet},
+};
+
+/// Decodes the SIID from the SI2, SI1 and SI0 bytes of the card detected/removed packets and transmit records.
+///
+/// Card 5 numbers (below 500000) are sent as the card series (SI2) and the number in the series (SI1, SI0),
+/// see `Card5Def::decode_siid`.
+///
+/// * `si` - SI2, SI1 and SI0 bytes
+pub(crate) fn decode_siid(si: [u8; 3]) -> u32 {
+    let siid = u32::from_be_bytes([0, si[0], si[1], si[2]]);
+    if siid < 500_000 {
+        return Card5Def::decode_siid(si[0], u16::from_be_bytes([si[1], si[2]]));
+    }
+    return siid;
+}
 
 #[derive(Debug)]
 pub struct SICardNewerDetected {
@@ -66,8 +83,59 @@ impl HostboundPacket for SICard5Detected {
         Self::deserialize_checks(&data)?;
 
         let station_code = u16::from_be_bytes([data[0], data[1]]);
-        let siid = u32::from_be_bytes([0, data[3], data[4], data[5]]);
+        // SI2 is the card series, SI1 and SI0 are the number in the series
+        let siid = Card5Def::decode_siid(data[3], u16::from_be_bytes([data[4], data[5]]));
 
         return Ok(Self { station_code, siid });
     }
 }
+
+/// Card 5 was inserted (base protocol)
+#[derive(Debug)]
+pub struct BaseSICard5Detected {
+    pub station_code: u16,
+    pub siid: u32,
+}
+
+impl Packet for BaseSICard5Detected {
+    const PACKET_ID: u8 = 0x46;
+}
+
+impl HostboundPacket for BaseSICard5Detected {
+    const EXPECTED_DATA_LEN: u8 = 5;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, crate::errors::DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN0, SI3, SI2, SI1, SI0 (base protocol has only the lower byte of the station code)
+        return Ok(Self {
+            station_code: data[0] as u16,
+            siid: Card5Def::decode_siid(data[2], u16::from_be_bytes([data[3], data[4]])),
+        });
+    }
+}
+
+/// Card 6 was inserted (base protocol)
+#[derive(Debug)]
+pub struct BaseSICard6Detected {
+    pub station_code: u16,
+    pub siid: u32,
+}
+
+impl Packet for BaseSICard6Detected {
+    const PACKET_ID: u8 = 0x66;
+}
+
+impl HostboundPacket for BaseSICard6Detected {
+    const EXPECTED_DATA_LEN: u8 = 5;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, crate::errors::DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN0, SI3, SI2, SI1, SI0
+        return Ok(Self {
+            station_code: data[0] as u16,
+            siid: u32::from_be_bytes([0, data[2], data[3], data[4]]),
+        });
+    }
+}
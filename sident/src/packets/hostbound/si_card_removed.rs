@@ -1,4 +1,9 @@
-use crate::packet::{HostboundPacket, Packet};
+use crate::{
+    extract_fixed,
+    packet::{HostboundPacket, Packet},
+};
+
+use super::si_card_detected::decode_siid;
 
 #[derive(Debug)]
 pub struct SICardRemoved {
@@ -17,7 +22,34 @@ impl HostboundPacket for SICardRemoved {
         Self::deserialize_checks(&data)?;
 
         let station_code = u16::from_be_bytes([data[0], data[1]]);
-        let siid = u32::from_be_bytes([0, data[3], data[4], data[5]]);
+        // the card type is not known, Card 5 numbers are sent as the series and the number
+        let siid = decode_siid(extract_fixed!(&data, 0x03..0x05));
+
+        return Ok(Self { station_code, siid });
+    }
+}
+
+/// Card was removed from the station (base protocol)
+#[derive(Debug)]
+pub struct BaseSICardRemoved {
+    pub station_code: u16,
+    pub siid: u32,
+}
+
+impl Packet for BaseSICardRemoved {
+    const PACKET_ID: u8 = 0x4F;
+}
+
+impl HostboundPacket for BaseSICardRemoved {
+    const EXPECTED_DATA_LEN: u8 = 5;
+    const EXPECTING_DATA_LEN: bool = true;
+
+    fn deserialize(data: Vec<u8>) -> Result<Self, crate::errors::DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN0, SI3, SI2, SI1, SI0 (base protocol has only the lower byte of the station code)
+        let station_code = data[0] as u16;
+        let siid = decode_siid(extract_fixed!(&data, 0x02..0x04));
 
         return Ok(Self { station_code, siid });
     }
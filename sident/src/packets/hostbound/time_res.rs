@@ -0,0 +1,55 @@
+use crate::{
+    errors::DeserializePacketError,
+    extract_fixed,
+    packet::{HostboundPacket, Packet},
+};
+
+/// Response to `GetTime`
+#[derive(Debug)]
+pub struct GetTimeResponse {
+    pub station_code: u16,
+    /// YY, MM, DD, TD, TH, TL, TSS
+    pub time: [u8; 7],
+}
+
+impl Packet for GetTimeResponse {
+    const PACKET_ID: u8 = 0xF7;
+}
+
+impl HostboundPacket for GetTimeResponse {
+    const EXPECTED_DATA_LEN: u8 = 9;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        return Ok(Self {
+            station_code: u16::from_be_bytes([data[0], data[1]]),
+            time: extract_fixed!(&data, 0x02..0x08),
+        });
+    }
+}
+
+/// Response to `SetTime`
+#[derive(Debug)]
+pub struct SetTimeResponse {
+    pub station_code: u16,
+    /// YY, MM, DD, TD, TH, TL, TSS
+    pub time: [u8; 7],
+}
+
+impl Packet for SetTimeResponse {
+    const PACKET_ID: u8 = 0xF6;
+}
+
+impl HostboundPacket for SetTimeResponse {
+    const EXPECTED_DATA_LEN: u8 = 9;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        return Ok(Self {
+            station_code: u16::from_be_bytes([data[0], data[1]]),
+            time: extract_fixed!(&data, 0x02..0x08),
+        });
+    }
+}
@@ -0,0 +1,122 @@
+use super::si_card_detected::decode_siid;
+use crate::{
+    errors::DeserializePacketError,
+    extract_fixed,
+    packet::{HostboundPacket, Packet},
+    punch::{Punch, PunchTime},
+    time::TdByte,
+};
+
+/// Decodes the punch from the transmit record bytes
+fn deserialize_punch(
+    station_code: u16,
+    td: u8,
+    th_tl: [u8; 2],
+    subsecond: Option<u8>,
+) -> Result<Punch, DeserializePacketError> {
+    let td = TdByte::deserialize(td)?;
+
+    return Ok(Punch {
+        station_code,
+        punch_time: PunchTime {
+            time: td.time(th_tl)?,
+            day: td.day,
+            week: td.week,
+            twelve_hour_only: false,
+            subsecond,
+        },
+    });
+}
+
+/// Punch sent by the station in the autosend mode (`ProtocolConfig::AUTO_SEND_OUT`)
+#[derive(Debug, Clone, Copy)]
+pub struct TransmitRecord {
+    pub station_code: u16,
+    pub siid: u32,
+    /// Punch, including the sub second (TSS, 1/256 s) in `punch.punch_time.subsecond`
+    pub punch: Punch,
+    /// Address of the record in the backup memory
+    pub memory_address: u32,
+}
+
+impl Packet for TransmitRecord {
+    const PACKET_ID: u8 = 0xD3;
+}
+
+impl HostboundPacket for TransmitRecord {
+    const EXPECTED_DATA_LEN: u8 = 13;
+    const EXPECTING_DATA_LEN: bool = true;
+
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN1, CN0, SI3, SI2, SI1, SI0, TD, TH, TL, TSS, MEM2, MEM1, MEM0
+        let station_code = u16::from_be_bytes([data[0], data[1]]);
+
+        return Ok(Self {
+            station_code,
+            // SI3 is ignored, same as in the card detected packets
+            siid: decode_siid(extract_fixed!(&data, 0x03..0x05)),
+            punch: deserialize_punch(
+                station_code,
+                data[6],
+                extract_fixed!(&data, 0x07..0x08),
+                Some(data[9]),
+            )?,
+            memory_address: u32::from_be_bytes([0, data[10], data[11], data[12]]),
+        });
+    }
+}
+
+/// Punch sent by the station in the autosend mode (base protocol)
+#[derive(Debug, Clone, Copy)]
+pub struct BaseTransmitRecord {
+    pub station_code: u16,
+    pub siid: u32,
+    /// Punch without the sub second (`punch.punch_time.subsecond` is always `None`)
+    pub punch: Punch,
+    /// Address of the record in the backup memory
+    pub memory_address: u32,
+}
+
+impl Packet for BaseTransmitRecord {
+    const PACKET_ID: u8 = 0x53;
+}
+
+impl HostboundPacket for BaseTransmitRecord {
+    const EXPECTED_DATA_LEN: u8 = 11;
+    const EXPECTING_DATA_LEN: bool = true;
+
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        // CN0, SI3, SI2, SI1, SI0, TD, TH, TL, MEM2, MEM1, MEM0 (base protocol has only the lower byte of the station code)
+        let station_code = data[0] as u16;
+
+        // the record has no TSS byte - TL (data[7]) is followed directly by the memory address,
+        // so the sub second is unknown rather than 0
+
+        return Ok(Self {
+            station_code,
+            siid: decode_siid(extract_fixed!(&data, 0x02..0x04)),
+            punch: deserialize_punch(
+                station_code,
+                data[5],
+                extract_fixed!(&data, 0x06..0x07),
+                None,
+            )?,
+            memory_address: u32::from_be_bytes([0, data[8], data[9], data[10]]),
+        });
+    }
+}
+
+impl From<BaseTransmitRecord> for TransmitRecord {
+    fn from(value: BaseTransmitRecord) -> Self {
+        return Self {
+            station_code: value.station_code,
+            siid: value.siid,
+            punch: value.punch,
+            memory_address: value.memory_address,
+        };
+    }
+}
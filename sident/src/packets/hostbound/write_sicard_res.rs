@@ -0,0 +1,52 @@
+use crate::{
+    errors::DeserializePacketError,
+    packet::{HostboundPacket, Packet},
+};
+
+/// Response to `WriteSICardNewer`
+#[derive(Debug)]
+pub struct WriteSICardNewerResponse {
+    pub station_code: u16,
+    pub block_number: u8,
+}
+
+impl Packet for WriteSICardNewerResponse {
+    const PACKET_ID: u8 = 0xEA;
+}
+
+impl HostboundPacket for WriteSICardNewerResponse {
+    const EXPECTED_DATA_LEN: u8 = 3;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        return Ok(Self {
+            station_code: u16::from_be_bytes([data[0], data[1]]),
+            block_number: data[2],
+        });
+    }
+}
+
+/// Response to `WriteSICard6`
+#[derive(Debug)]
+pub struct WriteSICard6Response {
+    pub station_code: u16,
+    pub block_number: u8,
+}
+
+impl Packet for WriteSICard6Response {
+    const PACKET_ID: u8 = 0xE2;
+}
+
+impl HostboundPacket for WriteSICard6Response {
+    const EXPECTED_DATA_LEN: u8 = 3;
+    const EXPECTING_DATA_LEN: bool = true;
+    fn deserialize(data: Vec<u8>) -> Result<Self, DeserializePacketError> {
+        Self::deserialize_checks(&data)?;
+
+        return Ok(Self {
+            station_code: u16::from_be_bytes([data[0], data[1]]),
+            block_number: data[2],
+        });
+    }
+}
@@ -0,0 +1,22 @@
+use crate::packet::{Packet, StationboundPacket};
+
+/// Reads the backup memory of the station
+#[derive(Debug)]
+pub struct GetBackupData {
+    /// Address in the backup memory (3 bytes)
+    pub address: u32,
+    /// Number of bytes to read (max 128)
+    pub len: u8,
+}
+
+impl Packet for GetBackupData {
+    const PACKET_ID: u8 = 0x81;
+}
+
+impl StationboundPacket for GetBackupData {
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = self.address.to_be_bytes()[1..].to_vec();
+        payload.push(self.len);
+        payload
+    }
+}
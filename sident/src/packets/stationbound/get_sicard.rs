@@ -42,3 +42,33 @@ impl StationboundPacket for GetSICard5 {
         vec![]
     }
 }
+
+/// Read out SI-Card 5 in the base protocol
+#[derive(Debug)]
+pub struct BaseGetSICard5;
+
+impl Packet for BaseGetSICard5 {
+    const PACKET_ID: u8 = 0x31;
+}
+
+impl StationboundPacket for BaseGetSICard5 {
+    fn payload(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+/// Read out a block of SI-Card 6 in the base protocol
+#[derive(Debug)]
+pub struct BaseGetSICard6 {
+    pub block_number: u8,
+}
+
+impl Packet for BaseGetSICard6 {
+    const PACKET_ID: u8 = 0x61;
+}
+
+impl StationboundPacket for BaseGetSICard6 {
+    fn payload(&self) -> Vec<u8> {
+        vec![self.block_number]
+    }
+}
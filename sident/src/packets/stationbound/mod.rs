@@ -12,3 +12,15 @@ pub use set_baudrate::*;
 
 mod get_sicard;
 pub use get_sicard::*;
+
+mod write_sicard;
+pub use write_sicard::*;
+
+mod set_system_value;
+pub use set_system_value::*;
+
+mod time;
+pub use time::*;
+
+mod get_backup_data;
+pub use get_backup_data::*;
@@ -0,0 +1,20 @@
+use crate::packet::{Packet, StationboundPacket};
+
+/// Writes system values (the station config) starting at the address
+#[derive(Debug)]
+pub struct SetSystemValue {
+    pub address: u8,
+    pub data: Vec<u8>,
+}
+
+impl Packet for SetSystemValue {
+    const PACKET_ID: u8 = 0x82;
+}
+
+impl StationboundPacket for SetSystemValue {
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = vec![self.address];
+        payload.extend_from_slice(&self.data);
+        payload
+    }
+}
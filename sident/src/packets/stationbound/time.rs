@@ -0,0 +1,32 @@
+use crate::packet::{Packet, StationboundPacket};
+
+/// Reads the station clock
+#[derive(Debug)]
+pub struct GetTime;
+
+impl Packet for GetTime {
+    const PACKET_ID: u8 = 0xF7;
+}
+
+impl StationboundPacket for GetTime {
+    fn payload(&self) -> Vec<u8> {
+        vec![]
+    }
+}
+
+/// Sets the station clock
+#[derive(Debug)]
+pub struct SetTime {
+    /// YY, MM, DD, TD, TH, TL, TSS
+    pub time: [u8; 7],
+}
+
+impl Packet for SetTime {
+    const PACKET_ID: u8 = 0xF6;
+}
+
+impl StationboundPacket for SetTime {
+    fn payload(&self) -> Vec<u8> {
+        self.time.to_vec()
+    }
+}
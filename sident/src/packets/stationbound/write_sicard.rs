@@ -0,0 +1,39 @@
+use crate::packet::{Packet, StationboundPacket};
+
+/// Writes a block of SI8 and newer cards
+#[derive(Debug)]
+pub struct WriteSICardNewer {
+    pub block_number: u8,
+    pub data: [u8; 128],
+}
+
+impl Packet for WriteSICardNewer {
+    const PACKET_ID: u8 = 0xEA;
+}
+
+impl StationboundPacket for WriteSICardNewer {
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = vec![self.block_number];
+        payload.extend_from_slice(&self.data);
+        payload
+    }
+}
+
+/// Writes a block of SI6 card
+#[derive(Debug)]
+pub struct WriteSICard6 {
+    pub block_number: u8,
+    pub data: [u8; 128],
+}
+
+impl Packet for WriteSICard6 {
+    const PACKET_ID: u8 = 0xE2;
+}
+
+impl StationboundPacket for WriteSICard6 {
+    fn payload(&self) -> Vec<u8> {
+        let mut payload = vec![self.block_number];
+        payload.extend_from_slice(&self.data);
+        payload
+    }
+}
@@ -0,0 +1,341 @@
+/*
+    Station pool - several stations read out at once
+
+    Every station is driven by its own tokio task:
+
+      connect ──ok──> Connected ──> Event ... Event(Disconnected) ──┐
+         │                                                           │
+         └──err──> ConnectFailed (first failure of the outage only)  │
+         ^                                                           │
+         └──────────────── wait reconnect_interval <─────────────────┘
+
+    The task runs until the station is removed from the pool, the pool is dropped
+    or the event stream (`PoolEventStream`) is dropped.
+*/
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::Stream;
+use log::{info, warn};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc,
+    task::JoinHandle,
+};
+
+use crate::{
+    connection::{Connection, ConnectionStream},
+    discovery::list_candidate_ports,
+    errors::{DiscoveryError, NewConnectionError},
+    event::StationEvent,
+};
+
+/// Configuration of `StationPool`
+#[derive(Debug, Clone, Copy)]
+pub struct StationPoolConfig {
+    /// Delay before the next connection attempt (after a failed connect or a disconnect)
+    pub reconnect_interval: Duration,
+}
+
+impl Default for StationPoolConfig {
+    fn default() -> Self {
+        return Self {
+            reconnect_interval: Duration::from_secs(2),
+        };
+    }
+}
+
+/// Event of the `StationPool`
+#[derive(Debug)]
+pub enum PoolEvent {
+    /// Station was connected (also after a reconnect)
+    Connected { port: String, serial: u32 },
+    /// Connecting to the station failed, the pool tries again every `StationPoolConfig::reconnect_interval`.
+    /// Reported once per outage - the following failed attempts are silent until the station is connected again.
+    ConnectFailed {
+        port: String,
+        error: NewConnectionError,
+    },
+    /// Event of the station. After `StationEvent::Disconnected` the pool tries to reconnect.
+    Station {
+        port: String,
+        serial: u32,
+        event: StationEvent,
+    },
+}
+
+/// Stream of `PoolEvent`s of all stations in the pool (see `StationPool::new`).
+///
+/// Dropping the stream stops the pool - the stations are closed and no longer reconnected.
+pub struct PoolEventStream {
+    inner: mpsc::UnboundedReceiver<PoolEvent>,
+}
+
+impl Stream for PoolEventStream {
+    type Item = PoolEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        return self.inner.poll_recv(cx);
+    }
+}
+
+/// Manages several stations at once and merges their events into one stream.
+///
+/// The stations are identified by the port name. Every event of the station is tagged with
+/// the serial number of the station (from `SystemConfig`, 0 for the stations in the base protocol).
+/// When the port disappears, the pool reconnects to it as soon as it is back.
+///
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use futures::StreamExt;
+/// use sident::{
+///     connection::Connection,
+///     event::StationEvent,
+///     pool::{PoolEvent, StationPool, StationPoolConfig},
+///     sim::{VirtualCard, VirtualStation},
+/// };
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (mut pool, mut events) = StationPool::new(StationPoolConfig::default());
+///
+/// let (station, stream) = VirtualStation::new();
+/// let handle = station.handle();
+/// tokio::spawn(station.run());
+///
+/// // Real stations are added with `pool.add_station("COM4")`
+/// let mut stream = Some(stream);
+/// pool.add_station_with("virtual", move || {
+///     let stream = stream.take();
+///     async move { Connection::from_stream(stream.expect("connected only once")).await }
+/// });
+///
+/// let PoolEvent::Connected { port, .. } = events.next().await.unwrap() else {
+///     panic!("station not connected");
+/// };
+/// assert_eq!(port, "virtual");
+///
+/// handle.insert_card(VirtualCard::new(2_000_123, BTreeMap::new()));
+/// match events.next().await.unwrap() {
+///     PoolEvent::Station {
+///         event: StationEvent::CardInserted { siid, .. },
+///         ..
+///     } => assert_eq!(siid, 2_000_123),
+///     _ => unreachable!(),
+/// }
+/// # }
+/// ```
+pub struct StationPool {
+    config: StationPoolConfig,
+    events: mpsc::UnboundedSender<PoolEvent>,
+    /// Port name -> task driving the station
+    stations: BTreeMap<String, JoinHandle<()>>,
+    /// Ports added by `refresh` (the only ones `refresh` removes)
+    discovered: BTreeSet<String>,
+}
+
+impl StationPool {
+    /// Creates a new empty pool.
+    ///
+    /// Returns the pool and the stream of the events of all stations.
+    ///
+    /// * `config` - Configuration of the pool
+    pub fn new(config: StationPoolConfig) -> (Self, PoolEventStream) {
+        let (events, events_rx) = mpsc::unbounded_channel();
+
+        let pool = Self {
+            config,
+            events,
+            stations: BTreeMap::new(),
+            discovered: BTreeSet::new(),
+        };
+
+        return (pool, PoolEventStream { inner: events_rx });
+    }
+
+    /// Adds the station on the serial port. Returns false if the port is already in the pool.
+    ///
+    /// * `port` - Name of the serial port
+    pub fn add_station(&mut self, port: &str) -> bool {
+        let port_name = port.to_string();
+        return self.add_station_with(port, move || {
+            let port_name = port_name.clone();
+            async move { Connection::<ConnectionStream>::new(&port_name).await }
+        });
+    }
+
+    /// Adds the station connected by `connect`. Returns false if the port is already in the pool.
+    ///
+    /// `connect` is called for the first connection and for every reconnect.
+    ///
+    /// * `port` - Name of the station in the pool
+    /// * `connect` - Opens the connection to the station
+    pub fn add_station_with<S, F, Fut>(&mut self, port: &str, connect: F) -> bool
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Connection<S>, NewConnectionError>> + Send + 'static,
+    {
+        self.stations.retain(|_, task| !task.is_finished());
+        if self.stations.contains_key(port) {
+            return false;
+        }
+
+        let task = tokio::spawn(drive_station(
+            port.to_string(),
+            connect,
+            self.config.reconnect_interval,
+            self.events.clone(),
+        ));
+        self.stations.insert(port.to_string(), task);
+        return true;
+    }
+
+    /// Removes the station from the pool and closes its connection. Returns false if the port is not in the pool.
+    ///
+    /// * `port` - Name of the station in the pool
+    pub fn remove_station(&mut self, port: &str) -> bool {
+        self.discovered.remove(port);
+        let Some(task) = self.stations.remove(port) else {
+            return false;
+        };
+
+        info!("removing {} from the pool", port);
+        task.abort();
+        return true;
+    }
+
+    /// Returns the names of the stations in the pool
+    pub fn ports(&self) -> Vec<String> {
+        return self.stations.keys().cloned().collect();
+    }
+
+    /// Synchronizes the pool with the connected USB devices (hot-plug).
+    ///
+    /// New SPORTident ports (see `list_candidate_ports`) are added and the ports added by `refresh`
+    /// which no longer exist are removed. Stations added with `add_station` or `add_station_with` are left alone.
+    pub fn refresh(&mut self) -> Result<(), DiscoveryError> {
+        let candidates = list_candidate_ports()?;
+
+        let gone: Vec<String> = self
+            .discovered
+            .iter()
+            .filter(|port| !candidates.contains(port))
+            .cloned()
+            .collect();
+        for port in gone {
+            self.remove_station(&port);
+        }
+        for port in candidates {
+            if self.add_station(&port) {
+                info!("added {} to the pool", port);
+                self.discovered.insert(port);
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+impl Drop for StationPool {
+    fn drop(&mut self) {
+        for task in self.stations.values() {
+            task.abort();
+        }
+    }
+}
+
+/// Connects to the station and forwards its events until the receiver is dropped
+async fn drive_station<S, F, Fut>(
+    port: String,
+    connect: F,
+    reconnect_interval: Duration,
+    events: mpsc::UnboundedSender<PoolEvent>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Connection<S>, NewConnectionError>>,
+{
+    // the station can be idle (or missing) for a long time, so do not wait for its next event
+    tokio::select! {
+        _ = forward_events(&port, connect, reconnect_interval, &events) => {}
+        _ = events.closed() => info!("stopped driving {}, the receiver was dropped", port),
+    }
+}
+
+/// Connects (and reconnects) to the station and forwards its events. Returns when sending fails.
+async fn forward_events<S, F, Fut>(
+    port: &str,
+    mut connect: F,
+    reconnect_interval: Duration,
+    events: &mpsc::UnboundedSender<PoolEvent>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Connection<S>, NewConnectionError>>,
+{
+    // the failure is reported only once, the station can be missing for hours
+    let mut failure_reported = false;
+
+    loop {
+        let mut conn = match connect().await {
+            Ok(conn) => conn,
+            Err(error) => {
+                if !failure_reported {
+                    warn!("could not connect to {}: {}", port, error);
+                    let event = PoolEvent::ConnectFailed {
+                        port: port.to_string(),
+                        error,
+                    };
+                    if events.send(event).is_err() {
+                        return;
+                    }
+                    failure_reported = true;
+                }
+                tokio::time::sleep(reconnect_interval).await;
+                continue;
+            }
+        };
+        failure_reported = false;
+
+        let serial = conn
+            .get_system_config()
+            .map(|config| config.serial)
+            .unwrap_or_default();
+        info!("station {} connected on {}", serial, port);
+        let connected = PoolEvent::Connected {
+            port: port.to_string(),
+            serial,
+        };
+        if events.send(connected).is_err() {
+            return;
+        }
+
+        loop {
+            let event = conn.next_event().await;
+            let disconnected = matches!(event, StationEvent::Disconnected(_));
+            let event = PoolEvent::Station {
+                port: port.to_string(),
+                serial,
+                event,
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+            if disconnected {
+                break;
+            }
+        }
+
+        drop(conn);
+        tokio::time::sleep(reconnect_interval).await;
+    }
+}
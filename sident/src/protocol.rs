@@ -0,0 +1,11 @@
+/// Protocol used for the communication with the station
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// Extended protocol - length byte and CRC
+    Extended,
+    /// Base (legacy) protocol - DLE stuffing, no CRC. Deprecated by SPORTident.
+    Base,
+}
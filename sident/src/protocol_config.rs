@@ -1,7 +1,9 @@
 use bitflags::bitflags;
 
 bitflags! {
-    #[derive(Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct ProtocolConfig: u8 {
         const EXTENDED_PROTOCOL =       0b0000_0001;
         const AUTO_SEND_OUT =           0b0000_0010;
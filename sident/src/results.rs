@@ -0,0 +1,271 @@
+use chrono::Duration;
+
+use crate::{connection::GeneralReadout, punch::Punch};
+
+/// Course - codes of the controls in the order they are printed on the map (without start and finish)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Course {
+    pub controls: Vec<u16>,
+}
+
+impl Course {
+    pub fn new(controls: Vec<u16>) -> Self {
+        return Self { controls };
+    }
+}
+
+/// How the controls of the `Course` have to be visited
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlOrder {
+    /// In the order of the course
+    #[default]
+    Ordered,
+    /// In any order (score-O)
+    ScoreO,
+    /// The course is split into loops by the center control. The loops (parts between two visits
+    /// of the center) can be run in any order, the controls of the loop in the order of the course.
+    /// The part before the first and after the last visit of the center is fixed.
+    ///
+    /// The order of the loops is taken from the punches of the center (each loop is matched once, greedily),
+    /// so after a missing punch of the center the controls of a loop can count as in the wrong order.
+    Butterfly { center: u16 },
+}
+
+/// One leg of the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Split {
+    /// Code of the control
+    pub control: u16,
+    /// Punch of the control. `None` if the control is missing.
+    pub punch: Option<Punch>,
+    /// Time from the previous control (or start). `None` if one of the punches is missing.
+    pub leg: Option<Duration>,
+    /// Time from the start. `None` if the punch or the start is missing.
+    pub elapsed: Option<Duration>,
+}
+
+/// Result of `evaluate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    /// Splits in the order the controls were run
+    /// (`ControlOrder::Ordered` - course order, `ControlOrder::ScoreO` - punch order,
+    /// `ControlOrder::Butterfly` - course order with the loops in the order they were run)
+    pub splits: Vec<Split>,
+    /// Controls of the course which were not punched at all
+    pub missing: Vec<u16>,
+    /// Controls of the course which were punched, but not in the right order
+    pub wrong_order: Vec<u16>,
+    /// Punches of controls which are not in the course
+    pub extra: Vec<Punch>,
+    /// Time from the start to the finish. `None` if the start or the finish is missing.
+    pub total_time: Option<Duration>,
+}
+
+impl Evaluation {
+    /// Returns true if every control of the course was punched in the right order
+    pub fn is_complete(&self) -> bool {
+        return self.missing.is_empty() && self.wrong_order.is_empty();
+    }
+}
+
+/// Evaluates the run of the readout against the course
+///
+/// * `readout` - Readout of the card
+/// * `course` - Course
+/// * `order` - How the controls have to be visited
+///
+/// # Example
+/// ```
+/// use sident::results::{ControlOrder, Course, evaluate};
+/// # use sident::{connection::GeneralReadout, punch::Punch};
+/// # let punch = |station_code: u16, hour: u32| {
+/// #     let mut punch = Punch::default();
+/// #     punch.station_code = station_code;
+/// #     punch.punch_time.time = chrono::NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+/// #     punch
+/// # };
+/// # let readout = GeneralReadout {
+/// #     siid: 1,
+/// #     personal_data: None,
+/// #     clear_check: Some(punch(1, 8)),
+/// #     start: Some(punch(2, 9)),
+/// #     finish: Some(punch(3, 13)),
+/// #     punches: vec![punch(31, 10), punch(99, 11), punch(33, 12)],
+/// # };
+///
+/// let evaluation = evaluate(&readout, &Course::new(vec![31, 32, 33]), ControlOrder::Ordered);
+///
+/// assert_eq!(evaluation.missing, vec![32]);
+/// assert_eq!(evaluation.extra.len(), 1);
+/// assert_eq!(evaluation.total_time, Some(chrono::Duration::hours(4)));
+/// ```
+pub fn evaluate(readout: &GeneralReadout, course: &Course, order: ControlOrder) -> Evaluation {
+    let punched: Vec<u16> = readout.punches.iter().map(|p| p.station_code).collect();
+
+    let (controls, matched) = match order {
+        ControlOrder::Ordered => {
+            let matched = match_ordered(&course.controls, &punched);
+            (course.controls.clone(), matched)
+        }
+        ControlOrder::ScoreO => match_score(&course.controls, &punched),
+        ControlOrder::Butterfly { center } if course.controls.contains(&center) => {
+            let controls = order_loops(&course.controls, center, &punched);
+            let matched = match_ordered(&controls, &punched);
+            (controls, matched)
+        }
+        // without the center it is just an ordered course
+        ControlOrder::Butterfly { .. } => {
+            let matched = match_ordered(&course.controls, &punched);
+            (course.controls.clone(), matched)
+        }
+    };
+
+    let mut used = vec![false; punched.len()];
+    let mut splits = Vec::with_capacity(controls.len());
+    let mut previous = readout.start;
+    for (control, punch_index) in controls.iter().zip(&matched) {
+        let punch = punch_index.map(|i| {
+            used[i] = true;
+            readout.punches[i]
+        });
+        splits.push(Split {
+            control: *control,
+            punch,
+            leg: duration_between(previous.as_ref(), punch.as_ref()),
+            elapsed: duration_between(readout.start.as_ref(), punch.as_ref()),
+        });
+        previous = punch;
+    }
+
+    let mut missing = Vec::new();
+    let mut wrong_order = Vec::new();
+    for split in splits.iter().filter(|split| split.punch.is_none()) {
+        let punched_elsewhere = punched
+            .iter()
+            .zip(&used)
+            .any(|(code, used)| !used && *code == split.control);
+        if punched_elsewhere {
+            wrong_order.push(split.control);
+        } else {
+            missing.push(split.control);
+        }
+    }
+
+    let extra = readout
+        .punches
+        .iter()
+        .filter(|punch| !course.controls.contains(&punch.station_code))
+        .copied()
+        .collect();
+
+    return Evaluation {
+        splits,
+        missing,
+        wrong_order,
+        extra,
+        total_time: duration_between(readout.start.as_ref(), readout.finish.as_ref()),
+    };
+}
+
+fn duration_between(from: Option<&Punch>, to: Option<&Punch>) -> Option<Duration> {
+    return Some(to?.punch_time.duration_since(&from?.punch_time));
+}
+
+/// Matches the controls to the punches in order (longest common subsequence).
+/// Returns the index of the punch for every control.
+fn match_ordered(controls: &[u16], punched: &[u16]) -> Vec<Option<usize>> {
+    // lcs[i][j] = length of the LCS of controls[i..] and punched[j..]
+    let mut lcs = vec![vec![0usize; punched.len() + 1]; controls.len() + 1];
+    for i in (0..controls.len()).rev() {
+        for j in (0..punched.len()).rev() {
+            lcs[i][j] = if controls[i] == punched[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut matched = vec![None; controls.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < controls.len() && j < punched.len() {
+        if controls[i] == punched[j] {
+            matched[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    return matched;
+}
+
+/// Matches the controls to their first punch. Returns the controls in the punch order
+/// (unpunched controls at the end) and the index of the punch for every control.
+fn match_score(controls: &[u16], punched: &[u16]) -> (Vec<u16>, Vec<Option<usize>>) {
+    let mut matched: Vec<(u16, Option<usize>)> = controls
+        .iter()
+        .map(|control| (*control, punched.iter().position(|code| code == control)))
+        .collect();
+    matched.sort_by_key(|(_, punch_index)| punch_index.unwrap_or(usize::MAX));
+
+    return matched.into_iter().unzip();
+}
+
+/// Returns the butterfly course with the loops in the order they were run.
+///
+/// The loops are matched greedily: the punches between two punches of the center are assigned to the loop
+/// (not assigned yet) with the most matching controls. Loops which were not run are appended in the course order.
+fn order_loops(controls: &[u16], center: u16, punched: &[u16]) -> Vec<u16> {
+    let (fixed_start, mut loops, fixed_end) = split_loops(controls, center);
+
+    let mut run = Vec::with_capacity(loops.len());
+    // the part before the first punch of the center belongs to the fixed start
+    for part in punched.split(|code| *code == center).skip(1) {
+        // (index of the loop, count of the matching controls)
+        let mut best: Option<(usize, usize)> = None;
+        for (i, controls) in loops.iter().enumerate() {
+            let count = match_ordered(controls, part).iter().flatten().count();
+            if count > best.map_or(0, |(_, best_count)| best_count) {
+                best = Some((i, count));
+            }
+        }
+
+        if let Some((i, _)) = best {
+            run.push(loops.remove(i));
+        }
+    }
+    run.append(&mut loops);
+
+    let mut ordered = fixed_start;
+    for controls in run {
+        ordered.push(center);
+        ordered.extend(controls);
+    }
+    ordered.push(center);
+    ordered.extend(fixed_end);
+
+    return ordered;
+}
+
+/// Splits the butterfly course into the fixed part before the first center, the loops and the fixed part after the last center.
+/// The course has to contain the center.
+fn split_loops(controls: &[u16], center: u16) -> (Vec<u16>, Vec<Vec<u16>>, Vec<u16>) {
+    let mut parts: Vec<Vec<u16>> = controls
+        .split(|control| *control == center)
+        .map(|part| part.to_vec())
+        .collect();
+
+    let fixed_end = parts.pop().unwrap(); // cant fail, there are at least two parts
+    let loops = parts.split_off(1);
+    return (parts.remove(0), loops, fixed_end);
+}
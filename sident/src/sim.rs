@@ -0,0 +1,619 @@
+use std::collections::BTreeMap;
+
+use chrono::Timelike;
+use log::{debug, warn};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, DuplexStream},
+    sync::mpsc,
+};
+
+use crate::{
+    MsMode, OperatingMode, ProtocolConfig, StationFeedback,
+    addr_len::presets::SystemConfigAddrLen,
+    backup::{
+        BACKUP_MEMORY_START, BackupRecord, BackupRecordFormat, backup_pointer, set_backup_pointer,
+    },
+    card::CardType,
+    card_image::CardImage,
+    carddef::si5::Card5Def,
+    codec::consts::NAK,
+    errors::FrameError,
+    framing::FrameDecoder,
+    is_extended_packet_id,
+    packet::{Packet, RawPacket, RawPacketBody},
+    packets::{
+        hostbound::{
+            BaseSICard5Detected, BaseSICard6Detected, BaseSICardRemoved, BaseTransmitRecord,
+            SICard5Detected, SICard6Detected, SICardNewerDetected, SICardRemoved, TransmitRecord,
+        },
+        stationbound::{
+            BaseGetSICard5, BaseGetSICard6, BaseSetMsMode, BeepIfStationReady, GetBackupData,
+            GetSICard5, GetSICard6, GetSICardNewer, GetSystemValue, GetTime, SetMsMode,
+            SetSystemValue, SetTime, WriteSICard6, WriteSICardNewer,
+        },
+    },
+    product::ProductModel,
+    punch::{Punch, PunchTime},
+    time::StationTime,
+};
+
+/// Size of the in-memory duplex buffer between the host and the virtual station
+const DUPLEX_BUFFER_SIZE: usize = 4096;
+
+/// Card that can be inserted into the `VirtualStation`.
+///
+/// The card is just a block image - blocks are served as they are, so any card definition can be emulated.
+#[derive(Debug, Clone)]
+pub struct VirtualCard {
+    siid: u32,
+    blocks: BTreeMap<u8, [u8; 128]>,
+}
+
+impl VirtualCard {
+    /// Creates a new virtual card from a block image.
+    ///
+    /// * `siid` - SIID (card number) reported by the station on insert
+    /// * `blocks` - Blocks of the card (block number -> 128 bytes of data)
+    pub fn new(siid: u32, blocks: BTreeMap<u8, [u8; 128]>) -> Self {
+        Self { siid, blocks }
+    }
+
+    /// Returns the SIID of the card
+    pub fn siid(&self) -> u32 {
+        return self.siid;
+    }
+
+    /// Returns the block, if the image contains it
+    pub fn block(&self, block_number: u8) -> Option<&[u8; 128]> {
+        return self.blocks.get(&block_number);
+    }
+}
+
+impl From<CardImage> for VirtualCard {
+    fn from(value: CardImage) -> Self {
+        return VirtualCard::new(value.siid, value.blocks);
+    }
+}
+
+#[derive(Debug)]
+enum VirtualStationCommand {
+    InsertCard(VirtualCard),
+    RemoveCard,
+    Punch(u32),
+    SendRaw(Vec<u8>),
+}
+
+/// Handle for controlling a running `VirtualStation` (inserting and removing cards, punching).
+#[derive(Debug, Clone)]
+pub struct VirtualStationHandle {
+    commands: mpsc::UnboundedSender<VirtualStationCommand>,
+}
+
+impl VirtualStationHandle {
+    /// Inserts the card into the station. If there already is a card, it gets removed first.
+    ///
+    /// Returns `false` if the station is not running anymore.
+    pub fn insert_card(&self, card: VirtualCard) -> bool {
+        return self
+            .commands
+            .send(VirtualStationCommand::InsertCard(card))
+            .is_ok();
+    }
+
+    /// Removes the card from the station.
+    ///
+    /// Returns `false` if the station is not running anymore.
+    pub fn remove_card(&self) -> bool {
+        return self
+            .commands
+            .send(VirtualStationCommand::RemoveCard)
+            .is_ok();
+    }
+
+    /// Punches the card at the station (with the station time).
+    ///
+    /// The punch is stored in the backup memory and sent as `TransmitRecord`,
+    /// if `ProtocolConfig::AUTO_SEND_OUT` is set.
+    ///
+    /// Returns `false` if the station is not running anymore.
+    pub fn punch(&self, siid: u32) -> bool {
+        return self
+            .commands
+            .send(VirtualStationCommand::Punch(siid))
+            .is_ok();
+    }
+
+    /// Sends the bytes to the host as they are (for emulating a broken packet, `NAK`, line noise, ...).
+    ///
+    /// Returns `false` if the station is not running anymore.
+    pub fn send_raw(&self, data: Vec<u8>) -> bool {
+        return self
+            .commands
+            .send(VirtualStationCommand::SendRaw(data))
+            .is_ok();
+    }
+}
+
+/// Virtual SPORTident station (BSM8 by default) sitting on a tokio duplex stream.
+///
+/// Answers `SetMsMode` (also `BaseSetMsMode`), `GetSystemValue`, `SetSystemValue`, `BeepIfStationReady`,
+/// `GetTime`, `SetTime`, `GetBackupData`, `GetSICard5`, `GetSICard6`, `GetSICardNewer`, `WriteSICard6` and `WriteSICardNewer`.
+/// Everything else is answered with `NAK`.
+///
+/// If `ProtocolConfig::EXTENDED_PROTOCOL` is not set in the system values, the station emulates an old station
+/// which only knows the base protocol: it answers just `BaseSetMsMode`, `BaseGetSICard5` and `BaseGetSICard6`,
+/// punches are sent as `BaseTransmitRecord` and only Card 5 and Card 6 are reported
+/// (`BaseSICard5Detected`, `BaseSICard6Detected` and `BaseSICardRemoved`).
+///
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use sident::{
+///     connection::{Connection, ReadoutPreference},
+///     sim::{VirtualCard, VirtualStation},
+/// };
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (station, stream) = VirtualStation::new();
+/// let handle = station.handle();
+/// tokio::spawn(station.run());
+///
+/// let mut conn = Connection::from_stream(stream).await.unwrap();
+///
+/// // Card 8 with personal data and one punch (control 31)
+/// let mut block0 = [0u8; 128];
+/// block0[0x04..0x08].copy_from_slice(&[0xEA; 4]);
+/// block0[0x08..0x0C].copy_from_slice(&[0x00, 0x01, 0x00, 0x10]);
+/// block0[0x0C..0x14].copy_from_slice(&[0xEE; 8]);
+/// block0[0x16] = 1;
+/// block0[0x18..0x1C].copy_from_slice(&2_000_123u32.to_be_bytes());
+/// block0[0x20..0x2A].copy_from_slice(b"Jan;Novak;");
+/// let mut block1 = [0xEE; 128];
+/// block1[0x08..0x0C].copy_from_slice(&[0x00, 31, 0x01, 0x00]);
+///
+/// handle.insert_card(VirtualCard::new(
+///     2_000_123,
+///     BTreeMap::from([(0, block0), (1, block1)]),
+/// ));
+///
+/// let siid = conn.wait_for_card_insert().await.unwrap();
+/// let readout = conn
+///     .read_out(&ReadoutPreference::all(), siid)
+///     .await
+///     .unwrap()
+///     .to_general_readout()
+///     .unwrap();
+///
+/// assert_eq!(readout.siid, 2_000_123);
+/// assert_eq!(readout.punches[0].station_code, 31);
+/// assert_eq!(readout.personal_data.unwrap().last_name.unwrap(), "Novak");
+/// # }
+/// ```
+pub struct VirtualStation {
+    stream: DuplexStream,
+    commands_rx: mpsc::UnboundedReceiver<VirtualStationCommand>,
+    commands_tx: mpsc::UnboundedSender<VirtualStationCommand>,
+    ms_mode: MsMode,
+    system_values: [u8; 128],
+    card: Option<VirtualCard>,
+    /// Offset of the station clock against the host clock
+    clock_offset: chrono::Duration,
+    /// Written part of the backup memory (starting at 0x000)
+    backup_memory: Vec<u8>,
+    decoder: FrameDecoder,
+}
+
+impl VirtualStation {
+    /// Creates a new virtual station with `VirtualStation::default_system_values()`.
+    ///
+    /// Returns the station and the host end of the stream (pass it to `Connection::from_stream`).
+    pub fn new() -> (Self, DuplexStream) {
+        return Self::with_system_values(Self::default_system_values());
+    }
+
+    /// Creates a new virtual station with custom system values.
+    ///
+    /// * `system_values` - The whole 128 byte system value area of the station
+    ///
+    /// Returns the station and the host end of the stream (pass it to `Connection::from_stream`).
+    pub fn with_system_values(system_values: [u8; 128]) -> (Self, DuplexStream) {
+        let (host, station) = tokio::io::duplex(DUPLEX_BUFFER_SIZE);
+        let (commands_tx, commands_rx) = mpsc::unbounded_channel();
+
+        let station = Self {
+            stream: station,
+            commands_rx,
+            commands_tx,
+            ms_mode: MsMode::Slave,
+            system_values,
+            card: None,
+            clock_offset: chrono::Duration::zero(),
+            backup_memory: Vec::new(),
+            decoder: FrameDecoder::new(),
+        };
+
+        return (station, host);
+    }
+
+    /// System values of a BSM8 readout station with serial number 1 and station code 10
+    pub fn default_system_values() -> [u8; 128] {
+        let mut values = [0xFFu8; 128];
+
+        values[SystemConfigAddrLen::serial_number()].copy_from_slice(&1u32.to_be_bytes());
+        values[SystemConfigAddrLen::srr_config().address()] = 0x00;
+        values[SystemConfigAddrLen::firmware_version()].copy_from_slice(b"656");
+        values[SystemConfigAddrLen::prod_date()].copy_from_slice(&[20, 1, 1]);
+        values[SystemConfigAddrLen::product_model()]
+            .copy_from_slice(&(ProductModel::Bsm8 as u16).to_be_bytes());
+        values[SystemConfigAddrLen::memory_kb().address()] = 128;
+        values[SystemConfigAddrLen::operating_mode().address()] = OperatingMode::Readout as u8;
+        values[SystemConfigAddrLen::station_code().address()] = 10;
+        values[SystemConfigAddrLen::station_flags().address()] =
+            (StationFeedback::FLASH | StationFeedback::BEEP).bits();
+        values[SystemConfigAddrLen::protocol_config().address()] = 0b0000_0101;
+        values[SystemConfigAddrLen::active_time()].copy_from_slice(&30u16.to_be_bytes());
+        values[SystemConfigAddrLen::last_modification()].copy_from_slice(&[20, 1, 1]);
+        set_backup_pointer(&mut values, BACKUP_MEMORY_START);
+
+        return values;
+    }
+
+    /// Returns a handle for inserting and removing cards.
+    pub fn handle(&self) -> VirtualStationHandle {
+        return VirtualStationHandle {
+            commands: self.commands_tx.clone(),
+        };
+    }
+
+    /// Returns the station code from the system values
+    pub fn station_code(&self) -> u16 {
+        let high = self.system_values[SystemConfigAddrLen::station_flags().address()] >> 6;
+        let low = self.system_values[SystemConfigAddrLen::station_code().address()];
+        return ((high as u16) << 8) | low as u16;
+    }
+
+    /// Appends the record to the backup memory and moves the backup pointer.
+    ///
+    /// * `record` - Serialized record (see `BackupRecord::serialize`)
+    pub fn push_backup_record(&mut self, record: &[u8]) {
+        let pointer = backup_pointer(&self.system_values) as usize;
+        if self.backup_memory.len() < pointer {
+            self.backup_memory.resize(pointer, 0xFF);
+        }
+        self.backup_memory.truncate(pointer);
+        self.backup_memory.extend_from_slice(record);
+        set_backup_pointer(&mut self.system_values, self.backup_memory.len() as u32);
+    }
+
+    /// Runs the station until the host end of the stream is dropped.
+    pub async fn run(mut self) -> std::io::Result<()> {
+        let mut read_buf = [0u8; 256];
+
+        loop {
+            tokio::select! {
+                read = self.stream.read(&mut read_buf) => {
+                    let n = read?;
+                    if n == 0 {
+                        debug!("SIM: host disconnected");
+                        return Ok(());
+                    }
+                    let packets: Vec<_> = self.decoder.push(&read_buf[..n]).collect();
+                    for packet in packets {
+                        self.handle_packet(packet).await?;
+                    }
+                }
+                Some(command) = self.commands_rx.recv() => {
+                    self.handle_command(command).await?;
+                }
+            }
+        }
+    }
+
+    async fn handle_command(&mut self, command: VirtualStationCommand) -> std::io::Result<()> {
+        match command {
+            VirtualStationCommand::InsertCard(card) => {
+                self.remove_card().await?;
+
+                let card_type = CardType::from_siid(card.siid);
+                let data = self.card_packet_data(card.siid);
+                debug!("SIM: card {} inserted", card.siid);
+                self.card = Some(card);
+
+                if !self.extended_protocol() {
+                    let id = match card_type {
+                        Some(CardType::Card5) => BaseSICard5Detected::PACKET_ID,
+                        Some(CardType::Card6) => BaseSICard6Detected::PACKET_ID,
+                        // newer cards are not detected by the old stations
+                        _ => return Ok(()),
+                    };
+                    return self.send(id, data[1..].to_vec()).await;
+                }
+
+                let id = match card_type {
+                    Some(CardType::Card5) => SICard5Detected::PACKET_ID,
+                    Some(CardType::Card6) => SICard6Detected::PACKET_ID,
+                    _ => SICardNewerDetected::PACKET_ID,
+                };
+                self.send(id, data).await
+            }
+            VirtualStationCommand::RemoveCard => self.remove_card().await,
+            VirtualStationCommand::Punch(siid) => self.punch(siid).await,
+            VirtualStationCommand::SendRaw(data) => {
+                debug!("SIM: STATION -> HOST (raw): {:02X?}", data);
+                self.stream.write_all(&data).await
+            }
+        }
+    }
+
+    async fn punch(&mut self, siid: u32) -> std::io::Result<()> {
+        let time = self.station_time();
+        let record = BackupRecord {
+            siid,
+            punch: Punch {
+                station_code: self.station_code(),
+                punch_time: PunchTime {
+                    time: time.datetime.time().with_nanosecond(0).unwrap_or_default(),
+                    day: time.day,
+                    week: time.week,
+                    twelve_hour_only: false,
+                    subsecond: Some(time.subsecond),
+                },
+            },
+            date: Some(time.datetime.date()),
+        };
+        let memory_address = backup_pointer(&self.system_values);
+        self.push_backup_record(&record.serialize(BackupRecordFormat::Standard));
+        debug!("SIM: card {} punched", siid);
+
+        let protocol_config = ProtocolConfig::from_bits_truncate(
+            self.system_values[SystemConfigAddrLen::protocol_config().address()],
+        );
+        if !protocol_config.contains(ProtocolConfig::AUTO_SEND_OUT) {
+            return Ok(());
+        }
+
+        let time_bytes = time.serialize();
+        if !self.extended_protocol() {
+            // CN0, SI3, SI2, SI1, SI0, TD, TH, TL, MEM2, MEM1, MEM0
+            let mut data = vec![self.station_code() as u8];
+            data.extend_from_slice(&siid_bytes(siid));
+            data.extend_from_slice(&time_bytes[3..6]);
+            data.extend_from_slice(&memory_address.to_be_bytes()[1..]);
+            return self.send(BaseTransmitRecord::PACKET_ID, data).await;
+        }
+
+        // CN1, CN0, SI3, SI2, SI1, SI0, TD, TH, TL, TSS, MEM2, MEM1, MEM0
+        let mut data = self.station_code_bytes();
+        data.extend_from_slice(&siid_bytes(siid));
+        data.extend_from_slice(&time_bytes[3..7]);
+        data.extend_from_slice(&memory_address.to_be_bytes()[1..]);
+        return self.send(TransmitRecord::PACKET_ID, data).await;
+    }
+
+    async fn remove_card(&mut self) -> std::io::Result<()> {
+        if let Some(card) = self.card.take() {
+            debug!("SIM: card {} removed", card.siid);
+            let data = self.card_packet_data(card.siid);
+            if !self.extended_protocol() {
+                if !matches!(
+                    CardType::from_siid(card.siid),
+                    Some(CardType::Card5 | CardType::Card6)
+                ) {
+                    return Ok(());
+                }
+                return self
+                    .send(BaseSICardRemoved::PACKET_ID, data[1..].to_vec())
+                    .await;
+            }
+            self.send(SICardRemoved::PACKET_ID, data).await?;
+        }
+        return Ok(());
+    }
+
+    async fn handle_packet(
+        &mut self,
+        packet: Result<RawPacket, FrameError>,
+    ) -> std::io::Result<()> {
+        let body = match packet {
+            Ok(RawPacket::Body(body)) => body,
+            Ok(RawPacket::Nak) => return Ok(()),
+            Err(e) => {
+                warn!("SIM: could not parse frame: {}", e);
+                return self.send_nak().await;
+            }
+        };
+
+        debug!("SIM: HOST -> STATION: {:?}", body);
+
+        if is_extended_packet_id(body.id) && !self.extended_protocol() {
+            warn!(
+                "SIM: extended command 0x{:02X} in the base protocol",
+                body.id
+            );
+            return self.send_nak().await;
+        }
+
+        match body.id {
+            BaseSetMsMode::PACKET_ID => {
+                let Some(mode) = body.data.first().and_then(|b| MsMode::from_u8(*b)) else {
+                    return self.send_nak().await;
+                };
+                self.ms_mode = mode;
+                let data = vec![self.station_code() as u8, self.ms_mode.to_u8()];
+                self.send(BaseSetMsMode::PACKET_ID, data).await
+            }
+            SetMsMode::PACKET_ID => {
+                let Some(mode) = body.data.first().and_then(|b| MsMode::from_u8(*b)) else {
+                    return self.send_nak().await;
+                };
+                self.ms_mode = mode;
+                let mut data = self.station_code_bytes();
+                data.push(self.ms_mode.to_u8());
+                self.send(SetMsMode::PACKET_ID, data).await
+            }
+            GetSystemValue::PACKET_ID => {
+                let [address, length] = body.data[..] else {
+                    return self.send_nak().await;
+                };
+                let (address, length) = (address as usize, length as usize);
+                if address + length > self.system_values.len() {
+                    return self.send_nak().await;
+                }
+                let mut data = self.station_code_bytes();
+                data.push(address as u8);
+                data.extend_from_slice(&self.system_values[address..address + length]);
+                self.send(GetSystemValue::PACKET_ID, data).await
+            }
+            SetSystemValue::PACKET_ID => {
+                let Some((address, values)) = body.data.split_first() else {
+                    return self.send_nak().await;
+                };
+                let address = *address as usize;
+                if address + values.len() > self.system_values.len() {
+                    return self.send_nak().await;
+                }
+                self.system_values[address..address + values.len()].copy_from_slice(values);
+                let mut data = self.station_code_bytes();
+                data.push(address as u8);
+                self.send(SetSystemValue::PACKET_ID, data).await
+            }
+            GetBackupData::PACKET_ID => {
+                let [adr2, adr1, adr0, len] = body.data[..] else {
+                    return self.send_nak().await;
+                };
+                let address = u32::from_be_bytes([0, adr2, adr1, adr0]) as usize;
+                let mut data = self.station_code_bytes();
+                data.extend_from_slice(&[adr2, adr1, adr0]);
+                data.extend((address..address + len as usize).map(|i| {
+                    return self.backup_memory.get(i).copied().unwrap_or(0xFF);
+                }));
+                self.send(GetBackupData::PACKET_ID, data).await
+            }
+            GetTime::PACKET_ID => {
+                let mut data = self.station_code_bytes();
+                data.extend_from_slice(&self.station_time().serialize());
+                self.send(GetTime::PACKET_ID, data).await
+            }
+            SetTime::PACKET_ID => {
+                let Ok(time) = <[u8; 7]>::try_from(body.data.as_slice()) else {
+                    return self.send_nak().await;
+                };
+                let Ok(time) = StationTime::deserialize(&time) else {
+                    return self.send_nak().await;
+                };
+                self.clock_offset = time.datetime - chrono::Local::now().naive_local();
+                let mut data = self.station_code_bytes();
+                data.extend_from_slice(&self.station_time().serialize());
+                self.send(SetTime::PACKET_ID, data).await
+            }
+            BeepIfStationReady::PACKET_ID => {
+                let mut data = self.station_code_bytes();
+                data.push(body.data.first().copied().unwrap_or(1));
+                self.send(BeepIfStationReady::PACKET_ID, data).await
+            }
+            GetSICard5::PACKET_ID => {
+                let Some(block) = self.card.as_ref().and_then(|c| c.block(0)) else {
+                    return self.send_nak().await;
+                };
+                let mut data = self.station_code_bytes();
+                data.extend_from_slice(block);
+                self.send(GetSICard5::PACKET_ID, data).await
+            }
+            BaseGetSICard5::PACKET_ID => {
+                let Some(block) = self.card.as_ref().and_then(|c| c.block(0)) else {
+                    return self.send_nak().await;
+                };
+                let mut data = vec![self.station_code() as u8];
+                data.extend_from_slice(block);
+                self.send(BaseGetSICard5::PACKET_ID, data).await
+            }
+            BaseGetSICard6::PACKET_ID => {
+                let block_number = body.data.first().copied().unwrap_or(0);
+                let Some(block) = self.card.as_ref().and_then(|c| c.block(block_number)) else {
+                    return self.send_nak().await;
+                };
+                let mut data = vec![self.station_code() as u8, block_number];
+                data.extend_from_slice(block);
+                self.send(BaseGetSICard6::PACKET_ID, data).await
+            }
+            GetSICard6::PACKET_ID | GetSICardNewer::PACKET_ID => {
+                let block_number = body.data.first().copied().unwrap_or(0);
+                let Some(block) = self.card.as_ref().and_then(|c| c.block(block_number)) else {
+                    return self.send_nak().await;
+                };
+                let mut data = self.station_code_bytes();
+                data.push(block_number);
+                data.extend_from_slice(block);
+                self.send(body.id, data).await
+            }
+            WriteSICard6::PACKET_ID | WriteSICardNewer::PACKET_ID => {
+                let (Some(card), Some((block_number, block))) =
+                    (self.card.as_mut(), body.data.split_first())
+                else {
+                    return self.send_nak().await;
+                };
+                let Ok(block) = <[u8; 128]>::try_from(block) else {
+                    return self.send_nak().await;
+                };
+                card.blocks.insert(*block_number, block);
+                let mut data = self.station_code_bytes();
+                data.push(*block_number);
+                self.send(body.id, data).await
+            }
+            _ => {
+                warn!("SIM: unsupported command 0x{:02X}", body.id);
+                self.send_nak().await
+            }
+        }
+    }
+
+    /// Returns false if the station emulates an old station, which only knows the base protocol
+    /// (`ProtocolConfig::EXTENDED_PROTOCOL` is not set)
+    fn extended_protocol(&self) -> bool {
+        let protocol_config = ProtocolConfig::from_bits_truncate(
+            self.system_values[SystemConfigAddrLen::protocol_config().address()],
+        );
+        return protocol_config.contains(ProtocolConfig::EXTENDED_PROTOCOL);
+    }
+
+    fn station_time(&self) -> StationTime {
+        return StationTime::from_datetime(chrono::Local::now().naive_local() + self.clock_offset);
+    }
+
+    fn station_code_bytes(&self) -> Vec<u8> {
+        return self.station_code().to_be_bytes().to_vec();
+    }
+
+    /// Data of the card detected/removed packets (CN1, CN0, SI3, SI2, SI1, SI0).
+    /// The base protocol packets have just CN0.
+    fn card_packet_data(&self, siid: u32) -> Vec<u8> {
+        let mut data = self.station_code_bytes();
+        data.extend_from_slice(&siid_bytes(siid));
+        return data;
+    }
+
+    async fn send(&mut self, id: u8, data: Vec<u8>) -> std::io::Result<()> {
+        let body = RawPacketBody { id, data };
+        debug!("SIM: STATION -> HOST: {:?}", body);
+        self.stream.write_all(&body.serialize()).await?;
+        return Ok(());
+    }
+
+    async fn send_nak(&mut self) -> std::io::Result<()> {
+        self.stream.write_all(&[NAK]).await?;
+        return Ok(());
+    }
+}
+
+/// SI3, SI2, SI1 and SI0 bytes as the station sends them. Card 5 is sent as the series (SI2)
+/// and the number in the series (SI1, SI0).
+fn siid_bytes(siid: u32) -> [u8; 4] {
+    if CardType::from_siid(siid) == Some(CardType::Card5) {
+        let (series, number) = Card5Def::encode_siid(siid);
+        let [si1, si0] = number.to_be_bytes();
+        return [0, series, si1, si0];
+    }
+    return siid.to_be_bytes();
+}
@@ -1,9 +1,15 @@
+use bitflags::bitflags;
 use chrono::NaiveDate;
+use num_enum::TryFromPrimitive;
 
 // TODO: Docs
 use crate::{
-    addr_len::presets::SystemConfigAddrLen, errors::MakeSystemConfigError,
-    firmware::FirmwareVersion, product::ProductModel, time::SIDate,
+    ProtocolConfig,
+    addr_len::{AddrLen, presets::SystemConfigAddrLen},
+    errors::{MakeSystemConfigError, StationConfigError},
+    firmware::FirmwareVersion,
+    product::ProductModel,
+    time::SIDate,
 };
 
 /// System config of the station
@@ -73,7 +79,10 @@ impl SystemConfig {
 
 /// SRR channel
 /// Source: SPORTident.Communication.SimSrrFrequencyChannels
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 #[repr(u8)]
 pub enum SrrChannel {
     Red = 0x00,
@@ -82,7 +91,10 @@ pub enum SrrChannel {
 
 /// Operating mode of the station
 /// Source: SPORTident.Communication.OperatingMode
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 #[repr(u8)]
 pub enum OperatingMode {
     DControl = 0x01,
@@ -105,3 +117,105 @@ pub enum OperatingMode {
     BcLineSlave1 = 0x1E,
     BcLineSlave2 = 0x1F,
 }
+
+bitflags! {
+    /// Feedback of the station when a card is punched (0x73, bits 6-7 are the station code high bits)
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct StationFeedback: u8 {
+        const FLASH =   0b0000_0001;
+        const BEEP =    0b0000_0100;
+    }
+}
+
+/// Configurable settings of the station (like in SPORTident Config+)
+///
+/// Read it with `Connection::get_station_config` and write it with `Connection::apply_config`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "ts-rs", derive(ts_rs::TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StationConfig {
+    pub operating_mode: OperatingMode,
+    /// Station code (1-1023)
+    pub station_code: u16,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
+    pub feedback: StationFeedback,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
+    pub protocol_config: ProtocolConfig,
+    /// Time after which the station goes to sleep
+    pub active_time_minutes: u16,
+    /// `None` if the station has no SRR module (or the channel is unknown). `None` is not written.
+    pub srr_channel: Option<SrrChannel>,
+}
+
+impl StationConfig {
+    /// Deserializes StationConfig from the system values
+    ///
+    /// * `input` - The whole 128 byte system value area
+    pub fn deserialize(input: &[u8; 128]) -> Result<Self, StationConfigError> {
+        let mode_byte = input[SystemConfigAddrLen::operating_mode()][0];
+        let operating_mode = OperatingMode::try_from(mode_byte)
+            .map_err(|_| StationConfigError::InvalidOperatingMode(mode_byte))?;
+
+        let flags = input[SystemConfigAddrLen::station_flags()][0];
+        let code_low = input[SystemConfigAddrLen::station_code()][0];
+        let station_code = (((flags >> 6) as u16) << 8) | code_low as u16;
+        let feedback = StationFeedback::from_bits_truncate(flags);
+
+        let protocol_config =
+            ProtocolConfig::from_bits_retain(input[SystemConfigAddrLen::protocol_config()][0]);
+        let active_time_minutes = u16::from_be_bytes(
+            input[SystemConfigAddrLen::active_time()]
+                .try_into()
+                .unwrap(),
+        );
+        let srr_channel = SrrChannel::try_from(input[SystemConfigAddrLen::srr_channel()][0]).ok();
+
+        return Ok(Self {
+            operating_mode,
+            station_code,
+            feedback,
+            protocol_config,
+            active_time_minutes,
+            srr_channel,
+        });
+    }
+
+    /// Writes the config to the system values. Bytes which are not a part of the config stay untouched.
+    ///
+    /// * `output` - The whole 128 byte system value area
+    pub fn serialize_into(&self, output: &mut [u8; 128]) -> Result<(), StationConfigError> {
+        if self.station_code == 0 || self.station_code > 1023 {
+            return Err(StationConfigError::InvalidStationCode(self.station_code));
+        }
+
+        output[SystemConfigAddrLen::operating_mode()][0] = self.operating_mode as u8;
+        output[SystemConfigAddrLen::station_code()][0] = (self.station_code & 0xFF) as u8;
+
+        let flags = &mut output[SystemConfigAddrLen::station_flags()][0];
+        let feedback_mask = StationFeedback::all().bits();
+        *flags = (*flags & !(0b1100_0000 | feedback_mask))
+            | (((self.station_code >> 8) as u8) << 6)
+            | self.feedback.bits();
+
+        output[SystemConfigAddrLen::protocol_config()][0] = self.protocol_config.bits();
+        output[SystemConfigAddrLen::active_time()]
+            .copy_from_slice(&self.active_time_minutes.to_be_bytes());
+        if let Some(srr_channel) = self.srr_channel {
+            output[SystemConfigAddrLen::srr_channel()][0] = srr_channel as u8;
+        }
+
+        return Ok(());
+    }
+
+    /// System value areas written by `Connection::apply_config`
+    pub fn addr_lens() -> [AddrLen; 3] {
+        return [
+            SystemConfigAddrLen::station_settings(),
+            SystemConfigAddrLen::active_time(),
+            SystemConfigAddrLen::srr_channel(),
+        ];
+    }
+}
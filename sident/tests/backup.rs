@@ -0,0 +1,187 @@
+#![cfg(feature = "sim")]
+
+mod common;
+
+use chrono::{NaiveDate, NaiveTime};
+use sident::{
+    backup::{BackupRecord, BackupRecordFormat},
+    connection::Connection,
+    errors::BackupMemoryError,
+    punch::{Punch, PunchTime},
+    sim::VirtualStation,
+    time::DayOfWeek,
+};
+use tokio::io::DuplexStream;
+
+use common::base_protocol_system_values;
+
+/// Code of the default virtual station
+const STATION_CODE: u16 = 10;
+
+fn date() -> NaiveDate {
+    return NaiveDate::from_ymd_opt(2025, 6, 14).unwrap();
+}
+
+/// Record of the punch at `date()`, 10:00 + `minutes`, with `subsecond` ticks
+fn record(siid: u32, station_code: u16, minutes: u32, subsecond: u8) -> BackupRecord {
+    let datetime = date()
+        .and_time(NaiveTime::from_hms_opt(10, 0, 0).unwrap())
+        .checked_add_signed(chrono::Duration::minutes(minutes as i64))
+        .unwrap();
+    let mut punch_time = PunchTime::from_datetime(datetime);
+    punch_time.subsecond = Some(subsecond);
+
+    return BackupRecord {
+        siid,
+        punch: Punch {
+            station_code,
+            punch_time,
+        },
+        date: Some(datetime.date()),
+    };
+}
+
+/// Starts the virtual station with the records in its backup memory and connects to it
+async fn connect_with_records(records: &[Vec<u8>]) -> Connection<DuplexStream> {
+    let (mut station, stream) = VirtualStation::new();
+    for record in records {
+        station.push_backup_record(record);
+    }
+    tokio::spawn(station.run());
+    return Connection::from_stream(stream).await.unwrap();
+}
+
+fn assert_same_record(read: &BackupRecord, written: &BackupRecord) {
+    assert_eq!(read.siid, written.siid);
+    assert_eq!(read.date, written.date);
+    assert_eq!(read.punch, written.punch);
+}
+
+#[tokio::test]
+async fn standard_records_are_read_over_more_pages() {
+    // 40 records of 8 bytes - 3 pages of 128 bytes, the last one is shorter
+    let written: Vec<BackupRecord> = (0..40)
+        .map(|i| record(8_000_100 + i, STATION_CODE, i * 30, i as u8))
+        .collect();
+    let serialized: Vec<Vec<u8>> = written
+        .iter()
+        .map(|record| record.serialize(BackupRecordFormat::Standard))
+        .collect();
+    let mut conn = connect_with_records(&serialized).await;
+
+    let mut progress = Vec::new();
+    let read = conn
+        .read_backup_memory_with_progress(BackupRecordFormat::Standard, |read, total| {
+            progress.push((read, total));
+        })
+        .await
+        .unwrap();
+    assert_eq!(progress, [(128, 320), (256, 320), (320, 320)]);
+
+    assert_eq!(read.len(), 40);
+    for (read, written) in read.iter().zip(&written) {
+        assert_same_record(read, written);
+    }
+
+    // the records run into the afternoon (12h flag in the date) and over midnight
+    assert_eq!(
+        read[39].punch.punch_time.time,
+        NaiveTime::from_hms_opt(5, 30, 0).unwrap()
+    );
+    assert_eq!(read[39].date, NaiveDate::from_ymd_opt(2025, 6, 15));
+    assert_eq!(read[39].punch.punch_time.day, DayOfWeek::Sunday);
+    assert_eq!(
+        read[10].punch.punch_time.time,
+        NaiveTime::from_hms_opt(15, 0, 0).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn extended_records_keep_their_station_code() {
+    // the SIID needs all 4 bytes
+    let written = [
+        record(0x0100_0001, 31, 0, 0),
+        record(14_000_123, 0x1FF, 1, 255),
+        record(8_000_123, 32, 2, 128),
+    ];
+    let serialized: Vec<Vec<u8>> = written
+        .iter()
+        .map(|record| record.serialize(BackupRecordFormat::Extended))
+        .collect();
+    assert!(serialized.iter().all(|record| record.len() == 16));
+    let mut conn = connect_with_records(&serialized).await;
+
+    let read = conn
+        .read_backup_memory(BackupRecordFormat::Extended)
+        .await
+        .unwrap();
+    assert_eq!(read.len(), 3);
+    for (read, written) in read.iter().zip(&written) {
+        assert_same_record(read, written);
+    }
+}
+
+#[tokio::test]
+async fn erased_records_and_records_without_date() {
+    let mut without_date = record(12_345, STATION_CODE, 0, 0);
+    without_date.date = None;
+    let serialized = [
+        record(8_000_123, STATION_CODE, 0, 0).serialize(BackupRecordFormat::Standard),
+        vec![0xFF; 8],
+        without_date.serialize(BackupRecordFormat::Standard),
+    ];
+    let mut conn = connect_with_records(&serialized).await;
+
+    let read = conn
+        .read_backup_memory(BackupRecordFormat::Standard)
+        .await
+        .unwrap();
+    // the erased record is skipped
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[1].siid, 12_345);
+    assert_eq!(read[1].date, None);
+    assert!(read[1].punch.punch_time.twelve_hour_only);
+    assert_eq!(
+        read[1].punch.punch_time.time,
+        NaiveTime::from_hms_opt(10, 0, 0).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn punch_at_the_station_is_stored_in_the_backup_memory() {
+    let (station, stream) = VirtualStation::new();
+    let handle = station.handle();
+    tokio::spawn(station.run());
+    let mut conn = Connection::from_stream(stream).await.unwrap();
+
+    let empty = conn
+        .read_backup_memory(BackupRecordFormat::Standard)
+        .await
+        .unwrap();
+    assert!(empty.is_empty());
+
+    handle.punch(8_000_123);
+    // the station handles the punch before the next request
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let time = conn.get_time().await.unwrap().time;
+    let read = conn
+        .read_backup_memory(BackupRecordFormat::Standard)
+        .await
+        .unwrap();
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].siid, 8_000_123);
+    assert_eq!(read[0].punch.station_code, STATION_CODE);
+    assert_eq!(read[0].punch.punch_time.day, time.day);
+    assert_eq!(read[0].date, Some(time.datetime.date()));
+}
+
+#[tokio::test]
+async fn backup_memory_is_not_available_in_base_protocol() {
+    let (station, stream) = VirtualStation::with_system_values(base_protocol_system_values());
+    tokio::spawn(station.run());
+    let mut conn = Connection::from_stream(stream).await.unwrap();
+
+    let result = conn.read_backup_memory(BackupRecordFormat::Standard).await;
+    assert!(matches!(result, Err(BackupMemoryError::BaseNotSupported)));
+}
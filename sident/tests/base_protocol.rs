@@ -0,0 +1,143 @@
+#![cfg(feature = "sim")]
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveTime;
+use sident::{
+    Protocol,
+    connection::{
+        ReadoutEvent, ReadoutPreference, ReadoutResult, ReadoutService, ReadoutServiceConfig,
+    },
+    errors::ReadoutError,
+    event::StationEvent,
+    sim::VirtualCard,
+};
+
+use sident_core::fixtures::{card5_block, card6_blocks};
+
+use common::{base_protocol_system_values, connect_with};
+
+/// Code of the default virtual station
+const STATION_CODE: u16 = 10;
+
+#[tokio::test]
+async fn base_protocol_is_detected() {
+    let (conn, _handle) = connect_with(base_protocol_system_values()).await;
+    assert_eq!(conn.get_protocol(), Protocol::Base);
+    assert!(conn.get_system_config().is_none());
+}
+
+#[tokio::test]
+async fn card5_readout_in_base_protocol() {
+    let (mut conn, handle) = connect_with(base_protocol_system_values()).await;
+
+    // the times contain STX, ETX and DLE bytes, so they have to be DLE stuffed
+    let block = card5_block(
+        12345,
+        Some(0x0203),
+        Some(0x1003),
+        None,
+        &[(31, 0x0210), (32, 0x0310)],
+    );
+    handle.insert_card(VirtualCard::new(12345, BTreeMap::from([(0, block)])));
+    assert_eq!(conn.wait_for_card_insert().await.unwrap(), 12345);
+
+    let result = conn
+        .read_out(&ReadoutPreference::all(), 12345)
+        .await
+        .unwrap();
+    assert!(matches!(result, ReadoutResult::Card5(_)));
+
+    let readout = result.to_general_readout().unwrap();
+    assert_eq!(readout.siid, 12345);
+    assert_eq!(
+        readout.start.unwrap().punch_time.time,
+        NaiveTime::from_num_seconds_from_midnight_opt(0x0203, 0).unwrap()
+    );
+    assert_eq!(
+        readout.finish.unwrap().punch_time.time,
+        NaiveTime::from_num_seconds_from_midnight_opt(0x1003, 0).unwrap()
+    );
+    let codes: Vec<u16> = readout.punches.iter().map(|p| p.station_code).collect();
+    assert_eq!(codes, vec![31, 32]);
+}
+
+#[tokio::test]
+async fn card6_readout_in_base_protocol() {
+    let (mut conn, handle) = connect_with(base_protocol_system_values()).await;
+
+    let punches: Vec<(u8, u16)> = (0..40).map(|i| (31 + i as u8, 0x0300 + i)).collect();
+    let blocks = card6_blocks(600_123, 0x0100, 0x1000, &punches);
+    handle.insert_card(VirtualCard::new(600_123, blocks));
+    assert_eq!(conn.wait_for_card_insert().await.unwrap(), 600_123);
+
+    let readout = conn
+        .read_out(&[ReadoutPreference::Punches], 600_123)
+        .await
+        .unwrap()
+        .to_general_readout()
+        .unwrap();
+    assert_eq!(readout.siid, 600_123);
+    assert_eq!(readout.punches.len(), 40);
+    assert_eq!(readout.punches[39].station_code, 70);
+}
+
+#[tokio::test]
+async fn newer_cards_are_not_supported_in_base_protocol() {
+    let (mut conn, handle) = connect_with(base_protocol_system_values()).await;
+    // the card is not detected by the old station
+    handle.insert_card(VirtualCard::new(2_000_123, BTreeMap::new()));
+
+    let result = conn.read_out(&ReadoutPreference::all(), 2_000_123).await;
+    assert!(matches!(result, Err(ReadoutError::BaseNotSupported)));
+}
+
+#[tokio::test]
+async fn cards_are_detected_in_base_protocol() {
+    let (mut conn, handle) = connect_with(base_protocol_system_values()).await;
+
+    // Card 5 in series 3, Card 6, then a newer card, which the old station does not detect
+    handle.insert_card(VirtualCard::new(312_345, BTreeMap::new()));
+    handle.insert_card(VirtualCard::new(600_123, BTreeMap::new()));
+    handle.insert_card(VirtualCard::new(2_000_123, BTreeMap::new()));
+    handle.insert_card(VirtualCard::new(12_345, BTreeMap::new()));
+
+    let mut events = Vec::new();
+    for _ in 0..5 {
+        events.push(match conn.next_event().await {
+            StationEvent::CardInserted {
+                siid, station_code, ..
+            } => (true, siid, station_code),
+            StationEvent::CardRemoved { siid, station_code } => (false, siid, station_code),
+            event => panic!("unexpected event {event:?}"),
+        });
+    }
+    assert_eq!(
+        events,
+        [
+            (true, 312_345, STATION_CODE),
+            (false, 312_345, STATION_CODE),
+            (true, 600_123, STATION_CODE),
+            (false, 600_123, STATION_CODE),
+            (true, 12_345, STATION_CODE),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn readout_service_in_base_protocol() {
+    let (conn, handle) = connect_with(base_protocol_system_values()).await;
+    let (service, mut events) = ReadoutService::new(conn, ReadoutServiceConfig::default());
+    tokio::spawn(service.run());
+
+    let block = card5_block(12345, Some(0x0203), Some(0x1003), None, &[(31, 0x0210)]);
+    handle.insert_card(VirtualCard::new(12345, BTreeMap::from([(0, block)])));
+
+    let ReadoutEvent::Completed(readout) = events.recv().await.unwrap() else {
+        panic!("readout failed");
+    };
+    assert_eq!(readout.siid, 12345);
+    assert_eq!(readout.punches[0].station_code, 31);
+}
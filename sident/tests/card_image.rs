@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveTime;
+use sident::{
+    card::CardType,
+    card_image::{CardImage, ToCardImage},
+    carddef::{CardDefinition, si10::Card10Def},
+    errors::CardImageError,
+};
+
+use sident_core::fixtures::{
+    card5_block, card6_blocks, newer_card_block0, si10_block3, write_punches,
+};
+
+const START: u16 = 3600;
+const FINISH: u16 = 7200;
+const PUNCHES: [(u8, u16); 3] = [(31, 4000), (32, 5000), (33, 6000)];
+
+/// Image of the card as it would be read out, with the start, finish and `PUNCHES`
+fn captured_image(card_type: CardType, siid: u32) -> CardImage {
+    let block0 = newer_card_block0(siid, START, FINISH, PUNCHES.len() as u8);
+    let blocks = match card_type {
+        CardType::Card5 => BTreeMap::from([(
+            0,
+            card5_block(siid, Some(START), Some(FINISH), None, &PUNCHES),
+        )]),
+        CardType::Card6 => card6_blocks(siid, START, FINISH, &PUNCHES),
+        CardType::Card8 | CardType::ComCardUp => {
+            let mut block1 = [0xEE; 128];
+            write_punches(&mut block1, 0x08, 4, &PUNCHES);
+            BTreeMap::from([(0, block0), (1, block1)])
+        }
+        CardType::Card9 => {
+            let mut block0 = block0;
+            write_punches(&mut block0, 0x38, 4, &PUNCHES);
+            BTreeMap::from([(0, block0), (1, [0xEE; 128])])
+        }
+        CardType::PCard => {
+            let mut block1 = [0xEE; 128];
+            write_punches(&mut block1, 0x30, 4, &PUNCHES);
+            BTreeMap::from([(0, block0), (1, block1)])
+        }
+        CardType::TCard | CardType::FCard => {
+            let mut block4 = [0xEE; 128];
+            write_punches(&mut block4, 0x00, 8, &PUNCHES);
+            BTreeMap::from([(0, block0), (4, block4)])
+        }
+        CardType::Card10 | CardType::Card11 | CardType::ActiveCard | CardType::ComCardPro => {
+            let mut block4 = [0xEE; 128];
+            write_punches(&mut block4, 0x00, 4, &PUNCHES);
+            BTreeMap::from([(0, block0), (3, si10_block3()), (4, block4)])
+        }
+        card_type => panic!("no image of {card_type:?}"),
+    };
+
+    return CardImage {
+        card_type,
+        siid,
+        blocks,
+    };
+}
+
+fn seconds(seconds: u16) -> NaiveTime {
+    return NaiveTime::from_num_seconds_from_midnight_opt(seconds as u32, 0).unwrap();
+}
+
+#[test]
+fn captured_images_load_to_their_card_definition() {
+    let cards = [
+        (CardType::Card5, 12_345),
+        (CardType::Card6, 600_123),
+        (CardType::Card8, 2_000_123),
+        (CardType::Card9, 1_000_123),
+        (CardType::Card10, 7_000_123),
+        (CardType::Card11, 9_000_123),
+        (CardType::ActiveCard, 8_000_123),
+        (CardType::PCard, 4_000_123),
+        (CardType::TCard, 6_000_123),
+        (CardType::FCard, 14_000_123),
+        (CardType::ComCardUp, 2_800_123),
+        (CardType::ComCardPro, 7_100_123),
+    ];
+
+    for (card_type, siid) in cards {
+        let result = captured_image(card_type, siid).load().unwrap();
+        let readout = result.to_general_readout().unwrap();
+        assert_eq!(readout.siid, siid, "{card_type:?}");
+        assert_eq!(
+            readout.start.unwrap().punch_time.time,
+            seconds(START),
+            "{card_type:?}"
+        );
+        assert_eq!(
+            readout.finish.unwrap().punch_time.time,
+            seconds(FINISH),
+            "{card_type:?}"
+        );
+
+        let punches: Vec<(u16, NaiveTime)> = readout
+            .punches
+            .iter()
+            .map(|punch| (punch.station_code, punch.punch_time.time))
+            .collect();
+        let expected: Vec<(u16, NaiveTime)> = PUNCHES
+            .iter()
+            .map(|(code, time)| (*code as u16, seconds(*time)))
+            .collect();
+        assert_eq!(punches, expected, "{card_type:?}");
+
+        // the loaded definition makes the same image again (ComCardPro has no SIID range of its own)
+        let image = result.to_image().unwrap();
+        assert_eq!(image.siid, siid, "{card_type:?}");
+        assert_eq!(
+            image.blocks,
+            captured_image(card_type, siid).blocks,
+            "{card_type:?}"
+        );
+    }
+}
+
+#[test]
+fn load_generic_feeds_every_block() {
+    let image = captured_image(CardType::Card10, 7_000_123);
+    let carddef = image.load_generic::<Card10Def>().unwrap();
+    assert_eq!(carddef.get_siid(), Some(7_000_123));
+    assert_eq!(carddef.raw_blocks(), &image.blocks);
+    assert_eq!(carddef.to_image().unwrap(), image);
+}
+
+#[test]
+fn binary_round_trip() {
+    let image = captured_image(CardType::ActiveCard, 8_000_123);
+    let bytes = image.to_bytes();
+    assert_eq!(&bytes[0..4], b"SIDI");
+    assert_eq!(bytes.len(), 12 + 3 * 129);
+    assert_eq!(CardImage::from_bytes(&bytes).unwrap(), image);
+}
+
+#[test]
+fn binary_round_trip_of_every_block_number() {
+    let blocks: BTreeMap<u8, [u8; 128]> = (0..=255u8).map(|id| (id, [id; 128])).collect();
+    let image = CardImage {
+        card_type: CardType::Card8,
+        siid: 2_000_123,
+        blocks,
+    };
+
+    let bytes = image.to_bytes();
+    assert_eq!(bytes.len(), 12 + 256 * 129);
+    assert_eq!(CardImage::from_bytes(&bytes).unwrap(), image);
+}
+
+#[test]
+fn binary_round_trip_of_empty_image() {
+    let image = CardImage {
+        card_type: CardType::Card5,
+        siid: 12_345,
+        blocks: BTreeMap::new(),
+    };
+    assert_eq!(CardImage::from_bytes(&image.to_bytes()).unwrap(), image);
+}
+
+#[test]
+fn invalid_binary_images_are_rejected() {
+    let bytes = captured_image(CardType::Card6, 600_123).to_bytes();
+
+    let result = CardImage::from_bytes(&bytes[..11]);
+    assert!(matches!(result, Err(CardImageError::InvalidLength)));
+
+    // missing the last byte of the last block
+    let result = CardImage::from_bytes(&bytes[..bytes.len() - 1]);
+    assert!(matches!(result, Err(CardImageError::InvalidLength)));
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    let result = CardImage::from_bytes(&trailing);
+    assert!(matches!(result, Err(CardImageError::InvalidLength)));
+
+    let mut magic = bytes.clone();
+    magic[0..4].copy_from_slice(b"SIDX");
+    let result = CardImage::from_bytes(&magic);
+    assert!(matches!(result, Err(CardImageError::InvalidMagic)));
+
+    let mut version = bytes.clone();
+    version[4] = 2;
+    let result = CardImage::from_bytes(&version);
+    assert!(matches!(result, Err(CardImageError::UnsupportedVersion(2))));
+
+    let mut card_type = bytes.clone();
+    card_type[5] = 0xF0;
+    let result = CardImage::from_bytes(&card_type);
+    assert!(matches!(result, Err(CardImageError::UnknownCardType(0xF0))));
+}
+
+#[test]
+fn card_without_definition_is_not_supported() {
+    let image = CardImage {
+        card_type: CardType::Card5U,
+        siid: 5_373_953,
+        blocks: BTreeMap::new(),
+    };
+    let result = image.load();
+    assert!(matches!(
+        result,
+        Err(CardImageError::CardNotSupported(CardType::Card5U))
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_round_trip() {
+    let image = captured_image(CardType::Card10, 7_000_123);
+    let json = image.to_json().unwrap();
+    assert!(json.contains("\"card_type\": \"Card10\""));
+    assert_eq!(CardImage::from_json(&json).unwrap(), image);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn invalid_json_images_are_rejected() {
+    let json = captured_image(CardType::Card9, 1_000_123)
+        .to_json()
+        .unwrap();
+    let block1 = serde_json::to_string(&"EE".repeat(128)).unwrap();
+
+    let not_hex = json.replacen(
+        &block1,
+        &serde_json::to_string(&"XY".repeat(128)).unwrap(),
+        1,
+    );
+    let result = CardImage::from_json(&not_hex);
+    assert!(matches!(result, Err(CardImageError::InvalidBlock(1))));
+
+    let short = json.replacen(
+        &block1,
+        &serde_json::to_string(&"EE".repeat(127)).unwrap(),
+        1,
+    );
+    let result = CardImage::from_json(&short);
+    assert!(matches!(result, Err(CardImageError::InvalidBlock(1))));
+
+    let version = json.replacen("\"version\": 1", "\"version\": 2", 1);
+    let result = CardImage::from_json(&version);
+    assert!(matches!(result, Err(CardImageError::UnsupportedVersion(2))));
+
+    let result = CardImage::from_json("{}");
+    assert!(matches!(result, Err(CardImageError::JsonError(_))));
+}
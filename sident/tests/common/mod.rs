@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+
+#[cfg(feature = "sim")]
+use sident::{
+    ProtocolConfig,
+    addr_len::presets::SystemConfigAddrLen,
+    connection::Connection,
+    sim::{VirtualStation, VirtualStationHandle},
+};
+#[cfg(feature = "sim")]
+use tokio::io::DuplexStream;
+
+/// Starts the virtual station and connects to it
+#[cfg(feature = "sim")]
+pub async fn connect_with(
+    system_values: [u8; 128],
+) -> (Connection<DuplexStream>, VirtualStationHandle) {
+    let (station, stream) = VirtualStation::with_system_values(system_values);
+    let handle = station.handle();
+    tokio::spawn(station.run());
+
+    let conn = Connection::from_stream(stream).await.unwrap();
+    return (conn, handle);
+}
+
+/// Starts the default virtual station (extended protocol) and connects to it
+#[cfg(feature = "sim")]
+pub async fn connect() -> (Connection<DuplexStream>, VirtualStationHandle) {
+    return connect_with(VirtualStation::default_system_values()).await;
+}
+
+/// System values of an old station, which only knows the base protocol
+#[cfg(feature = "sim")]
+pub fn base_protocol_system_values() -> [u8; 128] {
+    let mut values = VirtualStation::default_system_values();
+    let address = SystemConfigAddrLen::protocol_config().address();
+    let mut protocol_config = ProtocolConfig::from_bits_truncate(values[address]);
+    protocol_config.remove(ProtocolConfig::EXTENDED_PROTOCOL);
+    values[address] = protocol_config.bits();
+    return values;
+}
@@ -0,0 +1,296 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use sident::{
+    backup::BackupRecord,
+    card::CardPersonalData,
+    connection::GeneralReadout,
+    csv::{
+        CsvColumns, CsvConfig, read_backup_records, read_readouts, write_backup_records,
+        write_readouts,
+    },
+    errors::CsvError,
+    punch::{Punch, PunchTime},
+    time::{DayOfWeek, WeekOfMonth},
+};
+
+const DAY_PREFIX: CsvConfig = CsvConfig {
+    columns: CsvColumns::all(),
+    day_prefix: true,
+};
+
+fn time(h: u32, m: u32, s: u32) -> NaiveTime {
+    return NaiveTime::from_hms_opt(h, m, s).unwrap();
+}
+
+fn datetime(day: u32, h: u32, m: u32, s: u32) -> NaiveDateTime {
+    return NaiveDate::from_ymd_opt(2025, 6, day)
+        .unwrap()
+        .and_time(time(h, m, s));
+}
+
+fn punch(station_code: u16, datetime: NaiveDateTime) -> Punch {
+    return Punch {
+        station_code,
+        punch_time: PunchTime::from_datetime(datetime),
+    };
+}
+
+/// Code, time and day of the punch - what the CSV keeps
+fn cells(punch: &Punch) -> (u16, NaiveTime, DayOfWeek) {
+    return (
+        punch.station_code,
+        punch.punch_time.time,
+        punch.punch_time.day,
+    );
+}
+
+/// Readout running over midnight from Saturday to Sunday
+fn readout() -> GeneralReadout {
+    return GeneralReadout {
+        siid: 8_000_123,
+        personal_data: Some(CardPersonalData {
+            first_name: Some("Jan".to_string()),
+            last_name: Some("Novák".to_string()),
+            club: Some("OK \"Praha\"; Brno".to_string()),
+            street: Some("Hlavní 1\r\nbyt 2".to_string()),
+            ..Default::default()
+        }),
+        clear_check: Some(punch(1, datetime(14, 23, 58, 0))),
+        start: Some(punch(10, datetime(14, 23, 59, 0))),
+        finish: Some(punch(20, datetime(15, 0, 31, 2))),
+        punches: vec![
+            punch(31, datetime(14, 23, 59, 59)),
+            punch(32, datetime(15, 0, 12, 0)),
+        ],
+    };
+}
+
+fn assert_same_punches(read: &GeneralReadout, written: &GeneralReadout) {
+    assert_eq!(
+        read.clear_check.map(|p| cells(&p)),
+        written.clear_check.map(|p| cells(&p))
+    );
+    assert_eq!(
+        read.start.map(|p| cells(&p)),
+        written.start.map(|p| cells(&p))
+    );
+    assert_eq!(
+        read.finish.map(|p| cells(&p)),
+        written.finish.map(|p| cells(&p))
+    );
+    assert_eq!(
+        read.punches.iter().map(cells).collect::<Vec<_>>(),
+        written.punches.iter().map(cells).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn readouts_round_trip_with_day_prefix() {
+    let written = [readout()];
+    let csv = write_readouts(&written, &DAY_PREFIX);
+    assert!(csv.contains(";Sa 23:59:00;"));
+    assert!(csv.contains(";Su 00:12:00"));
+
+    let read = read_readouts(&csv).unwrap();
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].siid, written[0].siid);
+    assert_eq!(read[0].personal_data, written[0].personal_data);
+    assert_same_punches(&read[0], &written[0]);
+}
+
+#[test]
+fn readouts_round_trip_without_day_prefix() {
+    let written = [readout()];
+    let csv = write_readouts(&written, &CsvConfig::default());
+    assert!(csv.contains(";23:59:00;"));
+
+    let read = read_readouts(&csv).unwrap();
+    assert_eq!(read[0].personal_data, written[0].personal_data);
+
+    // the day of week is unknown
+    let finish = read[0].finish.unwrap();
+    assert_eq!(finish.station_code, 20);
+    assert_eq!(finish.punch_time.time, time(0, 31, 2));
+    assert!(finish.punch_time.twelve_hour_only);
+}
+
+#[test]
+fn cells_with_separator_quotes_and_new_lines_are_quoted() {
+    let csv = write_readouts(&[readout()], &CsvConfig::default());
+    assert!(csv.contains(";\"OK \"\"Praha\"\"; Brno\";"));
+    assert!(csv.contains(";\"Hlavní 1\r\nbyt 2\";"));
+
+    // the quoted new line does not split the row
+    let read = read_readouts(&csv).unwrap();
+    assert_eq!(read.len(), 1);
+    let personal_data = read[0].personal_data.as_ref().unwrap();
+    assert_eq!(personal_data.club.as_deref(), Some("OK \"Praha\"; Brno"));
+    assert_eq!(personal_data.street.as_deref(), Some("Hlavní 1\r\nbyt 2"));
+}
+
+#[test]
+fn twelve_hour_punches_have_no_day_prefix() {
+    let mut written = readout();
+    written.punches[0].punch_time = PunchTime {
+        time: time(11, 59, 59),
+        twelve_hour_only: true,
+        ..Default::default()
+    };
+    let csv = write_readouts(&[written], &DAY_PREFIX);
+    assert!(csv.contains(";31;11:59:59;"));
+}
+
+#[test]
+fn readouts_with_less_columns() {
+    let mut short = readout();
+    short.punches.truncate(1);
+    let config = CsvConfig {
+        columns: CsvColumns::FINISH | CsvColumns::PUNCHES,
+        day_prefix: false,
+    };
+    let csv = write_readouts(&[readout(), short], &config);
+    assert!(csv.starts_with("No;SIID;Finish CN;Finish time;No. of punches;C1;T1;C2;T2\r\n"));
+    // every row has the same number of cells
+    assert!(csv.ends_with(";1;31;23:59:59;;\r\n"));
+
+    let read = read_readouts(&csv).unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[1].clear_check, None);
+    assert!(read[1].start.is_none());
+    assert!(read[1].personal_data.is_none());
+    assert_eq!(read[1].finish.unwrap().station_code, 20);
+    assert_eq!(read[1].punches.len(), 1);
+}
+
+#[test]
+fn readouts_file_without_day_prefix() {
+    let read = read_readouts(include_str!("data/readouts.csv")).unwrap();
+    assert_eq!(read.len(), 2);
+
+    assert_eq!(read[0].siid, 8_000_123);
+    let personal_data = read[0].personal_data.as_ref().unwrap();
+    assert_eq!(personal_data.last_name.as_deref(), Some("Novák"));
+    assert_eq!(personal_data.club.as_deref(), Some("OK \"Praha\"; Brno"));
+    assert_eq!(personal_data.country.as_deref(), Some("CZE"));
+    assert_eq!(personal_data.gender.as_deref(), Some("M"));
+    assert_eq!(read[0].start.unwrap().station_code, 10);
+    // without the prefix the day of week is unknown
+    assert!(
+        read[0]
+            .punches
+            .iter()
+            .all(|punch| punch.punch_time.twelve_hour_only)
+    );
+    let punches: Vec<_> = read[0].punches.iter().map(cells).collect();
+    let unknown = DayOfWeek::default();
+    assert_eq!(
+        punches,
+        [
+            (31, time(10, 5, 10), unknown),
+            (32, time(10, 12, 0), unknown),
+            (31, time(10, 20, 30), unknown),
+        ]
+    );
+
+    assert_eq!(read[1].siid, 12_345);
+    assert!(read[1].personal_data.is_none());
+    assert!(read[1].start.is_none());
+    assert_eq!(read[1].punches.len(), 1);
+}
+
+#[test]
+fn readouts_file_with_day_prefix() {
+    let read = read_readouts(include_str!("data/readouts_day_prefix.csv")).unwrap();
+    assert_eq!(read.len(), 2);
+
+    assert_eq!(
+        cells(&read[0].clear_check.unwrap()),
+        (1, time(23, 58, 0), DayOfWeek::Saturday)
+    );
+    assert_eq!(
+        cells(&read[0].finish.unwrap()),
+        (20, time(0, 31, 2), DayOfWeek::Sunday)
+    );
+    // the day is case insensitive
+    assert_eq!(
+        cells(&read[0].punches[1]),
+        (32, time(0, 12, 0), DayOfWeek::Sunday)
+    );
+
+    // the 4-week counter is not in the file
+    assert_eq!(read[0].finish.unwrap().punch_time.week, WeekOfMonth::Week1);
+    assert!(!read[0].finish.unwrap().punch_time.twelve_hour_only);
+
+    // rows without the prefix can be mixed in
+    assert_eq!(
+        cells(&read[1].punches[0]),
+        (31, time(9, 50, 0), DayOfWeek::default())
+    );
+    assert!(read[1].punches[0].punch_time.twelve_hour_only);
+}
+
+#[test]
+fn invalid_readouts_are_rejected() {
+    let result = read_readouts("No;Card\r\n1;8000123\r\n");
+    assert!(matches!(result, Err(CsvError::MissingColumn("SIID"))));
+
+    let result =
+        read_readouts("No;SIID;C1;T1\r\n1;8000123;31;10:00:00\r\n2;8000124;31;25:00:00\r\n");
+    assert!(
+        matches!(result, Err(CsvError::InvalidValue(3, column, value)) if column == "T1" && value == "25:00:00")
+    );
+
+    let result = read_readouts("No;SIID;C1;T1\r\n1;8000123;31;Xy 10:00:00\r\n");
+    assert!(matches!(result, Err(CsvError::InvalidValue(2, _, _))));
+}
+
+#[test]
+fn backup_records_round_trip() {
+    let written = [
+        BackupRecord {
+            siid: 8_000_123,
+            punch: punch(31, datetime(14, 10, 5, 10)),
+            date: Some(NaiveDate::from_ymd_opt(2025, 6, 14).unwrap()),
+        },
+        BackupRecord {
+            siid: 8_000_124,
+            punch: Punch {
+                station_code: 31,
+                punch_time: PunchTime {
+                    time: time(3, 12, 0),
+                    twelve_hour_only: true,
+                    ..Default::default()
+                },
+            },
+            date: None,
+        },
+    ];
+
+    for config in [CsvConfig::default(), DAY_PREFIX] {
+        let csv = write_backup_records(&written, &config);
+        let read = read_backup_records(&csv).unwrap();
+        assert_eq!(read.len(), 2);
+        for (read, written) in read.iter().zip(&written) {
+            assert_eq!(read.siid, written.siid);
+            assert_eq!(read.date, written.date);
+            assert_eq!(read.punch, written.punch);
+        }
+    }
+}
+
+#[test]
+fn backup_records_file() {
+    let read = read_backup_records(include_str!("data/backup.csv")).unwrap();
+    assert_eq!(read.len(), 2);
+
+    assert_eq!(read[0].siid, 8_000_123);
+    assert_eq!(read[0].date, NaiveDate::from_ymd_opt(2025, 6, 14));
+    assert_eq!(read[0].punch, punch(31, datetime(14, 10, 5, 10)));
+
+    // without the date the record is 12h only, like in the backup memory
+    assert_eq!(read[1].date, None);
+    assert!(read[1].punch.punch_time.twelve_hour_only);
+    assert_eq!(read[1].punch.punch_time.time, time(3, 12, 0));
+
+    let result = read_backup_records("No;SIID;CN;Date\r\n");
+    assert!(matches!(result, Err(CsvError::MissingColumn("Time"))));
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "framed")]
+
+use futures::StreamExt;
+use sident::{
+    codec::FrameCodec,
+    errors::FrameError,
+    packet::{RawPacket, RawPacketBody},
+};
+use tokio::io::AsyncWriteExt;
+use tokio_util::codec::Framed;
+
+#[tokio::test]
+async fn stream_continues_after_broken_frame() {
+    let (mut station, host) = tokio::io::duplex(1024);
+    let mut framed = Framed::new(host, FrameCodec::new());
+
+    let good = RawPacketBody {
+        id: 0xF0,
+        data: vec![0x00, 0x01, 0x4D],
+    }
+    .serialize();
+    let mut broken = good.clone();
+    let crc_index = broken.len() - 3;
+    broken[crc_index] ^= 0xFF;
+
+    station.write_all(&broken).await.unwrap();
+    station.write_all(&good).await.unwrap();
+    drop(station);
+
+    assert!(matches!(
+        framed.next().await,
+        Some(Ok(Err(FrameError::CrcError)))
+    ));
+    let Some(Ok(Ok(RawPacket::Body(body)))) = framed.next().await else {
+        panic!("frame after the broken one not decoded");
+    };
+    assert_eq!(body.data, vec![0x00, 0x01, 0x4D]);
+    assert!(framed.next().await.is_none());
+}
+
+#[tokio::test]
+async fn partial_frame_at_eof_is_reported() {
+    let (mut station, host) = tokio::io::duplex(1024);
+    let mut framed = Framed::new(host, FrameCodec::new());
+
+    let frame = RawPacketBody {
+        id: 0xF0,
+        data: vec![0x00, 0x01, 0x4D],
+    }
+    .serialize();
+    station.write_all(&frame[..4]).await.unwrap();
+    drop(station);
+
+    assert!(matches!(
+        framed.next().await,
+        Some(Ok(Err(FrameError::ParseError)))
+    ));
+    assert!(framed.next().await.is_none());
+}
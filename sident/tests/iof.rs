@@ -0,0 +1,207 @@
+#![cfg(feature = "iof")]
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use sident::{
+    card::CardPersonalData,
+    connection::GeneralReadout,
+    errors::IofError,
+    iof::{
+        IofEvent, read_card_read_list, read_result_list, write_card_read_list, write_result_list,
+    },
+    punch::{Punch, PunchTime},
+    time::{DayOfWeek, WeekOfMonth},
+};
+
+fn event_date() -> NaiveDate {
+    return NaiveDate::from_ymd_opt(2025, 6, 14).unwrap();
+}
+
+fn event() -> IofEvent {
+    return IofEvent {
+        name: "Night & Day <sprint>".to_string(),
+        date: event_date(),
+        class_name: "H21".to_string(),
+    };
+}
+
+fn datetime(h: u32, m: u32, s: u32, ms: u32) -> NaiveDateTime {
+    return event_date().and_hms_milli_opt(h, m, s, ms).unwrap();
+}
+
+fn punch(station_code: u16, datetime: NaiveDateTime) -> Punch {
+    return Punch {
+        station_code,
+        punch_time: PunchTime::from_datetime(datetime),
+    };
+}
+
+/// Punch of a card without the day of week (e.g. Card 5)
+fn twelve_hour_punch(station_code: u16, h: u32, m: u32) -> Punch {
+    return Punch {
+        station_code,
+        punch_time: PunchTime {
+            time: NaiveTime::from_hms_opt(h, m, 0).unwrap(),
+            day: DayOfWeek::Sunday,
+            week: WeekOfMonth::Week1,
+            twelve_hour_only: true,
+            subsecond: None,
+        },
+    };
+}
+
+fn personal_data() -> CardPersonalData {
+    return CardPersonalData {
+        first_name: Some("Jan".to_string()),
+        last_name: Some("Novák".to_string()),
+        club: Some("OK <Praha> & Brno".to_string()),
+        ..Default::default()
+    };
+}
+
+fn readout() -> GeneralReadout {
+    return GeneralReadout {
+        siid: 8_000_123,
+        personal_data: Some(personal_data()),
+        clear_check: Some(punch(1, datetime(9, 58, 0, 0))),
+        start: Some(punch(10, datetime(10, 0, 0, 500))),
+        finish: Some(punch(20, datetime(10, 31, 2, 250))),
+        punches: vec![
+            punch(31, datetime(10, 5, 10, 0)),
+            punch(32, datetime(10, 12, 0, 750)),
+            punch(31, datetime(10, 20, 30, 0)),
+        ],
+    };
+}
+
+/// Readout of a card with 12h only punches crossing 12 o'clock and without a start
+fn twelve_hour_readout() -> GeneralReadout {
+    return GeneralReadout {
+        siid: 12_345,
+        personal_data: None,
+        clear_check: Some(twelve_hour_punch(1, 11, 40)),
+        start: None,
+        finish: Some(twelve_hour_punch(20, 0, 30)),
+        punches: vec![twelve_hour_punch(31, 11, 50), twelve_hour_punch(32, 0, 10)],
+    };
+}
+
+#[test]
+fn card_read_list_round_trip() {
+    let readouts = [readout(), twelve_hour_readout()];
+    let xml = write_card_read_list(event_date(), &readouts).unwrap();
+    let read = read_card_read_list(&xml).unwrap();
+
+    assert_eq!(read.len(), 2);
+    for (read, written) in read.iter().zip(&readouts) {
+        assert_eq!(read.siid, written.siid);
+        assert_eq!(read.personal_data, written.personal_data);
+        assert_eq!(
+            read.resolve_times(event_date()),
+            written.resolve_times(event_date())
+        );
+    }
+
+    // the 12h punches are written as placed after the previous punch
+    let times = read[1].resolve_times(event_date());
+    assert_eq!(times.clear_check.unwrap().datetime, datetime(11, 40, 0, 0));
+    assert_eq!(times.punches[1].datetime, datetime(12, 10, 0, 0));
+    assert_eq!(times.finish.unwrap().datetime, datetime(12, 30, 0, 0));
+    // sub seconds are kept
+    assert_eq!(read[0].start.unwrap().punch_time.subsecond, Some(128));
+}
+
+#[test]
+fn card_read_list_is_not_an_iof_document() {
+    let xml = write_card_read_list(event_date(), &[readout()]).unwrap();
+    assert!(xml.contains(r#"<CardReadList xmlns="urn:sident:card-read-list""#));
+    assert!(!xml.contains("iofVersion"));
+    assert!(!xml.contains("http://www.orienteering.org/datastandard/3.0"));
+}
+
+#[test]
+fn card_read_list_without_readouts() {
+    let xml = write_card_read_list(event_date(), &[]).unwrap();
+    assert!(read_card_read_list(&xml).unwrap().is_empty());
+}
+
+#[test]
+fn card_read_without_time_is_rejected() {
+    let xml = r#"<CardReadList><CardRead>
+        <ControlCard punchingSystem="SI">12345</ControlCard>
+        <Punch><ControlCode>31</ControlCode><Time>2025-06-14T10:00:00</Time></Punch>
+    </CardRead></CardReadList>"#;
+    let read = read_card_read_list(xml).unwrap();
+    assert_eq!(read[0].clear_check, None);
+
+    let xml = r#"<CardReadList><CardRead>
+        <ControlCard punchingSystem="SI">12345</ControlCard>
+        <ClearCheck><ControlCode>1</ControlCode></ClearCheck>
+    </CardRead></CardReadList>"#;
+    let result = read_card_read_list(xml);
+    assert!(matches!(result, Err(IofError::MissingValue("Time"))));
+}
+
+#[test]
+fn result_list_round_trip() {
+    let written = readout();
+    let xml = write_result_list(&event(), std::slice::from_ref(&written)).unwrap();
+    assert!(xml.contains("<Name>Night &amp; Day &lt;sprint&gt;</Name>"));
+
+    let read = read_result_list(&xml).unwrap();
+    assert_eq!(read.len(), 1);
+    let read = &read[0];
+    assert_eq!(read.siid, written.siid);
+    assert_eq!(read.personal_data, written.personal_data);
+
+    let read_times = read.resolve_times(event_date());
+    let written_times = written.resolve_times(event_date());
+    assert_eq!(
+        read_times.start.unwrap().datetime,
+        written_times.start.unwrap().datetime
+    );
+    assert_eq!(
+        read_times.finish.unwrap().datetime,
+        written_times.finish.unwrap().datetime
+    );
+    assert_eq!(read_times.punches, written_times.punches);
+
+    // documented losses - no station codes of the start and the finish, no clear/check
+    assert_eq!(read.start.unwrap().station_code, 0);
+    assert_eq!(read.finish.unwrap().station_code, 0);
+    assert_eq!(read.clear_check, None);
+}
+
+#[test]
+fn result_elements_are_in_the_iof_order() {
+    let xml = write_result_list(&event(), &[readout()]).unwrap();
+    let result = &xml[xml.find("<Result>").unwrap()..xml.find("</Result>").unwrap()];
+
+    // StartTime, FinishTime, Time, Status, SplitTime*, ControlCard
+    let positions: Vec<usize> = [
+        "<StartTime>",
+        "<FinishTime>",
+        "<Time>",
+        "<Status>",
+        "<SplitTime>",
+        "<ControlCard",
+    ]
+    .iter()
+    .map(|element| result.find(element).unwrap())
+    .collect();
+    assert!(positions.is_sorted(), "{result}");
+    assert!(result.rfind("</SplitTime>").unwrap() < result.find("<ControlCard").unwrap());
+}
+
+#[test]
+fn result_list_without_start_loses_punches() {
+    let written = twelve_hour_readout();
+    let xml = write_result_list(&event(), std::slice::from_ref(&written)).unwrap();
+    assert!(xml.contains("<Status>Finished</Status>"));
+    assert!(xml.contains("<ControlCode>32</ControlCode>"));
+
+    let read = read_result_list(&xml).unwrap();
+    assert_eq!(read[0].siid, written.siid);
+    assert!(read[0].start.is_none());
+    assert!(read[0].punches.is_empty());
+    assert_eq!(read[0].personal_data, None);
+}
@@ -0,0 +1,149 @@
+#![cfg(all(feature = "sim", not(target_os = "android")))]
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use futures::StreamExt;
+use sident::{
+    connection::Connection,
+    errors::NewConnectionError,
+    event::StationEvent,
+    pool::{PoolEvent, StationPool, StationPoolConfig},
+    sim::VirtualStation,
+};
+
+#[tokio::test]
+async fn idle_station_is_closed_after_receiver_is_dropped() {
+    let (mut pool, mut events) = StationPool::new(StationPoolConfig::default());
+
+    let (station, stream) = VirtualStation::new();
+    let station = tokio::spawn(station.run());
+
+    let mut stream = Some(stream);
+    pool.add_station_with("virtual", move || {
+        let stream = stream.take();
+        async move { Connection::from_stream(stream.expect("connected only once")).await }
+    });
+    assert!(matches!(
+        events.next().await,
+        Some(PoolEvent::Connected { .. })
+    ));
+
+    // no card is inserted, the pool has to close the connection without any station event
+    drop(events);
+    let result = tokio::time::timeout(Duration::from_secs(1), station).await;
+    assert!(matches!(result, Ok(Ok(Ok(())))));
+}
+
+#[tokio::test]
+async fn missing_station_is_not_retried_after_receiver_is_dropped() {
+    let config = StationPoolConfig {
+        reconnect_interval: Duration::from_secs(3600),
+    };
+    let (mut pool, mut events) = StationPool::new(config);
+
+    // the task drops the connect function (and its reference) when it ends
+    let alive = Arc::new(());
+    let reference = alive.clone();
+    pool.add_station_with("missing", move || {
+        let _reference = &reference;
+        async move {
+            return Err::<Connection<tokio::io::DuplexStream>, _>(NewConnectionError::IoError(
+                std::io::ErrorKind::NotFound.into(),
+            ));
+        }
+    });
+    assert!(matches!(
+        events.next().await,
+        Some(PoolEvent::ConnectFailed { .. })
+    ));
+
+    drop(events);
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while Arc::strong_count(&alive) > 1 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the task is still waiting for the reconnect");
+}
+
+#[tokio::test]
+async fn refresh_leaves_added_stations_alone() {
+    let (mut pool, _events) = StationPool::new(StationPoolConfig::default());
+
+    let (_station, stream) = VirtualStation::new();
+    let mut stream = Some(stream);
+    pool.add_station_with("virtual", move || {
+        let stream = stream.take();
+        async move { Connection::from_stream(stream.expect("connected only once")).await }
+    });
+
+    // "virtual" is never one of the serial ports
+    pool.refresh().unwrap();
+    assert!(pool.ports().contains(&"virtual".to_string()));
+}
+
+#[tokio::test]
+async fn connect_failure_is_reported_once_per_outage() {
+    let config = StationPoolConfig {
+        reconnect_interval: Duration::from_millis(10),
+    };
+    let (mut pool, mut events) = StationPool::new(config);
+
+    let (station, stream) = VirtualStation::new();
+    let station = tokio::spawn(station.run());
+
+    // the station is connected on the fourth attempt, every other attempt fails
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let mut stream = Some(stream);
+    pool.add_station_with("flaky", move || {
+        let stream = match counter.fetch_add(1, Ordering::SeqCst) {
+            3 => stream.take(),
+            _ => None,
+        };
+        async move {
+            return match stream {
+                Some(stream) => Connection::from_stream(stream).await,
+                None => Err(NewConnectionError::IoError(
+                    std::io::ErrorKind::NotFound.into(),
+                )),
+            };
+        }
+    });
+
+    assert!(matches!(
+        events.next().await,
+        Some(PoolEvent::ConnectFailed { .. })
+    ));
+    assert!(matches!(
+        events.next().await,
+        Some(PoolEvent::Connected { .. })
+    ));
+    assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+    // the station is gone, the next outage is reported again
+    station.abort();
+    assert!(matches!(
+        events.next().await,
+        Some(PoolEvent::Station {
+            event: StationEvent::Disconnected(_),
+            ..
+        })
+    ));
+    assert!(matches!(
+        events.next().await,
+        Some(PoolEvent::ConnectFailed { .. })
+    ));
+
+    // the pool keeps retrying without any event
+    let next = tokio::time::timeout(Duration::from_millis(200), events.next()).await;
+    assert!(next.is_err());
+    assert!(attempts.load(Ordering::SeqCst) > 6);
+}
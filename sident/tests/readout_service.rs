@@ -0,0 +1,61 @@
+#![cfg(feature = "sim")]
+
+mod common;
+
+use std::{collections::BTreeMap, time::Duration};
+
+use sident::{
+    connection::{ReadoutEvent, ReadoutService, ReadoutServiceConfig},
+    sim::VirtualCard,
+};
+
+use sident_core::fixtures::card6_blocks;
+
+use common::connect;
+
+#[tokio::test]
+async fn inserted_card_is_read_out() {
+    let (conn, handle) = connect().await;
+    let (service, mut events) = ReadoutService::new(conn, ReadoutServiceConfig::default());
+    let service = tokio::spawn(service.run());
+
+    let blocks = card6_blocks(600_123, 3600, 7200, &[(31, 4000)]);
+    handle.insert_card(VirtualCard::new(600_123, blocks));
+
+    let ReadoutEvent::Completed(readout) = events.recv().await.unwrap() else {
+        panic!("readout failed");
+    };
+    assert_eq!(readout.siid, 600_123);
+    assert_eq!(readout.punches[0].station_code, 31);
+
+    drop(events);
+    let result = tokio::time::timeout(Duration::from_secs(1), service).await;
+    assert!(matches!(result, Ok(Ok(Ok(())))));
+}
+
+#[tokio::test]
+async fn service_stops_while_waiting_for_card() {
+    let (conn, _handle) = connect().await;
+    let (service, events) = ReadoutService::new(conn, ReadoutServiceConfig::default());
+    let service = tokio::spawn(service.run());
+
+    // no card is inserted, the service has to notice the dropped receiver by itself
+    drop(events);
+    let result = tokio::time::timeout(Duration::from_secs(1), service).await;
+    assert!(matches!(result, Ok(Ok(Ok(())))));
+}
+
+#[tokio::test]
+async fn failed_readout_is_reported() {
+    let (conn, handle) = connect().await;
+    let (service, mut events) = ReadoutService::new(conn, ReadoutServiceConfig::default());
+    tokio::spawn(service.run());
+
+    // no blocks - the station answers with NAK
+    handle.insert_card(VirtualCard::new(2_000_123, BTreeMap::new()));
+
+    let ReadoutEvent::Failed { siid, .. } = events.recv().await.unwrap() else {
+        panic!("readout of a card without blocks completed");
+    };
+    assert_eq!(siid, 2_000_123);
+}
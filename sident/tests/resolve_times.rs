@@ -0,0 +1,162 @@
+mod common;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use sident::{
+    connection::GeneralReadout,
+    punch::{Punch, PunchTime},
+    time::WeekOfMonth,
+};
+
+fn datetime(day: u32, h: u32, m: u32, s: u32) -> NaiveDateTime {
+    return NaiveDate::from_ymd_opt(2025, 6, day)
+        .unwrap()
+        .and_hms_opt(h, m, s)
+        .unwrap();
+}
+
+/// 12h only punch (e.g. SI5)
+fn twelve_hour(station_code: u16, h: u32, m: u32) -> Punch {
+    return Punch {
+        station_code,
+        punch_time: PunchTime {
+            time: NaiveTime::from_hms_opt(h, m, 0).unwrap(),
+            twelve_hour_only: true,
+            ..Default::default()
+        },
+    };
+}
+
+fn full(station_code: u16, datetime: NaiveDateTime) -> Punch {
+    return Punch {
+        station_code,
+        punch_time: PunchTime::from_datetime(datetime),
+    };
+}
+
+/// Saturday of the last week of the 4-week counter in June 2025
+fn last_week_saturday() -> NaiveDate {
+    return NaiveDate::from_ymd_opt(2025, 6, 1)
+        .unwrap()
+        .iter_days()
+        .find(|date| {
+            date.weekday() == Weekday::Sat && WeekOfMonth::for_date(*date) == WeekOfMonth::Week4
+        })
+        .unwrap();
+}
+
+#[test]
+fn twelve_hour_punches_over_noon_and_midnight() {
+    // the race starts before noon and the finish is after midnight
+    let readout = GeneralReadout {
+        siid: 12_345,
+        personal_data: None,
+        clear_check: Some(twelve_hour(3, 11, 40)),
+        start: Some(twelve_hour(1, 11, 55)),
+        finish: Some(twelve_hour(2, 0, 10)),
+        punches: vec![
+            twelve_hour(31, 0, 5),
+            twelve_hour(32, 6, 0),
+            twelve_hour(33, 11, 58),
+        ],
+    };
+
+    let times = readout.resolve_times(NaiveDate::from_ymd_opt(2025, 6, 14).unwrap());
+    assert_eq!(times.clear_check.unwrap().datetime, datetime(14, 11, 40, 0));
+    assert_eq!(times.start.unwrap().datetime, datetime(14, 11, 55, 0));
+    let punches: Vec<_> = times.punches.iter().map(|punch| punch.datetime).collect();
+    assert_eq!(
+        punches,
+        [
+            datetime(14, 12, 5, 0),
+            datetime(14, 18, 0, 0),
+            datetime(14, 23, 58, 0)
+        ]
+    );
+    assert_eq!(times.finish.unwrap().datetime, datetime(15, 0, 10, 0));
+    assert_eq!(times.finish.unwrap().station_code, 2);
+}
+
+#[test]
+fn first_punch_is_placed_without_clear_check() {
+    // the card was not checked, the start is placed nearest to noon instead
+    let readout = GeneralReadout {
+        siid: 12_345,
+        personal_data: None,
+        clear_check: None,
+        start: Some(twelve_hour(1, 10, 0)),
+        finish: Some(twelve_hour(2, 1, 30)),
+        punches: vec![twelve_hour(31, 11, 0)],
+    };
+
+    let times = readout.resolve_times(NaiveDate::from_ymd_opt(2025, 6, 14).unwrap());
+    assert_eq!(times.clear_check, None);
+    assert_eq!(times.start.unwrap().datetime, datetime(14, 10, 0, 0));
+    assert_eq!(times.punches[0].datetime, datetime(14, 11, 0, 0));
+    assert_eq!(times.finish.unwrap().datetime, datetime(14, 13, 30, 0));
+}
+
+#[test]
+fn full_time_punches_over_the_week_counter_wrap() {
+    let saturday = last_week_saturday();
+    let check = saturday.and_hms_opt(22, 0, 0).unwrap();
+    let punches = [
+        check + Duration::minutes(90),
+        check + Duration::minutes(125),
+        check + Duration::hours(26),
+    ];
+    let readout = GeneralReadout {
+        siid: 8_000_123,
+        personal_data: None,
+        clear_check: Some(full(3, check)),
+        start: Some(full(1, check + Duration::minutes(30))),
+        finish: Some(full(2, check + Duration::hours(27))),
+        punches: punches.iter().map(|datetime| full(31, *datetime)).collect(),
+    };
+    assert_eq!(readout.punches[1].punch_time.week, WeekOfMonth::Week1);
+
+    // the event date is the Sunday after the check, the check is still placed on Saturday
+    let times = readout.resolve_times(saturday.succ_opt().unwrap());
+    assert_eq!(times.clear_check.unwrap().datetime, check);
+    assert_eq!(times.start.unwrap().datetime, check + Duration::minutes(30));
+    let resolved: Vec<_> = times.punches.iter().map(|punch| punch.datetime).collect();
+    assert_eq!(resolved, punches);
+    assert_eq!(times.finish.unwrap().datetime, check + Duration::hours(27));
+}
+
+#[cfg(feature = "sim")]
+#[tokio::test]
+async fn station_punch_over_the_week_counter_wrap() {
+    use sident::{
+        ProtocolConfig, addr_len::presets::SystemConfigAddrLen, event::StationEvent,
+        sim::VirtualStation,
+    };
+
+    let mut values = VirtualStation::default_system_values();
+    values[SystemConfigAddrLen::protocol_config().address()] |=
+        ProtocolConfig::AUTO_SEND_OUT.bits();
+    let (mut conn, handle) = common::connect_with(values).await;
+
+    // the station clock runs from the last week into the first one (the sub seconds are in 1/256 s)
+    let set = last_week_saturday()
+        .and_hms_milli_opt(23, 59, 59, 800)
+        .unwrap();
+    conn.set_time(set).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    handle.punch(8_000_123);
+
+    let StationEvent::Punch(record) = conn.next_event().await else {
+        panic!("punch expected");
+    };
+    assert_eq!(record.punch.punch_time.week, WeekOfMonth::Week1);
+
+    let resolved = record
+        .punch
+        .punch_time
+        .resolve(set.date().and_hms_opt(12, 0, 0).unwrap());
+    let difference = resolved - set;
+    assert!(
+        difference >= Duration::milliseconds(250) && difference < Duration::milliseconds(600),
+        "{resolved} is not just after {set}"
+    );
+    assert_eq!(resolved.date(), set.date().succ_opt().unwrap());
+}
@@ -0,0 +1,216 @@
+use chrono::{Duration, NaiveDate};
+use sident::{
+    connection::GeneralReadout,
+    punch::{Punch, PunchTime},
+    results::{ControlOrder, Course, Evaluation, evaluate},
+};
+
+fn punch(station_code: u16, minute: u32) -> Punch {
+    let datetime = NaiveDate::from_ymd_opt(2025, 6, 14)
+        .unwrap()
+        .and_hms_opt(10, minute, 0)
+        .unwrap();
+    return Punch {
+        station_code,
+        punch_time: PunchTime::from_datetime(datetime),
+    };
+}
+
+/// Readout started at 10:00 and finished at 10:59, one punch per minute starting at 10:01
+fn readout(punched: &[u16]) -> GeneralReadout {
+    return GeneralReadout {
+        siid: 8_000_123,
+        personal_data: None,
+        clear_check: Some(punch(1, 0)),
+        start: Some(punch(10, 0)),
+        finish: Some(punch(20, 59)),
+        punches: punched
+            .iter()
+            .enumerate()
+            .map(|(i, code)| punch(*code, i as u32 + 1))
+            .collect(),
+    };
+}
+
+/// Codes of the splits and the codes of the matched punches
+fn split_codes(evaluation: &Evaluation) -> (Vec<u16>, Vec<Option<u16>>) {
+    return evaluation
+        .splits
+        .iter()
+        .map(|split| (split.control, split.punch.map(|p| p.station_code)))
+        .unzip();
+}
+
+/// Butterfly course with the center 50 - fixed start 31, loops (41, 42), (43, 44), (45, 46) and fixed end 32
+fn butterfly() -> Course {
+    return Course::new(vec![31, 50, 41, 42, 50, 43, 44, 50, 45, 46, 50, 32]);
+}
+
+#[test]
+fn ordered_run() {
+    let evaluation = evaluate(
+        &readout(&[31, 99, 32, 33]),
+        &Course::new(vec![31, 32, 33]),
+        ControlOrder::Ordered,
+    );
+
+    assert!(evaluation.is_complete());
+    assert_eq!(evaluation.extra, vec![punch(99, 2)]);
+    assert_eq!(evaluation.total_time, Some(Duration::minutes(59)));
+
+    let legs: Vec<_> = evaluation.splits.iter().map(|split| split.leg).collect();
+    assert_eq!(
+        legs,
+        [
+            Some(Duration::minutes(1)),
+            Some(Duration::minutes(2)),
+            Some(Duration::minutes(1))
+        ]
+    );
+    assert_eq!(evaluation.splits[2].elapsed, Some(Duration::minutes(4)));
+}
+
+#[test]
+fn ordered_run_with_wrong_order_and_missing_control() {
+    let evaluation = evaluate(
+        &readout(&[31, 33, 32]),
+        &Course::new(vec![31, 32, 33, 34]),
+        ControlOrder::Ordered,
+    );
+
+    assert!(!evaluation.is_complete());
+    assert_eq!(evaluation.wrong_order, vec![32]);
+    assert_eq!(evaluation.missing, vec![34]);
+    assert!(evaluation.extra.is_empty());
+
+    // the leg after a missing punch is unknown
+    let (_, punches) = split_codes(&evaluation);
+    assert_eq!(punches, [Some(31), None, Some(33), None]);
+    assert_eq!(evaluation.splits[2].leg, None);
+    assert_eq!(evaluation.splits[2].elapsed, Some(Duration::minutes(2)));
+}
+
+#[test]
+fn ordered_run_with_duplicate_punches() {
+    let evaluation = evaluate(
+        &readout(&[31, 31, 32, 31, 33, 32]),
+        &Course::new(vec![31, 32, 33]),
+        ControlOrder::Ordered,
+    );
+
+    assert!(evaluation.is_complete());
+    // punches of the course controls are not extra, even if they are not used
+    assert!(evaluation.extra.is_empty());
+    let (_, punches) = split_codes(&evaluation);
+    assert_eq!(punches, [Some(31), Some(32), Some(33)]);
+}
+
+#[test]
+fn score_run() {
+    let evaluation = evaluate(
+        &readout(&[33, 31, 99, 33, 32]),
+        &Course::new(vec![31, 32, 33, 34]),
+        ControlOrder::ScoreO,
+    );
+
+    // in the punch order, the first punch of the control counts
+    let (controls, punches) = split_codes(&evaluation);
+    assert_eq!(controls, [33, 31, 32, 34]);
+    assert_eq!(punches, [Some(33), Some(31), Some(32), None]);
+    assert_eq!(evaluation.splits[0].elapsed, Some(Duration::minutes(1)));
+    assert_eq!(evaluation.splits[2].leg, Some(Duration::minutes(3)));
+
+    assert_eq!(evaluation.missing, vec![34]);
+    assert!(evaluation.wrong_order.is_empty());
+    assert_eq!(evaluation.extra, vec![punch(99, 3)]);
+}
+
+#[test]
+fn butterfly_run_in_any_loop_order() {
+    let punched = [31, 50, 45, 46, 50, 41, 42, 50, 43, 44, 50, 32];
+    let evaluation = evaluate(
+        &readout(&punched),
+        &butterfly(),
+        ControlOrder::Butterfly { center: 50 },
+    );
+
+    assert!(evaluation.is_complete());
+    let (controls, punches) = split_codes(&evaluation);
+    assert_eq!(controls, punched);
+    assert!(punches.iter().all(Option::is_some));
+    assert!(
+        evaluation
+            .splits
+            .iter()
+            .all(|split| split.leg == Some(Duration::minutes(1)))
+    );
+}
+
+#[test]
+fn butterfly_run_with_wrong_order_in_loop() {
+    let evaluation = evaluate(
+        &readout(&[31, 50, 44, 43, 50, 41, 42, 50, 45, 46, 50, 32]),
+        &butterfly(),
+        ControlOrder::Butterfly { center: 50 },
+    );
+
+    assert_eq!(evaluation.wrong_order, vec![43]);
+    assert!(evaluation.missing.is_empty());
+    let (controls, _) = split_codes(&evaluation);
+    assert_eq!(controls, [31, 50, 43, 44, 50, 41, 42, 50, 45, 46, 50, 32]);
+}
+
+#[test]
+fn butterfly_run_with_skipped_loop_and_duplicate_punches() {
+    let evaluation = evaluate(
+        &readout(&[31, 50, 43, 43, 44, 50, 41, 42, 50, 32]),
+        &butterfly(),
+        ControlOrder::Butterfly { center: 50 },
+    );
+
+    // the loop which was not run is at the end
+    let (controls, _) = split_codes(&evaluation);
+    assert_eq!(controls, [31, 50, 43, 44, 50, 41, 42, 50, 45, 46, 50, 32]);
+    // one visit of the center is missing with the loop
+    assert_eq!(evaluation.missing, vec![45, 46, 50]);
+    assert!(evaluation.wrong_order.is_empty());
+    assert!(evaluation.extra.is_empty());
+}
+
+#[test]
+fn butterfly_with_many_loops() {
+    // 12 loops - every permutation of the loops would be 479 001 600 courses
+    let loops: Vec<[u16; 2]> = (0..12).map(|i| [100 + 2 * i, 101 + 2 * i]).collect();
+    let mut controls = vec![31];
+    for controls_of_loop in &loops {
+        controls.push(50);
+        controls.extend(controls_of_loop);
+    }
+    controls.extend([50, 32]);
+
+    let mut punched = vec![31];
+    for controls_of_loop in loops.iter().rev() {
+        punched.push(50);
+        punched.extend(controls_of_loop);
+    }
+    punched.extend([50, 32]);
+
+    let evaluation = evaluate(
+        &readout(&punched),
+        &Course::new(controls),
+        ControlOrder::Butterfly { center: 50 },
+    );
+    assert!(evaluation.is_complete());
+    let (controls, _) = split_codes(&evaluation);
+    assert_eq!(controls, punched);
+}
+
+#[test]
+fn butterfly_without_center_is_ordered() {
+    let course = Course::new(vec![31, 32, 33]);
+    let readout = readout(&[31, 33, 32]);
+    assert_eq!(
+        evaluate(&readout, &course, ControlOrder::Butterfly { center: 50 }),
+        evaluate(&readout, &course, ControlOrder::Ordered)
+    );
+}
@@ -0,0 +1,228 @@
+#![cfg(feature = "sim")]
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use futures::StreamExt;
+use sident::{
+    ProtocolConfig,
+    addr_len::presets::SystemConfigAddrLen,
+    card::CardType,
+    connection::Connection,
+    errors::DeserializeRawPacketError,
+    event::StationEvent,
+    framing::consts::NAK,
+    packet::RawPacketBody,
+    sim::{VirtualCard, VirtualStation},
+};
+
+use common::{base_protocol_system_values, connect, connect_with};
+
+/// Code of the default virtual station
+const STATION_CODE: u16 = 10;
+
+/// System values with `ProtocolConfig::AUTO_SEND_OUT` set (punches are sent to the host)
+fn autosend(mut values: [u8; 128]) -> [u8; 128] {
+    values[SystemConfigAddrLen::protocol_config().address()] |=
+        ProtocolConfig::AUTO_SEND_OUT.bits();
+    return values;
+}
+
+fn card(siid: u32) -> VirtualCard {
+    return VirtualCard::new(siid, BTreeMap::new());
+}
+
+#[tokio::test]
+async fn events_come_in_order() {
+    let (mut conn, handle) = connect_with(autosend(VirtualStation::default_system_values())).await;
+
+    handle.insert_card(card(8_000_123));
+    handle.remove_card();
+    handle.punch(8_000_123);
+    handle.punch(600_123);
+
+    let mut events = conn.event_stream();
+    let StationEvent::CardInserted {
+        siid,
+        card_type,
+        station_code,
+    } = events.next().await.unwrap()
+    else {
+        panic!("card insertion expected");
+    };
+    assert_eq!(siid, 8_000_123);
+    assert_eq!(card_type, Some(CardType::ActiveCard));
+    assert_eq!(station_code, STATION_CODE);
+
+    let StationEvent::CardRemoved { siid, station_code } = events.next().await.unwrap() else {
+        panic!("card removal expected");
+    };
+    assert_eq!(siid, 8_000_123);
+    assert_eq!(station_code, STATION_CODE);
+
+    // the punches are stored one after another in the backup memory (from 0x100)
+    for (siid, memory_address) in [(8_000_123, 0x100), (600_123, 0x108)] {
+        let StationEvent::Punch(record) = events.next().await.unwrap() else {
+            panic!("punch expected");
+        };
+        assert_eq!(record.siid, siid);
+        assert_eq!(record.station_code, STATION_CODE);
+        assert_eq!(record.punch.station_code, STATION_CODE);
+        assert_eq!(record.memory_address, memory_address);
+        assert!(record.punch.punch_time.subsecond.is_some());
+        assert!(!record.punch.punch_time.twelve_hour_only);
+    }
+}
+
+#[tokio::test]
+async fn inserting_another_card_removes_the_first_one() {
+    let (mut conn, handle) = connect().await;
+
+    handle.insert_card(card(12_345));
+    handle.insert_card(card(600_123));
+
+    let mut events = conn.event_stream();
+    assert!(matches!(
+        events.next().await.unwrap(),
+        StationEvent::CardInserted {
+            siid: 12_345,
+            card_type: Some(CardType::Card5),
+            ..
+        }
+    ));
+    assert!(matches!(
+        events.next().await.unwrap(),
+        StationEvent::CardRemoved { siid: 12_345, .. }
+    ));
+    assert!(matches!(
+        events.next().await.unwrap(),
+        StationEvent::CardInserted {
+            siid: 600_123,
+            card_type: Some(CardType::Card6),
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn punch_is_sent_only_in_autosend_mode() {
+    let (mut conn, handle) = connect().await;
+
+    // the default station does not send the punch, the removal is the first event
+    handle.punch(8_000_123);
+    handle.insert_card(card(8_000_123));
+    handle.remove_card();
+
+    assert!(matches!(
+        conn.next_event().await,
+        StationEvent::CardInserted { .. }
+    ));
+    assert!(matches!(
+        conn.next_event().await,
+        StationEvent::CardRemoved { .. }
+    ));
+}
+
+#[tokio::test]
+async fn base_protocol_punch_has_no_subsecond() {
+    let (mut conn, handle) = connect_with(autosend(base_protocol_system_values())).await;
+
+    // the old station does not detect newer cards
+    handle.insert_card(card(8_000_123));
+    handle.punch(8_000_123);
+
+    let StationEvent::Punch(record) = conn.next_event().await else {
+        panic!("punch expected");
+    };
+    assert_eq!(record.siid, 8_000_123);
+    assert_eq!(record.station_code, STATION_CODE);
+    assert_eq!(record.memory_address, 0x100);
+    assert_eq!(record.punch.punch_time.subsecond, None);
+}
+
+#[tokio::test]
+async fn card5_punch_has_the_card_number() {
+    // Card 5 is sent as the series and the number in the series in both protocols
+    for values in [
+        VirtualStation::default_system_values(),
+        base_protocol_system_values(),
+    ] {
+        let (mut conn, handle) = connect_with(autosend(values)).await;
+        handle.punch(312_345);
+        handle.punch(12_345);
+
+        for siid in [312_345, 12_345] {
+            let StationEvent::Punch(record) = conn.next_event().await else {
+                panic!("punch expected");
+            };
+            assert_eq!(record.siid, siid);
+        }
+    }
+}
+
+#[tokio::test]
+async fn garbage_does_not_end_the_stream() {
+    let (mut conn, handle) = connect().await;
+
+    // packet with a broken CRC
+    let mut broken = RawPacketBody {
+        id: 0xE8,
+        data: vec![0x00, 0x0A, 0x00, 0x7A, 0x1E, 0x7B],
+    }
+    .serialize();
+    let crc = broken.len() - 2;
+    broken[crc] ^= 0xFF;
+    handle.send_raw(broken);
+    handle.send_raw(vec![NAK]);
+    // valid packet which is not an event
+    handle.send_raw(
+        RawPacketBody {
+            id: 0xF0,
+            data: vec![0x00, 0x0A, 0x4D],
+        }
+        .serialize(),
+    );
+    handle.insert_card(card(8_000_123));
+
+    let mut events = conn.event_stream();
+    assert!(matches!(
+        events.next().await.unwrap(),
+        StationEvent::Garbage(DeserializeRawPacketError::CrcError)
+    ));
+    assert!(matches!(events.next().await.unwrap(), StationEvent::Nak));
+    let StationEvent::Unexpected(body) = events.next().await.unwrap() else {
+        panic!("unexpected packet expected");
+    };
+    assert_eq!(body.id, 0xF0);
+    assert!(matches!(
+        events.next().await.unwrap(),
+        StationEvent::CardInserted {
+            siid: 8_000_123,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn stream_ends_after_disconnect() {
+    let (station, stream) = VirtualStation::new();
+    let handle = station.handle();
+    let station = tokio::spawn(station.run());
+    let mut conn = Connection::from_stream(stream).await.unwrap();
+
+    handle.insert_card(card(8_000_123));
+    let mut events = conn.event_stream();
+    assert!(matches!(
+        events.next().await.unwrap(),
+        StationEvent::CardInserted { .. }
+    ));
+
+    // the station end of the stream is dropped with the task
+    station.abort();
+    assert!(matches!(
+        events.next().await.unwrap(),
+        StationEvent::Disconnected(_)
+    ));
+    assert!(events.next().await.is_none());
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "sim")]
+
+mod common;
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use sident::{
+    errors::StationTimeError,
+    time::{DayOfWeek, StationTime, WeekOfMonth},
+};
+
+use common::{base_protocol_system_values, connect, connect_with};
+
+fn datetime(day: u32, h: u32, m: u32, s: u32, ms: u32) -> NaiveDateTime {
+    return NaiveDate::from_ymd_opt(2025, 6, day)
+        .unwrap()
+        .and_hms_milli_opt(h, m, s, ms)
+        .unwrap();
+}
+
+/// The station clock runs on, so the read time is a bit later than the set one
+fn assert_close(read: NaiveDateTime, expected: NaiveDateTime) {
+    let difference = read - expected;
+    assert!(
+        difference >= TimeDelta::zero() && difference < TimeDelta::milliseconds(200),
+        "{read} is not close to {expected}"
+    );
+}
+
+#[test]
+fn station_time_round_trip() {
+    for datetime in [
+        datetime(14, 0, 0, 0, 0),
+        datetime(14, 11, 59, 59, 500),
+        datetime(14, 12, 0, 0, 0),
+        datetime(15, 23, 59, 59, 996),
+    ] {
+        let time = StationTime::from_datetime(datetime);
+        assert_eq!(StationTime::deserialize(&time.serialize()).unwrap(), time);
+    }
+}
+
+#[test]
+fn station_time_bytes() {
+    // Saturday afternoon with a half second
+    let time = StationTime::from_datetime(datetime(14, 15, 30, 45, 500));
+    assert_eq!(time.day, DayOfWeek::Saturday);
+    assert_eq!(time.subsecond, 128);
+
+    let bytes = time.serialize();
+    assert_eq!(bytes[0..3], [25, 6, 14]);
+    // day of week (bits 3..1) and the 12h flag (bit 0)
+    assert_eq!(bytes[3] & 0b1111, 0b1101);
+    // seconds of the 12h half
+    assert_eq!(
+        u16::from_be_bytes([bytes[4], bytes[5]]),
+        3 * 3600 + 30 * 60 + 45
+    );
+    assert_eq!(bytes[6], 128);
+
+    // the morning has the same seconds, only without the 12h flag
+    let morning = StationTime::from_datetime(datetime(14, 3, 30, 45, 500)).serialize();
+    assert_eq!(morning[3], bytes[3] & !1);
+    assert_eq!(morning[4..], bytes[4..]);
+}
+
+#[tokio::test]
+async fn set_and_get_time() {
+    let (mut conn, _handle) = connect().await;
+
+    // afternoon, with sub seconds
+    let set = datetime(14, 15, 30, 45, 500);
+    let time = conn.set_time(set).await.unwrap();
+    assert_close(time.datetime, set);
+    assert_eq!(time.day, DayOfWeek::Saturday);
+    assert_eq!(time.week, WeekOfMonth::for_date(set.date()));
+
+    let clock = conn.get_time().await.unwrap();
+    assert_close(clock.time.datetime, set);
+    assert_eq!(clock.time.datetime.hour(), 15);
+    assert_eq!(clock.time.day, DayOfWeek::Saturday);
+
+    // the drift is computed against the host clock
+    let expected_drift = (set - Local::now().naive_local()).num_milliseconds();
+    assert!((clock.drift_ms - expected_drift).abs() < 200);
+}
+
+#[tokio::test]
+async fn set_time_over_midnight() {
+    let (mut conn, _handle) = connect().await;
+
+    // the station clock runs over midnight into Sunday
+    conn.set_time(datetime(14, 23, 59, 59, 900)).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    let clock = conn.get_time().await.unwrap();
+    assert_eq!(clock.time.datetime.date(), datetime(15, 0, 0, 0, 0).date());
+    assert_eq!(clock.time.datetime.hour(), 0);
+    assert_eq!(clock.time.day, DayOfWeek::Sunday);
+}
+
+#[tokio::test]
+async fn time_out_of_range_is_rejected() {
+    let (mut conn, _handle) = connect().await;
+    let result = conn
+        .set_time(NaiveDate::from_ymd_opt(1999, 12, 31).unwrap().into())
+        .await;
+    assert!(matches!(result, Err(StationTimeError::OutOfRange(_))));
+}
+
+#[tokio::test]
+async fn time_is_not_available_in_base_protocol() {
+    let (mut conn, _handle) = connect_with(base_protocol_system_values()).await;
+    let result = conn.get_time().await;
+    assert!(matches!(result, Err(StationTimeError::BaseNotSupported)));
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "sim")]
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use sident::{
+    card::CardPersonalData,
+    carddef::{si6::Card6Def, si10::Card10Def},
+    connection::ReadoutPreference,
+    errors::WritePersonalDataError,
+    packet::StationboundPacket,
+    packets::stationbound::{WriteSICard6, WriteSICardNewer},
+    sim::VirtualCard,
+};
+
+use sident_core::fixtures::{
+    card6_blocks, newer_card_block0, si10_block3, with_personal_data, write_punches,
+};
+
+use common::connect;
+
+fn personal_data() -> CardPersonalData {
+    return CardPersonalData {
+        first_name: Some("Petr".to_string()),
+        last_name: Some("Svoboda".to_string()),
+        club: Some("SK Brno".to_string()),
+        ..Default::default()
+    };
+}
+
+fn card10_blocks(siid: u32) -> BTreeMap<u8, [u8; 128]> {
+    let mut block4 = [0xEE; 128];
+    write_punches(&mut block4, 0x00, 4, &[(31, 4000), (32, 5000)]);
+    let mut blocks = BTreeMap::from([
+        (0, newer_card_block0(siid, 3600, 7200, 2)),
+        (3, si10_block3()),
+        (4, block4),
+    ]);
+    // the rest of the personal data and the unused punch blocks
+    for block_number in [1, 2, 5, 6, 7] {
+        blocks.insert(block_number, [0xEE; 128]);
+    }
+    return blocks;
+}
+
+#[test]
+fn write_packets_carry_the_whole_block() {
+    let data: [u8; 128] = core::array::from_fn(|i| i as u8);
+
+    let payload = WriteSICardNewer {
+        block_number: 1,
+        data,
+    }
+    .payload();
+    assert_eq!(payload.len(), 129);
+    assert_eq!(payload[0], 1);
+    assert_eq!(payload[1..], data);
+
+    let payload = WriteSICard6 {
+        block_number: 6,
+        data,
+    }
+    .payload();
+    assert_eq!(payload.len(), 129);
+    assert_eq!(payload[0], 6);
+    assert_eq!(payload[1..], data);
+
+    // STX, command, length, payload, CRC, ETX
+    let frame = WriteSICardNewer {
+        block_number: 1,
+        data,
+    }
+    .serialize();
+    assert_eq!(frame.len(), 3 + 129 + 3);
+    assert_eq!(frame[1..3], [0xEA, 129]);
+    let frame = WriteSICard6 {
+        block_number: 1,
+        data,
+    }
+    .serialize();
+    assert_eq!(frame[1..3], [0xE2, 129]);
+}
+
+#[tokio::test]
+async fn card10_personal_data_are_written() {
+    let (mut conn, handle) = connect().await;
+    let siid = 7_000_123;
+    let blocks = card10_blocks(siid);
+    handle.insert_card(VirtualCard::new(siid, blocks.clone()));
+    assert_eq!(conn.wait_for_card_insert().await.unwrap(), siid);
+
+    // the station rejects writes without the whole block and the written blocks are verified
+    // by reading them back (block 1 holds the end of the personal data region)
+    conn.write_personal_data(siid, &personal_data())
+        .await
+        .unwrap();
+
+    let result = conn
+        .read_out(&ReadoutPreference::all(), siid)
+        .await
+        .unwrap();
+    let readout = result.to_general_readout().unwrap();
+    assert_eq!(readout.personal_data, Some(personal_data()));
+    assert_eq!(readout.punches.len(), 2);
+
+    // only the personal data regions were changed, the rest of the blocks stays
+    let image = result.to_image().unwrap();
+    let expected = with_personal_data::<Card10Def>(&blocks, &personal_data());
+    for (block_number, block) in &image.blocks {
+        assert_eq!(block, &expected[block_number], "block {block_number}");
+    }
+    assert!(image.blocks.contains_key(&0));
+}
+
+#[tokio::test]
+async fn card6_personal_data_are_written() {
+    let (mut conn, handle) = connect().await;
+    let siid = 600_123;
+    let blocks = card6_blocks(siid, 3600, 7200, &[(31, 4000)]);
+    handle.insert_card(VirtualCard::new(siid, blocks.clone()));
+    assert_eq!(conn.wait_for_card_insert().await.unwrap(), siid);
+
+    conn.write_personal_data(siid, &personal_data())
+        .await
+        .unwrap();
+
+    let result = conn
+        .read_out(&ReadoutPreference::all(), siid)
+        .await
+        .unwrap();
+    let readout = result.to_general_readout().unwrap();
+    assert_eq!(readout.personal_data, Some(personal_data()));
+
+    let image = result.to_image().unwrap();
+    let expected = with_personal_data::<Card6Def>(&blocks, &personal_data());
+    assert!(image.blocks.contains_key(&1));
+    for (block_number, block) in &image.blocks {
+        assert_eq!(block, &expected[block_number], "block {block_number}");
+    }
+}
+
+#[tokio::test]
+async fn card5_personal_data_are_not_supported() {
+    let (mut conn, _handle) = connect().await;
+    let result = conn.write_personal_data(12_345, &personal_data()).await;
+    assert!(matches!(
+        result,
+        Err(WritePersonalDataError::CardNotSupported(_))
+    ));
+}